@@ -1,7 +1,20 @@
 //! This module provides the primitives used in the babylon programs.
 
+pub mod btcstaking;
+pub mod checkpoint;
+pub mod finality;
+pub mod ibc;
+pub mod keys;
+pub mod merkle;
+pub mod misbehaviour;
+pub mod predicate;
+pub mod tx;
+
+use crate::babylon::predicate::{Predicate, PredicateOutput};
+use crate::mmr::{MerkleMountainRange, MmrProof};
 use crate::sha256_hash;
 use bincode::{Decode, Encode};
+use core::time::Duration;
 use ibc_core_commitment_types::commitment::CommitmentRoot;
 use ibc_core_commitment_types::merkle::{MerklePath, MerkleProof};
 use ibc_core_commitment_types::proto::ics23::HostFunctionsManager;
@@ -9,12 +22,16 @@ use ibc_core_commitment_types::specs::ProofSpecs;
 use ibc_core_host_types::path::PathBytes;
 use ibc_proto::Protobuf;
 use serde::{Deserialize, Serialize};
-use tendermint_light_client_verifier::types::LightBlock;
+use tendermint_light_client_verifier::options::Options;
+use tendermint_light_client_verifier::types::{LightBlock, TrustThreshold};
+use tendermint_light_client_verifier::{ProdVerifier, Verdict, Verifier};
 
 /// Output data committed by the Tendermint light client proof.
 // TODO: remove unused fields.
 #[derive(Encode, Decode, Serialize, Deserialize, Clone, Default, Debug)]
 pub struct TendermintOutput {
+    /// Version of the layout, see [`TendermintOutput::VERSION`].
+    pub version: u8,
     /// Height of the last trusted block, used as the root of trust for verification.
     pub trusted_height: u64,
     /// Height of the block whose validity was proven by this proof.
@@ -27,19 +44,38 @@ pub struct TendermintOutput {
     pub compressed_block_public_input: [u8; 32],
     /// Application state root in the verified header.
     pub app_hash: [u8; 32],
+    /// Root of the MMR over all proven headers, see [`ProvenHeader::mmr_leaf`].
+    pub header_mmr_root: [u8; 32],
+    /// Hash of the validator set of the target block.
+    pub validators_hash: [u8; 32],
+    /// Hash of the validator set of the block following the target block.
+    pub next_validators_hash: [u8; 32],
+    /// Time of the target block, in seconds since the Unix epoch.
+    pub time: i64,
+    /// Total voting power of the validator set of the target block.
+    pub total_voting_power: u64,
 }
 
 impl TendermintOutput {
-    const BYTE_SIZE: usize = 8 + 8 + 32 + 32 + 32 + 32;
+    /// Current version of the layout, bumped whenever fields are added or removed.
+    pub const VERSION: u8 = 1;
+
+    const BYTE_SIZE: usize = 1 + 8 + 8 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8;
 
     pub fn encode(&self) -> Vec<u8> {
         let mut out = Vec::with_capacity(Self::BYTE_SIZE);
+        out.push(self.version);
         out.extend_from_slice(&self.trusted_height.to_le_bytes());
         out.extend_from_slice(&self.target_height.to_le_bytes());
         out.extend_from_slice(&self.trusted_header_hash);
         out.extend_from_slice(&self.target_header_hash);
         out.extend_from_slice(&self.compressed_block_public_input);
         out.extend_from_slice(&self.app_hash);
+        out.extend_from_slice(&self.header_mmr_root);
+        out.extend_from_slice(&self.validators_hash);
+        out.extend_from_slice(&self.next_validators_hash);
+        out.extend_from_slice(&self.time.to_le_bytes());
+        out.extend_from_slice(&self.total_voting_power.to_le_bytes());
         out
     }
 
@@ -52,26 +88,133 @@ impl TendermintOutput {
             ));
         }
 
-        let trusted_height = u64::from_le_bytes(data[0..8].try_into().unwrap());
-        let target_height = u64::from_le_bytes(data[8..16].try_into().unwrap());
-        let trusted_header_hash = data[16..48].try_into().unwrap();
-        let target_header_hash = data[48..80].try_into().unwrap();
-        let compressed_block_public_input = data[80..112].try_into().unwrap();
-        let app_hash = data[112..144].try_into().unwrap();
+        let version = data[0];
+        if version != Self::VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Unsupported TendermintOutput version {version}"),
+            ));
+        }
+
+        let trusted_height = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let target_height = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let trusted_header_hash = data[17..49].try_into().unwrap();
+        let target_header_hash = data[49..81].try_into().unwrap();
+        let compressed_block_public_input = data[81..113].try_into().unwrap();
+        let app_hash = data[113..145].try_into().unwrap();
+        let header_mmr_root = data[145..177].try_into().unwrap();
+        let validators_hash = data[177..209].try_into().unwrap();
+        let next_validators_hash = data[209..241].try_into().unwrap();
+        let time = i64::from_le_bytes(data[241..249].try_into().unwrap());
+        let total_voting_power = u64::from_le_bytes(data[249..257].try_into().unwrap());
 
         Ok(Self {
+            version,
             trusted_height,
             target_height,
             trusted_header_hash,
             target_header_hash,
             compressed_block_public_input,
             app_hash,
+            header_mmr_root,
+            validators_hash,
+            next_validators_hash,
+            time,
+            total_voting_power,
         })
     }
 
     pub fn compute_hash(&self) -> [u8; 32] {
         sha256_hash(&self.encode())
     }
+
+    /// Checks that `trusted_block`, from which the next block is proven, is the target block of
+    /// this output, so that each proof extends the chain of headers proven by its parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is not the target block.
+    pub fn verify_target_block(&self, trusted_block: &LightBlock) {
+        assert_eq!(
+            trusted_block.height().value(),
+            self.target_height,
+            "The trusted block is not the target of the parent proof."
+        );
+        assert_eq!(
+            light_block_hash(trusted_block),
+            self.target_header_hash,
+            "The trusted block is not the target of the parent proof."
+        );
+    }
+
+    /// Returns the header proven by this output.
+    pub fn target_header(&self) -> ProvenHeader {
+        ProvenHeader {
+            height: self.target_height,
+            header_hash: self.target_header_hash,
+            app_hash: self.app_hash,
+        }
+    }
+
+    /// Returns the `historical_header` if any, checked against the header MMR, or the target
+    /// header otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the output is not of the current [`TendermintOutput::VERSION`], or if the
+    /// historical header is not part of the proven header MMR.
+    pub fn resolve_header(&self, historical_header: Option<&HistoricalHeader>) -> ProvenHeader {
+        assert_eq!(
+            self.version,
+            Self::VERSION,
+            "Unsupported TendermintOutput version"
+        );
+        match historical_header {
+            Some(historical_header) => {
+                assert!(
+                    historical_header.verify(self.header_mmr_root),
+                    "Historical header is not part of the proven header MMR"
+                );
+                historical_header.header
+            }
+            None => self.target_header(),
+        }
+    }
+}
+
+/// A header proven by the consensus program, as accumulated in the header MMR.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct ProvenHeader {
+    pub height: u64,
+    pub header_hash: [u8; 32],
+    pub app_hash: [u8; 32],
+}
+
+impl ProvenHeader {
+    /// Computes the leaf of this header in the header MMR.
+    pub fn mmr_leaf(&self) -> [u8; 32] {
+        let mut buf = Vec::with_capacity(1 + 8 + 32 + 32);
+        buf.push(0x00);
+        buf.extend(self.height.to_le_bytes());
+        buf.extend(self.header_hash);
+        buf.extend(self.app_hash);
+        sha256_hash(&buf)
+    }
+}
+
+/// A historical header along with its proof against the header MMR of a later consensus proof.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoricalHeader {
+    pub header: ProvenHeader,
+    pub header_mmr_proof: MmrProof,
+}
+
+impl HistoricalHeader {
+    /// Returns `true` if the header is part of the MMR committed to by `header_mmr_root`.
+    pub fn verify(&self, header_mmr_root: [u8; 32]) -> bool {
+        self.header_mmr_proof
+            .verify(header_mmr_root, self.header.mmr_leaf())
+    }
 }
 
 /// Public input known to the verifier.
@@ -102,6 +245,59 @@ impl VerifierPublicInput {
     }
 }
 
+/// Returns the hash of the header of `block`.
+pub(crate) fn light_block_hash(block: &LightBlock) -> [u8; 32] {
+    block
+        .signed_header
+        .header
+        .hash()
+        .as_bytes()
+        .try_into()
+        .expect("The header hash must be a SHA256 hash.")
+}
+
+/// Verifies `untrusted_block` against `trusted_block` with the Tendermint light client.
+///
+/// For non-adjacent blocks, validators of the trusted set holding more than `trust_threshold`
+/// of its voting power must have signed the untrusted block.
+///
+/// # Panics
+///
+/// Panics if the untrusted block can not be trusted.
+pub fn verify_header(
+    trusted_block: &LightBlock,
+    untrusted_block: &LightBlock,
+    trust_threshold: TrustThreshold,
+) {
+    let vp = ProdVerifier::default();
+    // TODO: double check the values below, trusting_period in particular.
+    let opt = Options {
+        trust_threshold,
+        // 2 week trusting period.
+        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
+        clock_drift: Default::default(),
+    };
+
+    // Verify update header doesn't check this property.
+    assert_eq!(
+        trusted_block.next_validators.hash(),
+        trusted_block.as_trusted_state().next_validators_hash
+    );
+
+    let verify_time = untrusted_block.time() + Duration::from_secs(20);
+    let verdict = vp.verify_update_header(
+        untrusted_block.as_untrusted_state(),
+        trusted_block.as_trusted_state(),
+        &opt,
+        verify_time.unwrap(),
+    );
+
+    match verdict {
+        Verdict::Success => {}
+        v => panic!("Failed to verify light client update: {v:?}"),
+    }
+}
+
 /// Private input to the circuit for verifying the Tendermint light client.
 ///
 /// This input links the a previously trusted block to a new target
@@ -112,6 +308,10 @@ pub struct ConsensusWitness {
     pub trusted_block: LightBlock,
     /// The new light block that is being proven.
     pub untrusted_block: LightBlock,
+    /// Header MMR committed by the parent proof, the new block will be appended to it.
+    ///
+    /// Must be empty when proving the first block.
+    pub header_mmr: MerkleMountainRange,
 }
 
 impl ConsensusWitness {
@@ -159,6 +359,21 @@ pub struct KVPair {
 }
 
 impl KVPair {
+    /// Decodes the value as the protobuf message `T`, see [`keys`] for the message types.
+    pub fn decode_value<T: prost::Message + Default>(&self) -> Result<T, prost::DecodeError> {
+        T::decode(self.value.as_slice())
+    }
+
+    /// Returns the hash identifying the key path, each key being prefixed with its length.
+    pub fn key_hash(&self) -> [u8; 32] {
+        let mut buf = Vec::new();
+        self.keys.iter().for_each(|key| {
+            buf.extend((key.len() as u32).to_le_bytes());
+            buf.extend(key);
+        });
+        sha256_hash(&buf)
+    }
+
     pub fn into_merkle_path_and_value(self) -> (MerklePath, Vec<u8>) {
         let Self { keys, value } = self;
         (
@@ -198,15 +413,22 @@ pub fn verify_membership_proof(app_hash: [u8; 32], proofs: &[(KVPair, RawMerkleP
 pub struct MembershipInput {
     pub app_hash: [u8; 32],
     pub merkle_proofs: Vec<(KVPair, RawMerkleProof)>,
+    /// Predicates to evaluate, each on the value of the proof at the given index.
+    ///
+    /// When not empty, only the outcomes of the predicates are committed, not the key-value
+    /// pairs.
+    pub predicates: Vec<(u32, Predicate)>,
 }
 
 /// The public input committed by the zkVM.
 ///
-/// It contains the verified `app_hash` and the corresponding key-value pairs.
+/// It contains the verified `app_hash` and either the corresponding key-value pairs or the
+/// outcomes of the predicates evaluated on them.
 #[derive(Encode, Decode, Serialize, Deserialize, Debug)]
 pub struct MembershipOutput {
     pub app_hash: [u8; 32],
     pub kv_pairs: Vec<KVPair>,
+    pub predicates: Vec<PredicateOutput>,
 }
 
 impl MembershipOutput {
@@ -217,12 +439,42 @@ impl MembershipOutput {
             buf.extend(keys.iter().flatten());
             buf.extend(value);
         });
+        self.predicates.iter().for_each(|output| {
+            buf.extend(output.key_hash);
+            buf.extend(
+                bincode::encode_to_vec(&output.predicate, bincode::config::standard()).unwrap(),
+            );
+            buf.push(output.result as u8);
+        });
         sha256_hash(&buf)
     }
 
     pub fn encode(&self) -> Vec<u8> {
         bincode::encode_to_vec(self, bincode::config::standard()).unwrap()
     }
+
+    /// Returns the key-value pair of `key_path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key path is not proven by the membership proof.
+    pub fn kv_pair(&self, key_path: &[Vec<u8>]) -> &KVPair {
+        self.kv_pairs
+            .iter()
+            .find(|kv_pair| kv_pair.keys == key_path)
+            .expect("The store entry is not proven by the membership proof.")
+    }
+
+    /// Returns the value of `key_path`, decoded as the protobuf message `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key path is not proven or its value can not be decoded.
+    pub fn decode_value<T: prost::Message + Default>(&self, key_path: &[Vec<u8>]) -> T {
+        self.kv_pair(key_path)
+            .decode_value()
+            .expect("Failed to decode the store entry.")
+    }
 }
 
 /// The input to the aggregation program.
@@ -236,4 +488,116 @@ pub struct AggregationInput {
     pub membership_vkey_u32_hash: [u32; 8],
     /// Encoded [`MembershipOutput`] committed from the consensus proof.
     pub membership_public_input: Vec<u8>,
+    /// Header the membership proof was generated against, when it is older than the target
+    /// header of the consensus proof.
+    pub historical_header: Option<HistoricalHeader>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tendermint::Time;
+    use tendermint_testgen::light_block::TmLightBlock;
+    use tendermint_testgen::{
+        Commit, Generator, Header, LightBlock as TestgenLightBlock, Validator,
+    };
+
+    /// Returns the block of `validators` at `height` and `time`, signed by all of them.
+    pub(crate) fn light_block(height: u64, time: i64, validators: &[Validator]) -> LightBlock {
+        let header = Header::new(validators)
+            .height(height)
+            .chain_id("test-chain")
+            .next_validators(validators)
+            .time(Time::from_unix_timestamp(time, 0).unwrap());
+        let TmLightBlock {
+            signed_header,
+            validators,
+            next_validators,
+            provider,
+        } = TestgenLightBlock::new(header.clone(), Commit::new(header, 1))
+            .generate()
+            .unwrap();
+        LightBlock::new(signed_header, validators, next_validators, provider)
+    }
+
+    /// Returns validators with the given voting power.
+    pub(crate) fn validators(ids: &[&str], voting_power: u64) -> Vec<Validator> {
+        ids.iter()
+            .map(|id| Validator::new(id).voting_power(voting_power))
+            .collect()
+    }
+
+    fn parent_output(target_block: &LightBlock) -> TendermintOutput {
+        TendermintOutput {
+            version: TendermintOutput::VERSION,
+            target_height: target_block.height().value(),
+            target_header_hash: light_block_hash(target_block),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn trusted_block_is_the_parent_target() {
+        let block = light_block(2, 2, &validators(&["a", "b", "c"], 10));
+        parent_output(&block).verify_target_block(&block);
+    }
+
+    #[test]
+    #[should_panic(expected = "The trusted block is not the target of the parent proof.")]
+    fn rejects_a_trusted_block_of_another_validator_set() {
+        let block = light_block(2, 2, &validators(&["a", "b", "c"], 10));
+        // Same height, signed by a validator set controlled by the prover.
+        let forged = light_block(2, 2, &validators(&["forger"], 10));
+        parent_output(&block).verify_target_block(&forged);
+    }
+
+    #[test]
+    #[should_panic(expected = "The trusted block is not the target of the parent proof.")]
+    fn rejects_a_trusted_block_at_another_height() {
+        let validators = validators(&["a", "b", "c"], 10);
+        let block = light_block(2, 2, &validators);
+        parent_output(&block).verify_target_block(&light_block(1, 1, &validators));
+    }
+
+    fn tendermint_output() -> TendermintOutput {
+        TendermintOutput {
+            version: TendermintOutput::VERSION,
+            trusted_height: 1,
+            target_height: 2,
+            trusted_header_hash: [3; 32],
+            target_header_hash: [4; 32],
+            compressed_block_public_input: [5; 32],
+            app_hash: [6; 32],
+            header_mmr_root: [7; 32],
+            validators_hash: [8; 32],
+            next_validators_hash: [9; 32],
+            time: -10,
+            total_voting_power: 11,
+        }
+    }
+
+    #[test]
+    fn tendermint_output_round_trips() {
+        let output = tendermint_output();
+        let encoded = output.encode();
+        assert_eq!(encoded.len(), TendermintOutput::BYTE_SIZE);
+        assert_eq!(
+            TendermintOutput::decode(&encoded).unwrap().encode(),
+            encoded
+        );
+    }
+
+    #[test]
+    fn rejects_other_tendermint_output_versions() {
+        let mut encoded = tendermint_output().encode();
+        encoded[0] = 0;
+        assert!(TendermintOutput::decode(&encoded).is_err());
+        assert!(TendermintOutput::decode(&encoded[1..]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported TendermintOutput version")]
+    fn resolve_header_checks_the_version() {
+        TendermintOutput::default().resolve_header(None);
+    }
 }
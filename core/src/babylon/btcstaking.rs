@@ -0,0 +1,300 @@
+//! BTC delegations of the Babylon `btcstaking` module.
+//!
+//! Delegations are stored in the `btcstaking` store under the hash of their staking transaction.
+//! Their status is not stored, it is derived from the Bitcoin tip height and the covenant quorum
+//! as in `BTCDelegation::GetStatus` in Babylon. Both are proven along with the delegation, from
+//! the tip of the `btclightclient` module and the staking parameters the delegation was created
+//! under.
+
+use crate::babylon::keys::btclightclient::{BtcHeaderInfo, tip_key_path};
+use crate::babylon::keys::btcstaking::{
+    BtcDelegation, StoredParams, btc_delegation_key_path, params_key_path,
+};
+use crate::babylon::{HistoricalHeader, MembershipOutput};
+use serde::{Deserialize, Serialize};
+
+/// Returns the key paths to prove for the delegation of `staking_tx_hash`, created under the
+/// staking parameters of `params_version`.
+pub fn btc_delegation_key_paths(
+    staking_tx_hash: [u8; 32],
+    params_version: u32,
+) -> Vec<Vec<Vec<u8>>> {
+    vec![
+        btc_delegation_key_path(staking_tx_hash),
+        params_key_path(params_version),
+        tip_key_path(),
+    ]
+}
+
+/// Status of a BTC delegation (`BTCDelegationStatus` in Babylon).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BtcDelegationStatus {
+    /// Waiting for the covenant signatures, or for the stake to be active.
+    Pending,
+    /// Signed by the covenant, waiting for the inclusion proof of the staking transaction.
+    Verified,
+    /// The stake gives voting power to the finality providers.
+    Active,
+    /// The staker unbonded before the staking timelock expired.
+    Unbonded,
+    /// The staking timelock expires within the unbonding time.
+    Expired,
+}
+
+impl BtcDelegation {
+    fn is_unbonded_early(&self) -> bool {
+        self.btc_undelegation
+            .as_ref()
+            .is_some_and(|undelegation| undelegation.delegator_unbonding_info.is_some())
+    }
+
+    fn has_covenant_quorums(&self, covenant_quorum: u32) -> bool {
+        let quorum = covenant_quorum as usize;
+        self.covenant_sigs.len() >= quorum
+            && self.btc_undelegation.as_ref().is_some_and(|undelegation| {
+                undelegation.covenant_slashing_sigs.len() >= quorum
+                    && undelegation.covenant_unbonding_sig_list.len() >= quorum
+            })
+    }
+
+    fn has_inclusion_proof(&self) -> bool {
+        self.start_height > 0 && self.end_height > 0
+    }
+
+    /// Returns the status of the delegation at `btc_tip_height`, given the covenant quorum of its
+    /// staking parameters.
+    pub fn status(&self, btc_tip_height: u32, covenant_quorum: u32) -> BtcDelegationStatus {
+        if self.is_unbonded_early() {
+            return BtcDelegationStatus::Unbonded;
+        }
+        if !self.has_covenant_quorums(covenant_quorum) {
+            return BtcDelegationStatus::Pending;
+        }
+        if !self.has_inclusion_proof() {
+            return BtcDelegationStatus::Verified;
+        }
+        if btc_tip_height < self.start_height {
+            return BtcDelegationStatus::Pending;
+        }
+        if btc_tip_height.saturating_add(self.unbonding_time) > self.end_height {
+            return BtcDelegationStatus::Expired;
+        }
+        BtcDelegationStatus::Active
+    }
+}
+
+/// The input to the BTC delegation program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BtcDelegationInput {
+    /// Verification key used for the Babylon consensus proof.
+    pub consensus_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TendermintOutput`](crate::babylon::TendermintOutput) committed from the
+    /// consensus proof.
+    pub consensus_public_input: Vec<u8>,
+    /// Verification key used for the membership proof of the store entries, see
+    /// [`btc_delegation_key_paths`].
+    pub membership_vkey_u32_hash: [u32; 8],
+    /// Encoded [`MembershipOutput`](crate::babylon::MembershipOutput) committed from the
+    /// membership proof.
+    pub membership_public_input: Vec<u8>,
+    /// Header the membership proof was generated against, when it is older than the target
+    /// header of the consensus proof.
+    pub historical_header: Option<HistoricalHeader>,
+    /// Little-endian bytes of the staking transaction hash (as shown on explorers).
+    pub staking_tx_hash: [u8; 32],
+}
+
+impl BtcDelegationInput {
+    /// Returns the delegation proven by `membership`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delegation is not proven or can not be decoded.
+    pub fn btc_delegation(&self, membership: &MembershipOutput) -> BtcDelegation {
+        membership.decode_value(&btc_delegation_key_path(self.staking_tx_hash))
+    }
+
+    /// Returns the height of the Bitcoin tip of the `btclightclient` module proven by
+    /// `membership`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tip is not proven or can not be decoded.
+    pub fn btc_tip_height(&self, membership: &MembershipOutput) -> u32 {
+        membership
+            .decode_value::<BtcHeaderInfo>(&tip_key_path())
+            .height
+    }
+
+    /// Returns the covenant quorum of the staking parameters of `params_version` proven by
+    /// `membership`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters are not proven, can not be decoded, or are of another version.
+    pub fn covenant_quorum(&self, membership: &MembershipOutput, params_version: u32) -> u32 {
+        let stored_params: StoredParams = membership.decode_value(&params_key_path(params_version));
+        assert_eq!(
+            stored_params.version, params_version,
+            "The staking parameters are not of the version of the delegation."
+        );
+        stored_params
+            .params
+            .expect("The staking parameters are missing.")
+            .covenant_quorum
+    }
+}
+
+/// The public values committed by the BTC delegation program.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BtcDelegationOutput {
+    /// SHA256 of the public values of the Babylon consensus proof covering the block.
+    pub consensus_public_input_hash: [u8; 32],
+    /// Height of the block the delegation is proven at.
+    pub height: u64,
+    /// Application state root the delegation is proven against.
+    pub app_hash: [u8; 32],
+    /// Little-endian bytes of the staking transaction hash (as shown on explorers).
+    pub staking_tx_hash: [u8; 32],
+    /// Bitcoin tip height of the `btclightclient` module, used for deriving the status.
+    pub btc_tip_height: u32,
+    /// Covenant quorum of the staking parameters of the delegation, used for deriving the status.
+    pub covenant_quorum: u32,
+    pub status: BtcDelegationStatus,
+    /// Amount of staked satoshis.
+    pub total_sat: u64,
+    /// X-only public key of the staker.
+    pub staker_btc_pk: Vec<u8>,
+    /// X-only public keys of the finality providers the stake is delegated to.
+    pub fp_btc_pk_list: Vec<Vec<u8>>,
+    pub start_height: u32,
+    pub end_height: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babylon::KVPair;
+    use crate::babylon::keys::btcstaking::{BtcUndelegation, Params};
+    use prost::Message;
+
+    const COVENANT_QUORUM: u32 = 2;
+
+    /// Active delegation from height 100 until 1100, with an unbonding time of 100 blocks.
+    fn delegation() -> BtcDelegation {
+        let sigs = vec![vec![0u8; 32]; COVENANT_QUORUM as usize];
+        BtcDelegation {
+            start_height: 100,
+            end_height: 1100,
+            total_sat: 100_000,
+            covenant_sigs: sigs.clone(),
+            unbonding_time: 100,
+            btc_undelegation: Some(BtcUndelegation {
+                covenant_slashing_sigs: sigs.clone(),
+                covenant_unbonding_sig_list: sigs,
+                delegator_unbonding_info: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn undelegation(delegation: &mut BtcDelegation) -> &mut BtcUndelegation {
+        delegation.btc_undelegation.as_mut().unwrap()
+    }
+
+    #[test]
+    fn status() {
+        use BtcDelegationStatus::*;
+
+        let cases: [(&str, fn(&mut BtcDelegation), u32, BtcDelegationStatus); 9] = [
+            ("active", |_| {}, 500, Active),
+            ("active at the start height", |_| {}, 100, Active),
+            (
+                "unbonded early",
+                |delegation| undelegation(delegation).delegator_unbonding_info = Some(vec![1]),
+                500,
+                Unbonded,
+            ),
+            (
+                "covenant signatures missing",
+                |delegation| {
+                    delegation.covenant_sigs.pop();
+                },
+                500,
+                Pending,
+            ),
+            (
+                "covenant unbonding signatures missing",
+                |delegation| {
+                    undelegation(delegation).covenant_unbonding_sig_list.pop();
+                },
+                500,
+                Pending,
+            ),
+            ("tip below the start height", |_| {}, 99, Pending),
+            (
+                "no inclusion proof",
+                |delegation| {
+                    delegation.start_height = 0;
+                    delegation.end_height = 0;
+                },
+                500,
+                Verified,
+            ),
+            ("expiring within the unbonding time", |_| {}, 1001, Expired),
+            ("active until the unbonding time", |_| {}, 1000, Active),
+        ];
+
+        for (name, modify, btc_tip_height, expected) in cases {
+            let mut delegation = delegation();
+            modify(&mut delegation);
+            assert_eq!(
+                delegation.status(btc_tip_height, COVENANT_QUORUM),
+                expected,
+                "{name}"
+            );
+        }
+    }
+
+    fn input() -> BtcDelegationInput {
+        BtcDelegationInput {
+            consensus_vkey_u32_hash: [0; 8],
+            consensus_public_input: Vec::new(),
+            membership_vkey_u32_hash: [0; 8],
+            membership_public_input: Vec::new(),
+            historical_header: None,
+            staking_tx_hash: [0; 32],
+        }
+    }
+
+    fn params_membership(key_version: u32, stored_version: u32) -> MembershipOutput {
+        let stored_params = StoredParams {
+            version: stored_version,
+            params: Some(Params {
+                covenant_pks: Vec::new(),
+                covenant_quorum: COVENANT_QUORUM,
+            }),
+        };
+        MembershipOutput {
+            app_hash: [0; 32],
+            kv_pairs: vec![KVPair {
+                keys: params_key_path(key_version),
+                value: stored_params.encode_to_vec(),
+            }],
+            predicates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn covenant_quorum() {
+        let membership = params_membership(3, 3);
+        assert_eq!(input().covenant_quorum(&membership, 3), COVENANT_QUORUM);
+    }
+
+    #[test]
+    #[should_panic(expected = "The staking parameters are not of the version of the delegation.")]
+    fn covenant_quorum_rejects_params_of_another_version() {
+        let membership = params_membership(3, 2);
+        input().covenant_quorum(&membership, 3);
+    }
+}
@@ -0,0 +1,455 @@
+//! Babylon epoch checkpoints and their submission to Bitcoin.
+//!
+//! At the end of each epoch, Babylon stores a BLS-signed checkpoint in the `checkpointing` store.
+//! Submitters post it to Bitcoin in two transactions, each carrying one part of the checkpoint in
+//! an `OP_RETURN` output (`btctxformatter` in Babylon):
+//!
+//! - first part: `tag || header || epoch || block_hash || bitmap || submitter_address`
+//! - second part: `tag || header || bls_multi_sig || sha256(first part)[..10]`
+//!
+//! where the header byte holds the format version in its low nibble and the part index in its
+//! high nibble.
+
+use crate::babylon::keys::checkpointing::{
+    RawCheckpoint, RawCheckpointWithMeta, checkpoint_key_path,
+};
+use crate::babylon::{HistoricalHeader, MembershipOutput};
+use crate::bitcoin::AggregationWitness;
+use crate::bitcoin::consensus::ConsensusVerifierPublicInput;
+use crate::bitcoin::inclusion::InclusionInput;
+use crate::bitcoin::transaction::Transaction;
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+
+/// Length of the tag identifying the checkpoints of a Babylon network.
+pub const CHECKPOINT_TAG_LENGTH: usize = 4;
+/// Current version of the checkpoint format.
+pub const CHECKPOINT_FORMAT_VERSION: u8 = 0;
+
+const HEADER_LENGTH: usize = CHECKPOINT_TAG_LENGTH + 1;
+const EPOCH_LENGTH: usize = 8;
+const BLOCK_HASH_LENGTH: usize = 32;
+const BITMAP_LENGTH: usize = 13;
+const ADDRESS_LENGTH: usize = 20;
+const BLS_SIG_LENGTH: usize = 48;
+const FIRST_PART_HASH_LENGTH: usize = 10;
+
+/// Length of the first part of a checkpoint.
+pub const FIRST_PART_LENGTH: usize =
+    HEADER_LENGTH + EPOCH_LENGTH + BLOCK_HASH_LENGTH + BITMAP_LENGTH + ADDRESS_LENGTH;
+/// Length of the second part of a checkpoint.
+pub const SECOND_PART_LENGTH: usize = HEADER_LENGTH + BLS_SIG_LENGTH + FIRST_PART_HASH_LENGTH;
+
+const OP_RETURN: u8 = 0x6a;
+const OP_PUSHDATA1: u8 = 0x4c;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// A part does not have the expected length.
+    InvalidLength { part: u8, len: usize },
+    /// A part does not start with the expected tag.
+    InvalidTag,
+    /// A part has an unsupported format version.
+    UnsupportedVersion(u8),
+    /// A part has an unexpected part index.
+    InvalidPartIndex { expected: u8, found: u8 },
+    /// The second part does not commit to the first part.
+    FirstPartHashMismatch,
+}
+
+impl core::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength { part, len } => {
+                write!(f, "invalid length {len} of checkpoint part {part}")
+            }
+            Self::InvalidTag => write!(f, "invalid checkpoint tag"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint format version {version}")
+            }
+            Self::InvalidPartIndex { expected, found } => {
+                write!(f, "expected checkpoint part {expected}, found {found}")
+            }
+            Self::FirstPartHashMismatch => {
+                write!(f, "second checkpoint part does not match the first part")
+            }
+        }
+    }
+}
+
+impl core::error::Error for CheckpointError {}
+
+/// Checkpoint as submitted to Bitcoin (`RawBtcCheckpoint` in Babylon).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BtcCheckpoint {
+    pub epoch: u64,
+    /// Hash of the last block of the epoch.
+    pub block_hash: [u8; 32],
+    /// Bitmap of the validators which signed the checkpoint.
+    pub bitmap: [u8; BITMAP_LENGTH],
+    /// Babylon address of the submitter, rewarded once the checkpoint is finalized.
+    pub submitter_address: [u8; ADDRESS_LENGTH],
+    /// Aggregated BLS signature of the validators.
+    #[serde(with = "serde_bls_sig")]
+    pub bls_multi_sig: [u8; BLS_SIG_LENGTH],
+}
+
+/// Serde only supports arrays of up to 32 elements.
+mod serde_bls_sig {
+    use super::BLS_SIG_LENGTH;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(sig: &[u8; BLS_SIG_LENGTH], s: S) -> Result<S::Ok, S::Error> {
+        sig.as_slice().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[u8; BLS_SIG_LENGTH], D::Error> {
+        let sig = Vec::<u8>::deserialize(d)?;
+        sig.try_into()
+            .map_err(|_| serde::de::Error::custom("invalid BLS signature length"))
+    }
+}
+
+fn encode_header(tag: &[u8; CHECKPOINT_TAG_LENGTH], part_index: u8) -> Vec<u8> {
+    let mut bytes = tag.to_vec();
+    bytes.push(CHECKPOINT_FORMAT_VERSION | (part_index << 4));
+    bytes
+}
+
+fn check_header(
+    part: &[u8],
+    tag: &[u8; CHECKPOINT_TAG_LENGTH],
+    part_index: u8,
+) -> Result<(), CheckpointError> {
+    if !part.starts_with(tag) {
+        return Err(CheckpointError::InvalidTag);
+    }
+    let header = part[CHECKPOINT_TAG_LENGTH];
+    if header & 0x0f != CHECKPOINT_FORMAT_VERSION {
+        return Err(CheckpointError::UnsupportedVersion(header & 0x0f));
+    }
+    if header >> 4 != part_index {
+        return Err(CheckpointError::InvalidPartIndex {
+            expected: part_index,
+            found: header >> 4,
+        });
+    }
+    Ok(())
+}
+
+impl BtcCheckpoint {
+    /// Decodes a checkpoint from its two parts, checking that they carry `tag`.
+    pub fn decode(
+        tag: &[u8; CHECKPOINT_TAG_LENGTH],
+        first_part: &[u8],
+        second_part: &[u8],
+    ) -> Result<Self, CheckpointError> {
+        if first_part.len() != FIRST_PART_LENGTH {
+            return Err(CheckpointError::InvalidLength {
+                part: 0,
+                len: first_part.len(),
+            });
+        }
+        if second_part.len() != SECOND_PART_LENGTH {
+            return Err(CheckpointError::InvalidLength {
+                part: 1,
+                len: second_part.len(),
+            });
+        }
+        check_header(first_part, tag, 0)?;
+        check_header(second_part, tag, 1)?;
+
+        let (bls_multi_sig, first_part_hash) =
+            second_part[HEADER_LENGTH..].split_at(BLS_SIG_LENGTH);
+        if first_part_hash != &sha256_hash(first_part)[..FIRST_PART_HASH_LENGTH] {
+            return Err(CheckpointError::FirstPartHashMismatch);
+        }
+
+        let data = &first_part[HEADER_LENGTH..];
+        let (epoch, data) = data.split_at(EPOCH_LENGTH);
+        let (block_hash, data) = data.split_at(BLOCK_HASH_LENGTH);
+        let (bitmap, submitter_address) = data.split_at(BITMAP_LENGTH);
+
+        Ok(Self {
+            epoch: u64::from_be_bytes(epoch.try_into().expect("Length checked above; qed")),
+            block_hash: block_hash.try_into().expect("Length checked above; qed"),
+            bitmap: bitmap.try_into().expect("Length checked above; qed"),
+            submitter_address: submitter_address
+                .try_into()
+                .expect("Length checked above; qed"),
+            bls_multi_sig: bls_multi_sig.try_into().expect("Length checked above; qed"),
+        })
+    }
+
+    /// Encodes the checkpoint into its two parts.
+    pub fn encode(&self, tag: &[u8; CHECKPOINT_TAG_LENGTH]) -> (Vec<u8>, Vec<u8>) {
+        let mut first_part = encode_header(tag, 0);
+        first_part.extend_from_slice(&self.epoch.to_be_bytes());
+        first_part.extend_from_slice(&self.block_hash);
+        first_part.extend_from_slice(&self.bitmap);
+        first_part.extend_from_slice(&self.submitter_address);
+
+        let mut second_part = encode_header(tag, 1);
+        second_part.extend_from_slice(&self.bls_multi_sig);
+        second_part.extend_from_slice(&sha256_hash(&first_part)[..FIRST_PART_HASH_LENGTH]);
+
+        (first_part, second_part)
+    }
+
+    /// Returns `true` if the checkpoint matches the one signed by the Babylon validators.
+    pub fn matches(&self, raw_checkpoint: &RawCheckpoint) -> bool {
+        self.epoch == raw_checkpoint.epoch_num
+            && self.block_hash.as_slice() == raw_checkpoint.block_hash
+            && self.bitmap.as_slice() == raw_checkpoint.bitmap
+            && self.bls_multi_sig.as_slice() == raw_checkpoint.bls_multi_sig
+    }
+}
+
+/// Returns the data of the `OP_RETURN` output of `tx` holding a checkpoint part of `len` bytes.
+pub fn find_checkpoint_part(
+    tx: &Transaction,
+    tag: &[u8; CHECKPOINT_TAG_LENGTH],
+    len: usize,
+) -> Option<Vec<u8>> {
+    tx.outputs.iter().find_map(|output| {
+        let data = match output.script_pubkey.as_slice() {
+            [OP_RETURN, push, data @ ..]
+                if *push < OP_PUSHDATA1 && *push as usize == data.len() =>
+            {
+                data
+            }
+            [OP_RETURN, OP_PUSHDATA1, push, data @ ..] if *push as usize == data.len() => data,
+            _ => return None,
+        };
+        (data.len() == len && data.starts_with(tag)).then(|| data.to_vec())
+    })
+}
+
+/// Proof of a Bitcoin transaction carrying a checkpoint part.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CheckpointPartWitness {
+    /// Inclusion of the transaction in its block.
+    pub inclusion: InclusionInput,
+    /// Block of the transaction, proven against the Bitcoin consensus proof.
+    pub block: AggregationWitness,
+}
+
+/// The input to the BTC checkpoint program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BtcCheckpointInput {
+    /// Verification key used for the Babylon consensus proof.
+    pub babylon_consensus_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TendermintOutput`](crate::babylon::TendermintOutput) committed from the Babylon
+    /// consensus proof.
+    pub babylon_consensus_public_input: Vec<u8>,
+    /// Verification key used for the membership proof of the checkpoint.
+    pub membership_vkey_u32_hash: [u32; 8],
+    /// Encoded [`MembershipOutput`](crate::babylon::MembershipOutput) committed from the
+    /// membership proof.
+    pub membership_public_input: Vec<u8>,
+    /// Header the membership proof was generated against, when it is older than the target
+    /// header of the Babylon consensus proof.
+    pub historical_header: Option<HistoricalHeader>,
+    /// Verification key used for the Bitcoin consensus proof.
+    pub btc_consensus_vkey_u32_hash: [u32; 8],
+    /// Public values committed from the Bitcoin consensus proof.
+    pub btc_consensus_public_input: ConsensusVerifierPublicInput,
+    /// Tag of the checkpoints of the Babylon network.
+    pub checkpoint_tag: [u8; CHECKPOINT_TAG_LENGTH],
+    /// Epoch of the checkpoint.
+    pub epoch: u64,
+    /// Transactions carrying the first and second part of the checkpoint.
+    pub parts: [CheckpointPartWitness; 2],
+}
+
+impl BtcCheckpointInput {
+    /// Returns the checkpoint proven by `membership`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint is not proven or can not be decoded.
+    pub fn raw_checkpoint(&self, membership: &MembershipOutput) -> RawCheckpointWithMeta {
+        membership.decode_value(&checkpoint_key_path(self.epoch))
+    }
+}
+
+/// The public values committed by the BTC checkpoint program.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BtcCheckpointOutput {
+    /// SHA256 of the public values of the Babylon consensus proof.
+    pub babylon_consensus_public_input_hash: [u8; 32],
+    /// SHA256 of the public values of the Bitcoin consensus proof.
+    pub btc_consensus_public_input_hash: [u8; 32],
+    /// Tag of the checkpoints of the Babylon network.
+    pub checkpoint_tag: [u8; CHECKPOINT_TAG_LENGTH],
+    /// The checkpoint found in Bitcoin.
+    pub checkpoint: BtcCheckpoint,
+    /// Little-endian bytes of the ids of the transactions carrying the two parts.
+    pub tx_ids: [[u8; 32]; 2],
+    /// Heights of the Bitcoin blocks including the two parts.
+    pub btc_block_heights: [u64; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::transaction::TxOut;
+
+    const TAG: &[u8; CHECKPOINT_TAG_LENGTH] = b"bbn1";
+
+    /// Parts of [`checkpoint`] following the `btctxformatter` layout, computed independently of
+    /// this module.
+    const FIRST_PART: &str = "62626e3100000000000000002a000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1fffffffffffffffffffffffff80aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const SECOND_PART: &str = "62626e3110111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111be674b94bb4f9d4d267f";
+
+    fn checkpoint() -> BtcCheckpoint {
+        let mut bitmap = [0xff; BITMAP_LENGTH];
+        bitmap[BITMAP_LENGTH - 1] = 0x80;
+        BtcCheckpoint {
+            epoch: 42,
+            block_hash: core::array::from_fn(|i| i as u8),
+            bitmap,
+            submitter_address: [0xaa; ADDRESS_LENGTH],
+            bls_multi_sig: [0x11; BLS_SIG_LENGTH],
+        }
+    }
+
+    fn tx_with_outputs(script_pubkeys: &[Vec<u8>]) -> Transaction {
+        Transaction {
+            version: 2,
+            inputs: Vec::new(),
+            outputs: script_pubkeys
+                .iter()
+                .map(|script_pubkey| TxOut {
+                    value: 0,
+                    script_pubkey: script_pubkey.clone(),
+                })
+                .collect(),
+            lock_time: 0,
+        }
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let (first_part, second_part) = checkpoint().encode(TAG);
+        assert_eq!(first_part.len(), FIRST_PART_LENGTH);
+        assert_eq!(second_part.len(), SECOND_PART_LENGTH);
+        assert_eq!(
+            BtcCheckpoint::decode(TAG, &first_part, &second_part),
+            Ok(checkpoint())
+        );
+    }
+
+    #[test]
+    fn encodes_the_btctxformatter_layout() {
+        let (first_part, second_part) = checkpoint().encode(TAG);
+        assert_eq!(hex::encode(first_part), FIRST_PART);
+        assert_eq!(hex::encode(second_part), SECOND_PART);
+    }
+
+    #[test]
+    fn rejects_another_tag() {
+        let (first_part, second_part) = checkpoint().encode(TAG);
+        assert_eq!(
+            BtcCheckpoint::decode(b"bbn2", &first_part, &second_part),
+            Err(CheckpointError::InvalidTag)
+        );
+    }
+
+    #[test]
+    fn rejects_an_unsupported_version() {
+        let (mut first_part, second_part) = checkpoint().encode(TAG);
+        first_part[CHECKPOINT_TAG_LENGTH] |= 1;
+        assert_eq!(
+            BtcCheckpoint::decode(TAG, &first_part, &second_part),
+            Err(CheckpointError::UnsupportedVersion(1))
+        );
+    }
+
+    #[test]
+    fn rejects_swapped_parts() {
+        let (first_part, mut second_part) = checkpoint().encode(TAG);
+        second_part[CHECKPOINT_TAG_LENGTH] = 0x00;
+        assert_eq!(
+            BtcCheckpoint::decode(TAG, &first_part, &second_part),
+            Err(CheckpointError::InvalidPartIndex {
+                expected: 1,
+                found: 0
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_an_invalid_length() {
+        let (first_part, second_part) = checkpoint().encode(TAG);
+        assert_eq!(
+            BtcCheckpoint::decode(TAG, &first_part[1..], &second_part),
+            Err(CheckpointError::InvalidLength {
+                part: 0,
+                len: FIRST_PART_LENGTH - 1
+            })
+        );
+        assert_eq!(
+            BtcCheckpoint::decode(TAG, &first_part, &first_part),
+            Err(CheckpointError::InvalidLength {
+                part: 1,
+                len: FIRST_PART_LENGTH
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_a_second_part_of_another_first_part() {
+        let (mut first_part, second_part) = checkpoint().encode(TAG);
+        first_part[HEADER_LENGTH] ^= 1;
+        assert_eq!(
+            BtcCheckpoint::decode(TAG, &first_part, &second_part),
+            Err(CheckpointError::FirstPartHashMismatch)
+        );
+    }
+
+    #[test]
+    fn finds_checkpoint_parts() {
+        let (first_part, second_part) = checkpoint().encode(TAG);
+        // The first part is longer than a direct push allows.
+        let first_output = [
+            [OP_RETURN, OP_PUSHDATA1, FIRST_PART_LENGTH as u8].as_slice(),
+            &first_part,
+        ]
+        .concat();
+        let second_output = [
+            [OP_RETURN, SECOND_PART_LENGTH as u8].as_slice(),
+            &second_part,
+        ]
+        .concat();
+        let tx = tx_with_outputs(&[vec![0x51], first_output, second_output]);
+
+        assert_eq!(
+            find_checkpoint_part(&tx, TAG, FIRST_PART_LENGTH),
+            Some(first_part)
+        );
+        assert_eq!(
+            find_checkpoint_part(&tx, TAG, SECOND_PART_LENGTH),
+            Some(second_part)
+        );
+        assert_eq!(find_checkpoint_part(&tx, b"bbn2", SECOND_PART_LENGTH), None);
+    }
+
+    #[test]
+    fn ignores_parts_of_another_length() {
+        let (_, second_part) = checkpoint().encode(TAG);
+        let output = [
+            [OP_RETURN, SECOND_PART_LENGTH as u8].as_slice(),
+            &second_part,
+        ]
+        .concat();
+        let tx = tx_with_outputs(&[output.clone()]);
+        assert_eq!(find_checkpoint_part(&tx, TAG, FIRST_PART_LENGTH), None);
+
+        // The push does not cover the whole output.
+        let mut truncated_push = output;
+        truncated_push[1] -= 1;
+        let tx = tx_with_outputs(&[truncated_push]);
+        assert_eq!(find_checkpoint_part(&tx, TAG, SECOND_PART_LENGTH), None);
+    }
+}
@@ -0,0 +1,278 @@
+//! Primitives of the program proving the finality of Babylon blocks.
+//!
+//! A block is finalized once the finality providers holding more than two thirds of the voting
+//! power at its height submitted their EOTS signatures of it. The votes and the voting power are
+//! proven by membership of the `finality` store at a later height, and each signature is
+//! verified again: an EOTS signature is a BIP340 signature whose nonce is the public randomness
+//! committed by the finality provider for the height.
+
+use crate::babylon::keys::finality::{
+    IndexedBlock, VotingPowerDistCache, block_key_path, decode_voting_power, vote_key_path,
+    voting_power_dist_cache_key_path, voting_power_key_path,
+};
+use crate::babylon::{HistoricalHeader, MembershipOutput};
+use crate::bitcoin::schnorr::verify_schnorr_signature;
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+
+/// Returns the message signed by the finality providers to vote for the block at `height`.
+///
+/// EOTS signs the SHA256 of the height in big-endian followed by the `app_hash` of the block.
+pub fn vote_message(height: u64, app_hash: &[u8; 32]) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(8 + 32);
+    buf.extend(height.to_be_bytes());
+    buf.extend(app_hash);
+    sha256_hash(&buf)
+}
+
+/// Returns the key paths of the finality store entries to prove for `votes` at `height`.
+pub fn finality_key_paths(height: u64, votes: &[FinalityVote]) -> Vec<Vec<Vec<u8>>> {
+    let mut key_paths = vec![
+        block_key_path(height),
+        voting_power_dist_cache_key_path(height),
+    ];
+    for vote in votes {
+        key_paths.push(vote_key_path(height, vote.fp_btc_pk));
+        key_paths.push(voting_power_key_path(height, vote.fp_btc_pk));
+    }
+    key_paths
+}
+
+/// Vote of a finality provider, the signature itself being proven by the membership proof.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FinalityVote {
+    /// X-only public key of the finality provider.
+    pub fp_btc_pk: [u8; 32],
+    /// Public randomness of the finality provider at the height, the nonce of the signature.
+    pub pub_rand: [u8; 32],
+}
+
+/// The input to the finality program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FinalityInput {
+    /// Verification key used for the Babylon consensus proof.
+    pub consensus_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TendermintOutput`](crate::babylon::TendermintOutput) committed from the
+    /// consensus proof.
+    pub consensus_public_input: Vec<u8>,
+    /// Verification key used for the membership proof of the finality store entries.
+    pub membership_vkey_u32_hash: [u32; 8],
+    /// Encoded [`MembershipOutput`](crate::babylon::MembershipOutput) committed from the
+    /// membership proof.
+    pub membership_public_input: Vec<u8>,
+    /// Header the membership proof was generated against, when it is older than the target
+    /// header of the consensus proof.
+    pub historical_header: Option<HistoricalHeader>,
+    /// Header of the finalized block, covered by the consensus proof.
+    pub finalized_header: HistoricalHeader,
+    /// Votes for the finalized block.
+    pub votes: Vec<FinalityVote>,
+}
+
+impl FinalityInput {
+    /// Verifies the votes for the finalized block against the finality store entries proven by
+    /// `membership`, and returns the voted and the total voting power.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an entry is not proven, if a signature is invalid, or if the votes do not hold
+    /// more than two thirds of the voting power.
+    pub fn verify_votes(&self, membership: &MembershipOutput) -> (u64, u64) {
+        let header = &self.finalized_header.header;
+
+        let indexed_block: IndexedBlock = membership.decode_value(&block_key_path(header.height));
+        assert_eq!(
+            indexed_block.height, header.height,
+            "The indexed block is not the finalized block."
+        );
+        assert_eq!(
+            indexed_block.app_hash, header.app_hash,
+            "The indexed block is not the finalized block."
+        );
+
+        let dist_cache: VotingPowerDistCache =
+            membership.decode_value(&voting_power_dist_cache_key_path(header.height));
+        let total_power = dist_cache.total_voting_power;
+
+        let message = vote_message(header.height, &header.app_hash);
+
+        let mut voted_power = 0u64;
+        for (index, vote) in self.votes.iter().enumerate() {
+            assert!(
+                self.votes[..index]
+                    .iter()
+                    .all(|other| other.fp_btc_pk != vote.fp_btc_pk),
+                "A finality provider voted twice."
+            );
+
+            let eots_sig: [u8; 32] = membership
+                .kv_pair(&vote_key_path(header.height, vote.fp_btc_pk))
+                .value
+                .as_slice()
+                .try_into()
+                .expect("The EOTS signature must be 32 bytes.");
+            let mut signature = [0u8; 64];
+            signature[..32].copy_from_slice(&vote.pub_rand);
+            signature[32..].copy_from_slice(&eots_sig);
+            verify_schnorr_signature(&vote.fp_btc_pk, &message, &signature)
+                .expect("Invalid EOTS signature.");
+
+            let power = decode_voting_power(
+                &membership
+                    .kv_pair(&voting_power_key_path(header.height, vote.fp_btc_pk))
+                    .value,
+            )
+            .expect("Failed to decode the voting power.");
+            voted_power = voted_power
+                .checked_add(power)
+                .expect("The voting power overflows.");
+        }
+
+        assert!(
+            voted_power as u128 * 3 > total_power as u128 * 2,
+            "The votes do not hold a supermajority of the voting power."
+        );
+
+        (voted_power, total_power)
+    }
+}
+
+/// The public values committed by the finality program.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FinalityOutput {
+    /// SHA256 of the public values of the Babylon consensus proof covering the block.
+    pub consensus_public_input_hash: [u8; 32],
+    /// Height of the finalized block.
+    pub height: u64,
+    /// Hash of the header of the finalized block.
+    pub header_hash: [u8; 32],
+    /// Application state root of the finalized block.
+    pub app_hash: [u8; 32],
+    /// Voting power of the finality providers which voted for the block.
+    pub voted_power: u64,
+    /// Voting power of the active finality providers at the height of the block.
+    pub total_power: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babylon::{KVPair, ProvenHeader};
+    use k256::schnorr::SigningKey;
+    use prost::Message;
+
+    const HEIGHT: u64 = 100;
+    const APP_HASH: [u8; 32] = [0xaa; 32];
+
+    /// Finality providers with their voting power, out of a total of 100.
+    const POWERS: [u64; 3] = [40, 30, 30];
+
+    fn signing_key(index: usize) -> SigningKey {
+        SigningKey::from_bytes(&[index as u8 + 1; 32]).unwrap()
+    }
+
+    fn input(voters: &[usize]) -> (FinalityInput, MembershipOutput) {
+        let mut kv_pairs = vec![
+            KVPair {
+                keys: block_key_path(HEIGHT),
+                value: IndexedBlock {
+                    height: HEIGHT,
+                    app_hash: APP_HASH.to_vec(),
+                    finalized: true,
+                }
+                .encode_to_vec(),
+            },
+            KVPair {
+                keys: voting_power_dist_cache_key_path(HEIGHT),
+                value: VotingPowerDistCache {
+                    total_voting_power: POWERS.iter().sum(),
+                    num_active_fps: POWERS.len() as u32,
+                }
+                .encode_to_vec(),
+            },
+        ];
+
+        let message = vote_message(HEIGHT, &APP_HASH);
+        let votes = voters
+            .iter()
+            .map(|&index| {
+                let signing_key = signing_key(index);
+                let fp_btc_pk: [u8; 32] = signing_key.verifying_key().to_bytes().into();
+                // The nonce of the signature stands for the committed public randomness.
+                let signature = signing_key.sign_raw(&message, &[0; 32]).unwrap().to_bytes();
+                kv_pairs.push(KVPair {
+                    keys: vote_key_path(HEIGHT, fp_btc_pk),
+                    value: signature[32..].to_vec(),
+                });
+                kv_pairs.push(KVPair {
+                    keys: voting_power_key_path(HEIGHT, fp_btc_pk),
+                    value: POWERS[index].to_be_bytes().to_vec(),
+                });
+                FinalityVote {
+                    fp_btc_pk,
+                    pub_rand: signature[..32].try_into().unwrap(),
+                }
+            })
+            .collect();
+
+        let input = FinalityInput {
+            consensus_vkey_u32_hash: [0; 8],
+            consensus_public_input: Vec::new(),
+            membership_vkey_u32_hash: [0; 8],
+            membership_public_input: Vec::new(),
+            historical_header: None,
+            finalized_header: HistoricalHeader {
+                header: ProvenHeader {
+                    height: HEIGHT,
+                    header_hash: [0xbb; 32],
+                    app_hash: APP_HASH,
+                },
+                header_mmr_proof: Default::default(),
+            },
+            votes,
+        };
+        let membership = MembershipOutput {
+            app_hash: [0; 32],
+            kv_pairs,
+            predicates: Vec::new(),
+        };
+        (input, membership)
+    }
+
+    #[test]
+    fn supermajority_finalizes() {
+        let (input, membership) = input(&[0, 1]);
+        assert_eq!(input.verify_votes(&membership), (70, 100));
+    }
+
+    #[test]
+    #[should_panic(expected = "The votes do not hold a supermajority of the voting power.")]
+    fn minority_does_not_finalize() {
+        let (input, membership) = input(&[1, 2]);
+        input.verify_votes(&membership);
+    }
+
+    #[test]
+    #[should_panic(expected = "A finality provider voted twice.")]
+    fn rejects_duplicate_votes() {
+        let (mut input, membership) = input(&[1, 2]);
+        input.votes.push(input.votes[0]);
+        input.verify_votes(&membership);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid EOTS signature.")]
+    fn rejects_other_public_randomness() {
+        let (mut input, membership) = input(&[0, 1]);
+        input.votes[0].pub_rand = input.votes[1].pub_rand;
+        input.verify_votes(&membership);
+    }
+
+    #[test]
+    #[should_panic(expected = "The indexed block is not the finalized block.")]
+    fn rejects_other_blocks() {
+        let (mut input, membership) = input(&[0, 1]);
+        input.finalized_header.header.app_hash = [0xcc; 32];
+        input.verify_votes(&membership);
+    }
+}
@@ -0,0 +1,250 @@
+//! Primitives of the program proving the IBC packets of a Cosmos-based chain.
+//!
+//! The packet commitments, receipts and acknowledgements are stored in the `ibc` store under
+//! their ICS-24 paths, and proven by the membership program like any other key. The IBC packet
+//! program links the membership proof to a header proven by the consensus proof, and decodes the
+//! packet identifiers from the proven paths, so that a relayer can rely on them without parsing
+//! the keys.
+
+use crate::babylon::{HistoricalHeader, KVPair};
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+
+/// Name of the store of the IBC module.
+pub const STORE: &[u8] = b"ibc";
+
+/// Prefix of the ICS-24 path of each kind of packet entry.
+const COMMITMENTS_PREFIX: &str = "commitments";
+const RECEIPTS_PREFIX: &str = "receipts";
+const ACKS_PREFIX: &str = "acks";
+
+/// Value stored under the path of a received packet (`PacketReceipt` in ibc-go).
+pub const PACKET_RECEIPT: &[u8] = &[0x01];
+
+/// Kind of packet entry of the `ibc` store.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketKind {
+    /// Commitment of a packet sent by the chain, see [`packet_commitment`].
+    Commitment,
+    /// Receipt of a packet received by the chain on an unordered channel, see [`PACKET_RECEIPT`].
+    Receipt,
+    /// Commitment of the acknowledgement written by the chain for a received packet, see
+    /// [`ack_commitment`].
+    Acknowledgement,
+}
+
+impl PacketKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Commitment => COMMITMENTS_PREFIX,
+            Self::Receipt => RECEIPTS_PREFIX,
+            Self::Acknowledgement => ACKS_PREFIX,
+        }
+    }
+}
+
+/// Identifier of a packet on the chain end of a channel.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PacketId {
+    pub port_id: String,
+    pub channel_id: String,
+    pub sequence: u64,
+}
+
+impl PacketId {
+    /// Returns the ICS-24 path of the entry of the packet, e.g.
+    /// `commitments/ports/{port_id}/channels/{channel_id}/sequences/{sequence}`.
+    pub fn path(&self, kind: PacketKind) -> String {
+        format!(
+            "{}/ports/{}/channels/{}/sequences/{}",
+            kind.prefix(),
+            self.port_id,
+            self.channel_id,
+            self.sequence
+        )
+    }
+
+    /// Returns the key path of the entry of the packet, as in [`KVPair::keys`].
+    pub fn key_path(&self, kind: PacketKind) -> Vec<Vec<u8>> {
+        vec![STORE.to_vec(), self.path(kind).into_bytes()]
+    }
+
+    /// Decodes the kind and the identifier of a packet entry from its key path, `None` if the key
+    /// path is not the one of a packet entry.
+    pub fn from_key_path(keys: &[Vec<u8>]) -> Option<(PacketKind, Self)> {
+        let [store, path] = keys else {
+            return None;
+        };
+        if store != STORE {
+            return None;
+        }
+        let path = core::str::from_utf8(path).ok()?;
+        let segments = path.split('/').collect::<Vec<_>>();
+        let [
+            prefix,
+            "ports",
+            port_id,
+            "channels",
+            channel_id,
+            "sequences",
+            sequence,
+        ] = segments.as_slice()
+        else {
+            return None;
+        };
+        let kind = match *prefix {
+            COMMITMENTS_PREFIX => PacketKind::Commitment,
+            RECEIPTS_PREFIX => PacketKind::Receipt,
+            ACKS_PREFIX => PacketKind::Acknowledgement,
+            _ => return None,
+        };
+        // Reject non-canonical sequences, the path must be the one built by `path`.
+        let sequence_value = sequence.parse::<u64>().ok()?;
+        if sequence_value.to_string() != *sequence || port_id.is_empty() || channel_id.is_empty() {
+            return None;
+        }
+        Some((
+            kind,
+            Self {
+                port_id: port_id.to_string(),
+                channel_id: channel_id.to_string(),
+                sequence: sequence_value,
+            },
+        ))
+    }
+}
+
+/// Computes the commitment of a packet stored by the sending chain (`CommitPacket` in ibc-go).
+pub fn packet_commitment(
+    timeout_revision_number: u64,
+    timeout_revision_height: u64,
+    timeout_timestamp: u64,
+    data: &[u8],
+) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(8 + 8 + 8 + 32);
+    buf.extend(timeout_timestamp.to_be_bytes());
+    buf.extend(timeout_revision_number.to_be_bytes());
+    buf.extend(timeout_revision_height.to_be_bytes());
+    buf.extend(sha256_hash(data));
+    sha256_hash(&buf)
+}
+
+/// Computes the commitment of an acknowledgement stored by the receiving chain
+/// (`CommitAcknowledgement` in ibc-go).
+pub fn ack_commitment(acknowledgement: &[u8]) -> [u8; 32] {
+    sha256_hash(acknowledgement)
+}
+
+/// The input to the IBC packet program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IbcPacketInput {
+    /// Verification key used for the Babylon consensus proof.
+    pub consensus_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TendermintOutput`](crate::babylon::TendermintOutput) committed from the
+    /// consensus proof.
+    pub consensus_public_input: Vec<u8>,
+    /// Verification key used for the membership proof of the packet entries.
+    pub membership_vkey_u32_hash: [u32; 8],
+    /// Encoded [`MembershipOutput`](crate::babylon::MembershipOutput) committed from the
+    /// membership proof.
+    pub membership_public_input: Vec<u8>,
+    /// Header the membership proof was generated against, when it is older than the target
+    /// header of the consensus proof.
+    pub historical_header: Option<HistoricalHeader>,
+}
+
+/// A packet entry proven by the IBC packet program.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProvenPacket {
+    pub kind: PacketKind,
+    pub packet_id: PacketId,
+    /// Value of the entry, a commitment or [`PACKET_RECEIPT`].
+    pub value: Vec<u8>,
+}
+
+impl ProvenPacket {
+    /// Decodes the packet entry proven by `kv_pair`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kv_pair` is not a packet entry.
+    pub fn from_kv_pair(kv_pair: KVPair) -> Self {
+        let (kind, packet_id) =
+            PacketId::from_key_path(&kv_pair.keys).expect("The key is not an IBC packet path.");
+        Self {
+            kind,
+            packet_id,
+            value: kv_pair.value,
+        }
+    }
+}
+
+/// The public values committed by the IBC packet program.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IbcPacketOutput {
+    /// SHA256 of the public values of the Babylon consensus proof covering the block.
+    pub consensus_public_input_hash: [u8; 32],
+    /// Height of the block the packet entries are proven at.
+    pub height: u64,
+    /// Application state root the packet entries are proven against.
+    pub app_hash: [u8; 32],
+    pub packets: Vec<ProvenPacket>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet_id() -> PacketId {
+        PacketId {
+            port_id: "transfer".into(),
+            channel_id: "channel-0".into(),
+            sequence: 42,
+        }
+    }
+
+    #[test]
+    fn key_path_round_trips() {
+        for kind in [
+            PacketKind::Commitment,
+            PacketKind::Receipt,
+            PacketKind::Acknowledgement,
+        ] {
+            let key_path = packet_id().key_path(kind);
+            assert_eq!(
+                PacketId::from_key_path(&key_path),
+                Some((kind, packet_id()))
+            );
+        }
+        assert_eq!(
+            packet_id().path(PacketKind::Acknowledgement),
+            "acks/ports/transfer/channels/channel-0/sequences/42"
+        );
+    }
+
+    #[test]
+    fn rejects_other_key_paths() {
+        let path = |path: &str| vec![STORE.to_vec(), path.as_bytes().to_vec()];
+        // Non-canonical sequence.
+        assert_eq!(
+            PacketId::from_key_path(&path(
+                "commitments/ports/transfer/channels/channel-0/sequences/042"
+            )),
+            None
+        );
+        // Sequence counter of the channel end.
+        assert_eq!(
+            PacketId::from_key_path(&path("nextSequenceSend/ports/transfer/channels/channel-0")),
+            None
+        );
+        // Empty port.
+        assert_eq!(
+            PacketId::from_key_path(&path("receipts/ports//channels/channel-0/sequences/1")),
+            None
+        );
+        // Other store.
+        let mut key_path = packet_id().key_path(PacketKind::Commitment);
+        key_path[0] = b"bank".to_vec();
+        assert_eq!(PacketId::from_key_path(&key_path), None);
+    }
+}
@@ -0,0 +1,584 @@
+//! Store keys and value types of the Babylon modules.
+//!
+//! Each module exposes the name of its store, builders for the keys of its entries and their
+//! `KVPair` key paths, and the protobuf types of the values, decoded with
+//! [`KVPair::decode_value`](crate::babylon::KVPair::decode_value). Only the fields used by the
+//! programs are decoded, `prost` skips the others.
+
+/// Returns the key path of `key` in `store`, as in [`KVPair::keys`](crate::babylon::KVPair).
+fn key_path(store: &[u8], key: Vec<u8>) -> Vec<Vec<u8>> {
+    vec![store.to_vec(), key]
+}
+
+/// Keys and values of the `epoching` module.
+pub mod epoching {
+    /// Name of the store.
+    pub const STORE: &[u8] = b"epoching";
+
+    /// Prefix of the epoch metadata.
+    const EPOCH_INFO_PREFIX: u8 = 0x11;
+
+    /// Returns the key of the metadata of `epoch`.
+    pub fn epoch_key(epoch: u64) -> Vec<u8> {
+        let mut key = vec![EPOCH_INFO_PREFIX];
+        key.extend_from_slice(&epoch.to_be_bytes());
+        key
+    }
+
+    /// Returns the key path of the metadata of `epoch`.
+    pub fn epoch_key_path(epoch: u64) -> Vec<Vec<u8>> {
+        super::key_path(STORE, epoch_key(epoch))
+    }
+
+    /// Metadata of an epoch (`Epoch` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Epoch {
+        #[prost(uint64, tag = "1")]
+        pub epoch_number: u64,
+        /// Number of blocks of the epoch.
+        #[prost(uint64, tag = "2")]
+        pub current_epoch_interval: u64,
+        #[prost(uint64, tag = "3")]
+        pub first_block_height: u64,
+        /// `app_hash` of the block sealing the epoch, i.e. the first block of the next epoch.
+        #[prost(bytes = "vec", tag = "5")]
+        pub sealer_app_hash: Vec<u8>,
+        #[prost(uint64, tag = "6")]
+        pub sealer_block_height: u64,
+        #[prost(bytes = "vec", tag = "7")]
+        pub sealer_block_hash: Vec<u8>,
+    }
+}
+
+/// Keys and values of the `checkpointing` module.
+pub mod checkpointing {
+    /// Name of the store.
+    pub const STORE: &[u8] = b"checkpointing";
+
+    /// Prefix of the checkpoints.
+    const CHECKPOINTS_OBJECT_PREFIX: [u8; 2] = [0x01, 0x00];
+
+    /// Returns the key of the checkpoint of `epoch`.
+    pub fn checkpoint_key(epoch: u64) -> Vec<u8> {
+        let mut key = CHECKPOINTS_OBJECT_PREFIX.to_vec();
+        key.extend_from_slice(&epoch.to_be_bytes());
+        key
+    }
+
+    /// Returns the key path of the checkpoint of `epoch`.
+    pub fn checkpoint_key_path(epoch: u64) -> Vec<Vec<u8>> {
+        super::key_path(STORE, checkpoint_key(epoch))
+    }
+
+    /// Lifecycle status of a checkpoint (`CheckpointStatus` in Babylon).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(i32)]
+    pub enum CheckpointStatus {
+        /// The BLS signatures of the validators are being accumulated.
+        Accumulating = 0,
+        /// The checkpoint has enough voting power and is ready to be submitted.
+        Sealed = 1,
+        /// The checkpoint is included in Bitcoin.
+        Submitted = 2,
+        /// The checkpoint is `k`-deep in Bitcoin.
+        Confirmed = 3,
+        /// The checkpoint is `w`-deep in Bitcoin.
+        Finalized = 4,
+    }
+
+    impl TryFrom<i32> for CheckpointStatus {
+        type Error = i32;
+
+        fn try_from(status: i32) -> Result<Self, Self::Error> {
+            Ok(match status {
+                0 => Self::Accumulating,
+                1 => Self::Sealed,
+                2 => Self::Submitted,
+                3 => Self::Confirmed,
+                4 => Self::Finalized,
+                status => return Err(status),
+            })
+        }
+    }
+
+    /// Checkpoint of an epoch, as signed by the validators (`RawCheckpoint` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RawCheckpoint {
+        #[prost(uint64, tag = "1")]
+        pub epoch_num: u64,
+        /// Hash of the last block of the epoch.
+        #[prost(bytes = "vec", tag = "2")]
+        pub block_hash: Vec<u8>,
+        /// Bitmap of the validators which signed the checkpoint.
+        #[prost(bytes = "vec", tag = "3")]
+        pub bitmap: Vec<u8>,
+        /// Aggregated BLS signature of the validators.
+        #[prost(bytes = "vec", tag = "4")]
+        pub bls_multi_sig: Vec<u8>,
+    }
+
+    /// Checkpoint along with its metadata (`RawCheckpointWithMeta` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RawCheckpointWithMeta {
+        #[prost(message, optional, tag = "1")]
+        pub ckpt: Option<RawCheckpoint>,
+        #[prost(int32, tag = "2")]
+        pub status: i32,
+    }
+}
+
+/// Keys and values of the `btclightclient` module.
+pub mod btclightclient {
+    /// Name of the store.
+    pub const STORE: &[u8] = b"btclightclient";
+
+    /// Prefix of the tip, also used as its key within the prefixed store.
+    const TIP_PREFIX: u8 = 0x03;
+
+    /// Returns the key of the header at the tip of the Bitcoin chain known to Babylon.
+    pub fn tip_key() -> Vec<u8> {
+        vec![TIP_PREFIX, TIP_PREFIX]
+    }
+
+    /// Returns the key path of the tip.
+    pub fn tip_key_path() -> Vec<Vec<u8>> {
+        super::key_path(STORE, tip_key())
+    }
+
+    /// Bitcoin header along with its metadata (`BTCHeaderInfo` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BtcHeaderInfo {
+        /// Serialized header.
+        #[prost(bytes = "vec", tag = "1")]
+        pub header: Vec<u8>,
+        /// Hash of the header, in the internal byte order.
+        #[prost(bytes = "vec", tag = "2")]
+        pub hash: Vec<u8>,
+        #[prost(uint32, tag = "3")]
+        pub height: u32,
+    }
+}
+
+/// Keys and values of the `btcstaking` module.
+pub mod btcstaking {
+    use crate::bitcoin::to_little_endian_bytes;
+
+    /// Name of the store.
+    pub const STORE: &[u8] = b"btcstaking";
+
+    /// Prefix of the versioned staking parameters.
+    const PARAMS_PREFIX: u8 = 0x01;
+    /// Prefix of the finality providers.
+    const FINALITY_PROVIDER_PREFIX: u8 = 0x02;
+    /// Prefix of the BTC delegations.
+    const BTC_DELEGATION_PREFIX: u8 = 0x04;
+
+    /// Returns the key of the staking parameters of `version`.
+    pub fn params_key(version: u32) -> Vec<u8> {
+        let mut key = vec![PARAMS_PREFIX];
+        key.extend_from_slice(&version.to_be_bytes());
+        key
+    }
+
+    /// Returns the key path of the staking parameters of `version`.
+    pub fn params_key_path(version: u32) -> Vec<Vec<u8>> {
+        super::key_path(STORE, params_key(version))
+    }
+
+    /// Returns the key of the finality provider with the x-only public key `fp_btc_pk`.
+    pub fn finality_provider_key(fp_btc_pk: [u8; 32]) -> Vec<u8> {
+        let mut key = vec![FINALITY_PROVIDER_PREFIX];
+        key.extend_from_slice(&fp_btc_pk);
+        key
+    }
+
+    /// Returns the key path of the finality provider with the x-only public key `fp_btc_pk`.
+    pub fn finality_provider_key_path(fp_btc_pk: [u8; 32]) -> Vec<Vec<u8>> {
+        super::key_path(STORE, finality_provider_key(fp_btc_pk))
+    }
+
+    /// Returns the key of the delegation of `staking_tx_hash`.
+    ///
+    /// `staking_tx_hash` is in the little-endian byte order (as shown on explorers), while the
+    /// key holds the internal byte order.
+    pub fn btc_delegation_key(staking_tx_hash: [u8; 32]) -> Vec<u8> {
+        let mut key = vec![BTC_DELEGATION_PREFIX];
+        key.extend_from_slice(&to_little_endian_bytes(staking_tx_hash));
+        key
+    }
+
+    /// Returns the key path of the delegation of `staking_tx_hash`.
+    pub fn btc_delegation_key_path(staking_tx_hash: [u8; 32]) -> Vec<Vec<u8>> {
+        super::key_path(STORE, btc_delegation_key(staking_tx_hash))
+    }
+
+    /// Staking parameters (`Params` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Params {
+        /// X-only public keys of the covenant members.
+        #[prost(bytes = "vec", repeated, tag = "1")]
+        pub covenant_pks: Vec<Vec<u8>>,
+        /// Number of covenant signatures required.
+        #[prost(uint32, tag = "2")]
+        pub covenant_quorum: u32,
+    }
+
+    /// Staking parameters along with their version (`StoredParams` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StoredParams {
+        #[prost(uint32, tag = "1")]
+        pub version: u32,
+        #[prost(message, optional, tag = "2")]
+        pub params: Option<Params>,
+    }
+
+    /// Finality provider (`FinalityProvider` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FinalityProvider {
+        /// Babylon address of the finality provider.
+        #[prost(string, tag = "1")]
+        pub addr: String,
+        /// Commission rate, the `math.LegacyDec` scaled by 10^18 as a base-10 string.
+        #[prost(string, tag = "3")]
+        pub commission: String,
+        /// X-only public key of the finality provider.
+        #[prost(bytes = "vec", tag = "4")]
+        pub btc_pk: Vec<u8>,
+        /// Babylon height at which the finality provider was slashed, zero if not slashed.
+        #[prost(uint64, tag = "6")]
+        pub slashed_babylon_height: u64,
+        /// Bitcoin height at which the finality provider was slashed, zero if not slashed.
+        #[prost(uint32, tag = "7")]
+        pub slashed_btc_height: u32,
+        /// Whether the finality provider is jailed for missing votes.
+        #[prost(bool, tag = "8")]
+        pub jailed: bool,
+    }
+
+    /// Undelegation of a BTC delegation (`BTCUndelegation` in Babylon).
+    ///
+    /// The signatures are only counted, hence kept encoded.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BtcUndelegation {
+        #[prost(bytes = "vec", repeated, tag = "4")]
+        pub covenant_slashing_sigs: Vec<Vec<u8>>,
+        #[prost(bytes = "vec", repeated, tag = "5")]
+        pub covenant_unbonding_sig_list: Vec<Vec<u8>>,
+        /// Encoded `DelegatorUnbondingInfo`, set once the staker unbonded.
+        #[prost(bytes = "vec", optional, tag = "6")]
+        pub delegator_unbonding_info: Option<Vec<u8>>,
+    }
+
+    /// BTC delegation (`BTCDelegation` in Babylon).
+    ///
+    /// Nested messages which are only counted are kept encoded, which protobuf allows as they
+    /// share the wire type of `bytes`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BtcDelegation {
+        /// Babylon address of the staker.
+        #[prost(string, tag = "1")]
+        pub staker_addr: String,
+        /// X-only public key of the staker.
+        #[prost(bytes = "vec", tag = "2")]
+        pub btc_pk: Vec<u8>,
+        /// X-only public keys of the finality providers the stake is delegated to.
+        #[prost(bytes = "vec", repeated, tag = "4")]
+        pub fp_btc_pk_list: Vec<Vec<u8>>,
+        /// Bitcoin height from which the stake is active, zero until the staking transaction is
+        /// proven.
+        #[prost(uint32, tag = "6")]
+        pub start_height: u32,
+        /// Bitcoin height at which the staking transaction timelock expires.
+        #[prost(uint32, tag = "7")]
+        pub end_height: u32,
+        /// Amount of staked satoshis.
+        #[prost(uint64, tag = "8")]
+        pub total_sat: u64,
+        /// Encoded `CovenantAdaptorSignatures` of the covenant members on the slashing transaction.
+        #[prost(bytes = "vec", repeated, tag = "13")]
+        pub covenant_sigs: Vec<Vec<u8>>,
+        /// Timelock of the unbonding transaction, in Bitcoin blocks.
+        #[prost(uint32, tag = "14")]
+        pub unbonding_time: u32,
+        #[prost(message, optional, tag = "15")]
+        pub btc_undelegation: Option<BtcUndelegation>,
+        /// Version of the staking parameters the delegation was created under.
+        #[prost(uint32, tag = "16")]
+        pub params_version: u32,
+    }
+}
+
+/// Keys and values of the `finality` module.
+pub mod finality {
+    /// Name of the store.
+    pub const STORE: &[u8] = b"finality";
+
+    /// Prefix of the indexed blocks.
+    const BLOCK_PREFIX: u8 = 0x02;
+    /// Prefix of the finality votes.
+    const VOTE_PREFIX: u8 = 0x03;
+    /// Prefix of the voting power table.
+    const VOTING_POWER_PREFIX: u8 = 0x07;
+    /// Prefix of the voting power distribution caches.
+    const VOTING_POWER_DIST_CACHE_PREFIX: u8 = 0x08;
+
+    /// Returns the key of the indexed block at `height`.
+    pub fn block_key(height: u64) -> Vec<u8> {
+        let mut key = vec![BLOCK_PREFIX];
+        key.extend_from_slice(&height.to_be_bytes());
+        key
+    }
+
+    /// Returns the key path of the indexed block at `height`.
+    pub fn block_key_path(height: u64) -> Vec<Vec<u8>> {
+        super::key_path(STORE, block_key(height))
+    }
+
+    /// Returns the key of the vote of the finality provider `fp_btc_pk` at `height`.
+    ///
+    /// The value is the EOTS signature of the finality provider, a 32-byte scalar.
+    pub fn vote_key(height: u64, fp_btc_pk: [u8; 32]) -> Vec<u8> {
+        let mut key = vec![VOTE_PREFIX];
+        key.extend_from_slice(&height.to_be_bytes());
+        key.extend_from_slice(&fp_btc_pk);
+        key
+    }
+
+    /// Returns the key path of the vote of the finality provider `fp_btc_pk` at `height`.
+    pub fn vote_key_path(height: u64, fp_btc_pk: [u8; 32]) -> Vec<Vec<u8>> {
+        super::key_path(STORE, vote_key(height, fp_btc_pk))
+    }
+
+    /// Returns the key of the voting power of the finality provider `fp_btc_pk` at `height`.
+    ///
+    /// Only the active finality providers have a voting power, see [`decode_voting_power`].
+    pub fn voting_power_key(height: u64, fp_btc_pk: [u8; 32]) -> Vec<u8> {
+        let mut key = vec![VOTING_POWER_PREFIX];
+        key.extend_from_slice(&height.to_be_bytes());
+        key.extend_from_slice(&fp_btc_pk);
+        key
+    }
+
+    /// Returns the key path of the voting power of the finality provider `fp_btc_pk` at `height`.
+    pub fn voting_power_key_path(height: u64, fp_btc_pk: [u8; 32]) -> Vec<Vec<u8>> {
+        super::key_path(STORE, voting_power_key(height, fp_btc_pk))
+    }
+
+    /// Returns the key of the voting power distribution at `height`.
+    pub fn voting_power_dist_cache_key(height: u64) -> Vec<u8> {
+        let mut key = vec![VOTING_POWER_DIST_CACHE_PREFIX];
+        key.extend_from_slice(&height.to_be_bytes());
+        key
+    }
+
+    /// Returns the key path of the voting power distribution at `height`.
+    pub fn voting_power_dist_cache_key_path(height: u64) -> Vec<Vec<u8>> {
+        super::key_path(STORE, voting_power_dist_cache_key(height))
+    }
+
+    /// Decodes a voting power, stored as a big-endian `u64`.
+    pub fn decode_voting_power(value: &[u8]) -> Option<u64> {
+        Some(u64::from_be_bytes(value.try_into().ok()?))
+    }
+
+    /// Voting power distribution of the finality providers (`VotingPowerDistCache` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VotingPowerDistCache {
+        /// Voting power of the active finality providers.
+        #[prost(uint64, tag = "1")]
+        pub total_voting_power: u64,
+        #[prost(uint32, tag = "3")]
+        pub num_active_fps: u32,
+    }
+
+    /// Block indexed by the finality module (`IndexedBlock` in Babylon).
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct IndexedBlock {
+        #[prost(uint64, tag = "1")]
+        pub height: u64,
+        #[prost(bytes = "vec", tag = "2")]
+        pub app_hash: Vec<u8>,
+        /// Whether the block is finalized by the finality providers.
+        #[prost(bool, tag = "3")]
+        pub finalized: bool,
+    }
+}
+
+/// Keys and values of the Cosmos SDK `bank` module.
+pub mod bank {
+    /// Name of the store.
+    pub const STORE: &[u8] = b"bank";
+
+    /// Prefix of the total supplies.
+    const SUPPLY_PREFIX: u8 = 0x00;
+    /// Prefix of the account balances.
+    const BALANCES_PREFIX: u8 = 0x02;
+
+    /// Returns the key of the balance of `denom` of the account `address`.
+    ///
+    /// `address` is the raw account address, not its bech32 encoding.
+    pub fn balance_key(address: &[u8], denom: &str) -> Vec<u8> {
+        let mut key = Vec::with_capacity(2 + address.len() + denom.len());
+        key.push(BALANCES_PREFIX);
+        key.push(address.len() as u8);
+        key.extend_from_slice(address);
+        key.extend_from_slice(denom.as_bytes());
+        key
+    }
+
+    /// Returns the key path of the balance of `denom` of the account `address`.
+    pub fn balance_key_path(address: &[u8], denom: &str) -> Vec<Vec<u8>> {
+        super::key_path(STORE, balance_key(address, denom))
+    }
+
+    /// Returns the key of the total supply of `denom`.
+    pub fn supply_key(denom: &str) -> Vec<u8> {
+        let mut key = vec![SUPPLY_PREFIX];
+        key.extend_from_slice(denom.as_bytes());
+        key
+    }
+
+    /// Returns the key path of the total supply of `denom`.
+    pub fn supply_key_path(denom: &str) -> Vec<Vec<u8>> {
+        super::key_path(STORE, supply_key(denom))
+    }
+
+    /// Decodes a balance or supply amount, stored as the decimal string of a `math.Int`.
+    ///
+    /// Returns `None` if the value is not a non-negative amount fitting in a `u128`.
+    pub fn decode_amount(value: &[u8]) -> Option<u128> {
+        if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        value.iter().try_fold(0u128, |amount, digit| {
+            amount.checked_mul(10)?.checked_add((digit - b'0') as u128)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_key() {
+        assert_eq!(
+            hex::encode(epoching::epoch_key(0x0102)),
+            "110000000000000102"
+        );
+        assert_eq!(
+            epoching::epoch_key_path(1),
+            [
+                b"epoching".to_vec(),
+                hex::decode("110000000000000001").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn checkpoint_key() {
+        assert_eq!(
+            hex::encode(checkpointing::checkpoint_key(0x0102)),
+            "01000000000000000102"
+        );
+    }
+
+    #[test]
+    fn btc_delegation_key_is_in_internal_byte_order() {
+        let staking_tx_hash: [u8; 32] = core::array::from_fn(|i| i as u8 + 1);
+        assert_eq!(
+            hex::encode(btcstaking::btc_delegation_key(staking_tx_hash)),
+            "04201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201"
+        );
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn btc_delegation_key_matches_txid() {
+        use ::bitcoin::hashes::Hash;
+        use core::str::FromStr;
+
+        let txid = ::bitcoin::Txid::from_str(
+            "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+        )
+        .unwrap();
+        let staking_tx_hash = crate::bitcoin::to_little_endian_bytes(txid.to_byte_array());
+        assert_eq!(
+            btcstaking::btc_delegation_key(staking_tx_hash)[1..],
+            txid.to_byte_array()
+        );
+    }
+
+    #[test]
+    fn params_and_tip_keys() {
+        assert_eq!(hex::encode(btcstaking::params_key(0x0102)), "0100000102");
+        assert_eq!(hex::encode(btclightclient::tip_key()), "0303");
+    }
+
+    #[test]
+    fn finality_provider_key() {
+        assert_eq!(
+            hex::encode(btcstaking::finality_provider_key([0xab; 32])),
+            format!("02{}", "ab".repeat(32))
+        );
+    }
+
+    #[test]
+    fn balance_key_is_length_prefixed() {
+        assert_eq!(
+            hex::encode(bank::balance_key(&[0xaa; 20], "ubbn")),
+            format!("0214{}{}", "aa".repeat(20), hex::encode("ubbn"))
+        );
+        // 32-byte addresses of module and contract accounts.
+        assert_eq!(
+            hex::encode(bank::balance_key(&[0xbb; 32], "ubbn")),
+            format!("0220{}{}", "bb".repeat(32), hex::encode("ubbn"))
+        );
+        assert_eq!(hex::encode(bank::supply_key("ubbn")), "007562626e");
+    }
+
+    #[test]
+    fn finality_keys() {
+        let fp_btc_pk = [0xcd; 32];
+        assert_eq!(
+            hex::encode(finality::vote_key(0x0102, fp_btc_pk)),
+            format!("030000000000000102{}", "cd".repeat(32))
+        );
+        assert_eq!(
+            hex::encode(finality::voting_power_key(0x0102, fp_btc_pk)),
+            format!("070000000000000102{}", "cd".repeat(32))
+        );
+        assert_eq!(
+            hex::encode(finality::block_key(0x0102)),
+            "020000000000000102"
+        );
+        assert_eq!(
+            hex::encode(finality::voting_power_dist_cache_key(0x0102)),
+            "080000000000000102"
+        );
+    }
+
+    #[test]
+    fn voting_power_is_big_endian() {
+        assert_eq!(
+            finality::decode_voting_power(&hex::decode("0000000000000102").unwrap()),
+            Some(0x0102)
+        );
+        assert_eq!(finality::decode_voting_power(&[1, 2]), None);
+    }
+
+    #[test]
+    fn amount_is_a_decimal_string() {
+        assert_eq!(bank::decode_amount(b"1000"), Some(1000));
+        assert_eq!(bank::decode_amount(b""), None);
+        assert_eq!(bank::decode_amount(b"-1"), None);
+        assert_eq!(
+            bank::decode_amount(u128::MAX.to_string().as_bytes()),
+            Some(u128::MAX)
+        );
+        assert_eq!(
+            bank::decode_amount(b"340282366920938463463374607431768211456"),
+            None
+        );
+    }
+}
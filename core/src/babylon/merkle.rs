@@ -0,0 +1,204 @@
+//! The simple Merkle tree of Tendermint (RFC 6962), committing to the transactions of a block
+//! (`data_hash`), their results (`last_results_hash`) and the fields of the header.
+
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+
+/// Domain separation prefixes of the leaves and of the inner nodes.
+const LEAF_PREFIX: u8 = 0x00;
+const INNER_PREFIX: u8 = 0x01;
+
+pub fn leaf_hash(item: &[u8]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(1 + item.len());
+    bytes.push(LEAF_PREFIX);
+    bytes.extend_from_slice(item);
+    sha256_hash(&bytes)
+}
+
+pub fn inner_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = [0u8; 65];
+    bytes[0] = INNER_PREFIX;
+    bytes[1..33].copy_from_slice(left);
+    bytes[33..].copy_from_slice(right);
+    sha256_hash(&bytes)
+}
+
+/// Returns the number of leaves of the left subtree, the largest power of two less than `total`.
+fn split_point(total: u64) -> u64 {
+    debug_assert!(total > 1);
+    1 << (63 - (total - 1).leading_zeros())
+}
+
+/// Computes the root of the tree over `items`.
+pub fn merkle_root<T: AsRef<[u8]>>(items: &[T]) -> [u8; 32] {
+    match items {
+        [] => sha256_hash(&[]),
+        [item] => leaf_hash(item.as_ref()),
+        _ => {
+            let (left, right) = items.split_at(split_point(items.len() as u64) as usize);
+            inner_hash(&merkle_root(left), &merkle_root(right))
+        }
+    }
+}
+
+/// Proof of the inclusion of an item in a tree.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimpleMerkleProof {
+    /// Number of items of the tree.
+    pub total: u64,
+    /// Index of the item.
+    pub index: u64,
+    /// Siblings of the path from the leaf to the root, starting from the leaf.
+    pub aunts: Vec<[u8; 32]>,
+}
+
+impl SimpleMerkleProof {
+    /// Generates the proof of the item at `index` of `items`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn generate<T: AsRef<[u8]>>(items: &[T], index: usize) -> Self {
+        assert!(index < items.len(), "Index {index} out of bounds.");
+        let total = items.len() as u64;
+        let mut aunts = Vec::new();
+        let (mut items, mut offset) = (items, index);
+        while items.len() > 1 {
+            let (left, right) = items.split_at(split_point(items.len() as u64) as usize);
+            if offset < left.len() {
+                aunts.push(merkle_root(right));
+                items = left;
+            } else {
+                aunts.push(merkle_root(left));
+                offset -= left.len();
+                items = right;
+            }
+        }
+        aunts.reverse();
+        Self {
+            total,
+            index: index as u64,
+            aunts,
+        }
+    }
+
+    /// Computes the root of the tree from `item`, `None` if the proof is malformed.
+    pub fn compute_root(&self, item: &[u8]) -> Option<[u8; 32]> {
+        compute_root_from_aunts(self.index, self.total, leaf_hash(item), &self.aunts)
+    }
+
+    /// Returns `true` if `item` is part of the tree committed to by `root`.
+    pub fn verify(&self, root: [u8; 32], item: &[u8]) -> bool {
+        self.compute_root(item) == Some(root)
+    }
+}
+
+fn compute_root_from_aunts(
+    index: u64,
+    total: u64,
+    leaf_hash: [u8; 32],
+    aunts: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if index >= total {
+        return None;
+    }
+    match (total, aunts.split_last()) {
+        (1, None) => Some(leaf_hash),
+        (1, Some(_)) | (_, None) => None,
+        (_, Some((aunt, aunts))) => {
+            let left_count = split_point(total);
+            if index < left_count {
+                let left = compute_root_from_aunts(index, left_count, leaf_hash, aunts)?;
+                Some(inner_hash(&left, aunt))
+            } else {
+                let right = compute_root_from_aunts(
+                    index - left_count,
+                    total - left_count,
+                    leaf_hash,
+                    aunts,
+                )?;
+                Some(inner_hash(aunt, &right))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn empty_and_single_item_roots() {
+        assert_eq!(
+            hex::encode(merkle_root::<&[u8]>(&[])),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        // SHA256 of the leaf prefix alone.
+        assert_eq!(
+            hex::encode(merkle_root(&[b""])),
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+        );
+    }
+
+    #[test]
+    fn left_subtree_is_the_largest_power_of_two() {
+        let items: [&[u8]; 5] = [b"a", b"b", b"c", b"d", b"e"];
+        let leaves = items.map(leaf_hash);
+        let left = inner_hash(
+            &inner_hash(&leaves[0], &leaves[1]),
+            &inner_hash(&leaves[2], &leaves[3]),
+        );
+        assert_eq!(merkle_root(&items), inner_hash(&left, &leaves[4]));
+        assert_eq!(
+            merkle_root(&items[..3]),
+            inner_hash(&inner_hash(&leaves[0], &leaves[1]), &leaves[2])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn proofs_verify(
+            items in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..8), 1..40),
+            index in any::<prop::sample::Index>(),
+        ) {
+            let index = index.index(items.len());
+            let root = merkle_root(&items);
+            let proof = SimpleMerkleProof::generate(&items, index);
+            prop_assert!(proof.verify(root, &items[index]));
+        }
+
+        #[test]
+        fn proofs_are_bound_to_the_index(
+            items in prop::collection::vec(any::<[u8; 4]>(), 2..40),
+            index in any::<prop::sample::Index>(),
+        ) {
+            let index = index.index(items.len());
+            let root = merkle_root(&items);
+            let mut proof = SimpleMerkleProof::generate(&items, index);
+            proof.index = (proof.index + 1) % proof.total;
+            prop_assume!(items[proof.index as usize] != items[index]);
+            prop_assert!(!proof.verify(root, &items[index]));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_proofs() {
+        let items = [b"a", b"b", b"c"];
+        let root = merkle_root(&items);
+        let proof = SimpleMerkleProof::generate(&items, 2);
+        assert!(proof.verify(root, b"c"));
+        assert!(!proof.verify(root, b"d"));
+
+        let mut extra_aunt = proof.clone();
+        extra_aunt.aunts.push([0; 32]);
+        assert_eq!(extra_aunt.compute_root(b"c"), None);
+
+        let mut missing_aunt = proof.clone();
+        missing_aunt.aunts.pop();
+        assert_eq!(missing_aunt.compute_root(b"c"), None);
+
+        let out_of_bounds = SimpleMerkleProof { index: 3, ..proof };
+        assert_eq!(out_of_bounds.compute_root(b"c"), None);
+    }
+}
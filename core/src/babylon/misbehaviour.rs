@@ -0,0 +1,208 @@
+//! Primitives of the program proving misbehaviour of the validators of a Cosmos-based chain.
+//!
+//! Validators misbehave when they sign two different blocks at the same height. Each of the
+//! conflicting blocks is verified against a block proven by the consensus program as a light
+//! client would when skipping blocks, with the trust threshold of one third, as for the light
+//! client attack evidence of IBC. Each block is then signed by validators holding more than one
+//! third of the trusted voting power, but two such sets need not overlap: the evidence proves
+//! that light clients can be misled into following either block, not which validators signed
+//! both of them.
+
+use crate::babylon::{HistoricalHeader, ProvenHeader, light_block_hash, verify_header};
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+use tendermint_light_client_verifier::types::{LightBlock, TrustThreshold};
+
+/// Returns the hash identifying the evidence of two conflicting blocks at `height`, both verified
+/// from the trusted header.
+///
+/// The header hashes are sorted, so that the evidence does not depend on the order in which the
+/// blocks are submitted.
+pub fn evidence_hash(
+    height: u64,
+    trusted_header_hash: &[u8; 32],
+    header_hashes: &[[u8; 32]; 2],
+) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(8 + 32 * 3);
+    buf.extend(height.to_le_bytes());
+    buf.extend(trusted_header_hash);
+    buf.extend(header_hashes.iter().flatten());
+    sha256_hash(&buf)
+}
+
+/// The input to the misbehaviour program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MisbehaviourInput {
+    /// Verification key used for the Babylon consensus proof.
+    pub consensus_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TendermintOutput`](crate::babylon::TendermintOutput) committed from the
+    /// consensus proof.
+    pub consensus_public_input: Vec<u8>,
+    /// Header of the trusted block, when it is older than the target header of the consensus
+    /// proof.
+    pub historical_header: Option<HistoricalHeader>,
+    /// Block proven by the consensus proof, trusted to verify the conflicting blocks.
+    pub trusted_block: LightBlock,
+    /// Blocks signed at the same height.
+    pub conflicting_blocks: [LightBlock; 2],
+}
+
+impl MisbehaviourInput {
+    /// Verifies the conflicting blocks against the trusted block, itself matching
+    /// `trusted_header`, and returns their height and their sorted header hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the trusted block is not the trusted header, if the blocks do not conflict, or
+    /// if one of them is not signed by more than one third of the trusted voting power.
+    pub fn verify(&self, trusted_header: &ProvenHeader) -> (u64, [[u8; 32]; 2]) {
+        let trusted_signed_header = &self.trusted_block.signed_header.header;
+        assert_eq!(
+            trusted_signed_header.height.value(),
+            trusted_header.height,
+            "The trusted block is not the proven header."
+        );
+        assert_eq!(
+            light_block_hash(&self.trusted_block),
+            trusted_header.header_hash,
+            "The trusted block is not the proven header."
+        );
+
+        let [first, second] = &self.conflicting_blocks;
+        assert_eq!(
+            first.height(),
+            second.height(),
+            "The conflicting blocks must be at the same height."
+        );
+        let mut header_hashes = [light_block_hash(first), light_block_hash(second)];
+        assert_ne!(
+            header_hashes[0], header_hashes[1],
+            "The conflicting blocks must be different."
+        );
+        header_hashes.sort();
+
+        for block in &self.conflicting_blocks {
+            verify_header(&self.trusted_block, block, TrustThreshold::ONE_THIRD);
+        }
+
+        (first.height().value(), header_hashes)
+    }
+}
+
+/// The public values committed by the misbehaviour program.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MisbehaviourOutput {
+    /// SHA256 of the public values of the Babylon consensus proof covering the trusted block.
+    pub consensus_public_input_hash: [u8; 32],
+    /// Height of the trusted block.
+    pub trusted_height: u64,
+    /// Hash of the header of the trusted block.
+    pub trusted_header_hash: [u8; 32],
+    /// Height of the conflicting blocks.
+    pub height: u64,
+    /// Sorted hashes of the headers of the conflicting blocks.
+    pub header_hashes: [[u8; 32]; 2],
+    /// See [`evidence_hash`].
+    pub evidence_hash: [u8; 32],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babylon::tests::{light_block, validators};
+
+    const HEIGHT: u64 = 5;
+
+    fn trusted_block() -> LightBlock {
+        light_block(1, 1, &validators(&["a", "b", "c"], 10))
+    }
+
+    fn trusted_header(trusted_block: &LightBlock) -> ProvenHeader {
+        ProvenHeader {
+            height: trusted_block.height().value(),
+            header_hash: light_block_hash(trusted_block),
+            app_hash: [0; 32],
+        }
+    }
+
+    /// Two blocks at [`HEIGHT`] signed by the trusted validators, differing in their time.
+    fn conflicting_blocks() -> [LightBlock; 2] {
+        let validators = validators(&["a", "b", "c"], 10);
+        [
+            light_block(HEIGHT, 5, &validators),
+            light_block(HEIGHT, 6, &validators),
+        ]
+    }
+
+    fn input(conflicting_blocks: [LightBlock; 2]) -> MisbehaviourInput {
+        MisbehaviourInput {
+            consensus_vkey_u32_hash: [0; 8],
+            consensus_public_input: Vec::new(),
+            historical_header: None,
+            trusted_block: trusted_block(),
+            conflicting_blocks,
+        }
+    }
+
+    #[test]
+    fn conflicting_blocks_are_verified() {
+        let input = input(conflicting_blocks());
+        let (height, header_hashes) = input.verify(&trusted_header(&input.trusted_block));
+
+        assert_eq!(height, HEIGHT);
+        let mut expected = input.conflicting_blocks.each_ref().map(light_block_hash);
+        expected.sort();
+        assert_eq!(header_hashes, expected);
+    }
+
+    #[test]
+    fn evidence_does_not_depend_on_the_order_of_the_blocks() {
+        let [first, second] = conflicting_blocks();
+        let trusted_header = trusted_header(&trusted_block());
+
+        let evidence = |conflicting_blocks| {
+            let (height, header_hashes) = input(conflicting_blocks).verify(&trusted_header);
+            evidence_hash(height, &trusted_header.header_hash, &header_hashes)
+        };
+        assert_eq!(
+            evidence([first.clone(), second.clone()]),
+            evidence([second, first])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The conflicting blocks must be different.")]
+    fn rejects_identical_blocks() {
+        let [first, _] = conflicting_blocks();
+        let input = input([first.clone(), first]);
+        input.verify(&trusted_header(&input.trusted_block));
+    }
+
+    #[test]
+    #[should_panic(expected = "The conflicting blocks must be at the same height.")]
+    fn rejects_blocks_at_different_heights() {
+        let [first, _] = conflicting_blocks();
+        let second = light_block(HEIGHT + 1, 6, &validators(&["a", "b", "c"], 10));
+        let input = input([first, second]);
+        input.verify(&trusted_header(&input.trusted_block));
+    }
+
+    #[test]
+    #[should_panic(expected = "The trusted block is not the proven header.")]
+    fn rejects_a_trusted_block_other_than_the_proven_header() {
+        let input = input(conflicting_blocks());
+        // Same height, signed by a validator set controlled by the prover.
+        let forged = light_block(1, 1, &validators(&["forger"], 10));
+        input.verify(&trusted_header(&forged));
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to verify light client update")]
+    fn rejects_a_block_signed_by_a_third_of_the_trusted_power() {
+        let [first, _] = conflicting_blocks();
+        // Only "a" is trusted, holding exactly one third of the trusted voting power.
+        let second = light_block(HEIGHT, 6, &validators(&["a", "x", "y"], 10));
+        let input = input([first, second]);
+        input.verify(&trusted_header(&input.trusted_block));
+    }
+}
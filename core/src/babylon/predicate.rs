@@ -0,0 +1,389 @@
+//! Predicates evaluated by the membership program on the proven values.
+//!
+//! Instead of committing the raw values, which would need to be decoded again by the consumers
+//! of the proof, the membership program can commit the outcome of predicates over them. A
+//! predicate applies to the whole value or to a field of the protobuf message stored as value,
+//! addressed by its path of field numbers.
+
+use crate::babylon::KVPair;
+use crate::babylon::keys::bank::decode_amount;
+use bincode::{Decode, Encode};
+use core::cmp::Ordering;
+use serde::{Deserialize, Serialize};
+
+/// Protobuf wire types.
+const WIRE_TYPE_VARINT: u64 = 0;
+const WIRE_TYPE_FIXED64: u64 = 1;
+const WIRE_TYPE_LEN: u64 = 2;
+const WIRE_TYPE_FIXED32: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredicateError {
+    /// The value is not a valid protobuf message.
+    MalformedMessage,
+    /// The field of the path is not a nested message.
+    NotAMessage(u32),
+    /// The field does not have the type expected by the condition.
+    TypeMismatch,
+    /// The field is not a non-negative base-10 integer fitting in a `u128`.
+    InvalidDecimal,
+}
+
+impl core::fmt::Display for PredicateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MalformedMessage => write!(f, "malformed protobuf message"),
+            Self::NotAMessage(number) => write!(f, "field {number} is not a message"),
+            Self::TypeMismatch => write!(f, "field type does not match the condition"),
+            Self::InvalidDecimal => write!(f, "invalid decimal"),
+        }
+    }
+}
+
+impl core::error::Error for PredicateError {}
+
+/// Comparison of a field against the operand of a condition.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    /// Returns whether `ordering`, the ordering of the field relative to the operand, satisfies
+    /// the comparison.
+    pub fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Eq => ordering.is_eq(),
+            Self::Ge => ordering.is_ge(),
+            Self::Gt => ordering.is_gt(),
+        }
+    }
+}
+
+/// Condition on a field.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// The field is a string, bytes or message equal to the operand.
+    Equals(Vec<u8>),
+    /// The field is a string, bytes or message starting with the operand.
+    HasPrefix(Vec<u8>),
+    /// The field is an unsigned integer, encoded as varint or fixed, compared to the operand.
+    U64(Comparison, u64),
+    /// The field is the base-10 string of an integer compared to the operand, as `math.Int`
+    /// and `math.LegacyDec` (scaled by 10^18) are stored.
+    Decimal(Comparison, u128),
+}
+
+/// Condition on the whole value or on one of its fields.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Predicate {
+    /// Field numbers leading to the field from the value, empty to address the whole value.
+    ///
+    /// Every field of the path but the last one must be a nested message.
+    pub field_path: Vec<u32>,
+    pub condition: Condition,
+}
+
+/// Field of a protobuf message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field<'a> {
+    Varint(u64),
+    Fixed(u64),
+    Bytes(&'a [u8]),
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, PredicateError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or(PredicateError::MalformedMessage)?;
+        *bytes = rest;
+        // The tenth byte only holds the last bit of a `u64`.
+        if shift == 63 && byte > 1 {
+            return Err(PredicateError::MalformedMessage);
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(PredicateError::MalformedMessage)
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8], len: u64) -> Result<&'a [u8], PredicateError> {
+    if len > bytes.len() as u64 {
+        return Err(PredicateError::MalformedMessage);
+    }
+    let (field, rest) = bytes.split_at(len as usize);
+    *bytes = rest;
+    Ok(field)
+}
+
+/// Returns the last occurrence of the field `number` of `message`, which wins for scalar fields.
+///
+/// `None` means the field holds its default value.
+fn find_field(mut message: &[u8], number: u32) -> Result<Option<Field<'_>>, PredicateError> {
+    let mut found = None;
+    while !message.is_empty() {
+        let key = read_varint(&mut message)?;
+        let field = match key & 0x07 {
+            WIRE_TYPE_VARINT => Field::Varint(read_varint(&mut message)?),
+            WIRE_TYPE_FIXED64 => {
+                let bytes = read_bytes(&mut message, 8)?;
+                Field::Fixed(u64::from_le_bytes(
+                    bytes.try_into().expect("8 bytes read; qed"),
+                ))
+            }
+            WIRE_TYPE_LEN => {
+                let len = read_varint(&mut message)?;
+                Field::Bytes(read_bytes(&mut message, len)?)
+            }
+            WIRE_TYPE_FIXED32 => {
+                let bytes = read_bytes(&mut message, 4)?;
+                Field::Fixed(
+                    u32::from_le_bytes(bytes.try_into().expect("4 bytes read; qed")) as u64,
+                )
+            }
+            // Groups are deprecated and not used by the Cosmos SDK.
+            _ => return Err(PredicateError::MalformedMessage),
+        };
+        if key >> 3 == number as u64 {
+            found = Some(field);
+        }
+    }
+    Ok(found)
+}
+
+impl Predicate {
+    /// Evaluates the predicate on `value`.
+    pub fn evaluate(&self, value: &[u8]) -> Result<bool, PredicateError> {
+        let mut field = Some(Field::Bytes(value));
+        for &number in &self.field_path {
+            field = match field {
+                Some(Field::Bytes(message)) => find_field(message, number)?,
+                // Absent messages hold default values only.
+                None => None,
+                Some(_) => return Err(PredicateError::NotAMessage(number)),
+            };
+        }
+
+        match &self.condition {
+            Condition::Equals(operand) => Ok(field_bytes(field)? == operand.as_slice()),
+            Condition::HasPrefix(operand) => Ok(field_bytes(field)?.starts_with(operand)),
+            Condition::U64(comparison, operand) => {
+                let value = match field {
+                    Some(Field::Varint(value) | Field::Fixed(value)) => value,
+                    None => 0,
+                    Some(Field::Bytes(_)) => return Err(PredicateError::TypeMismatch),
+                };
+                Ok(comparison.holds(value.cmp(operand)))
+            }
+            Condition::Decimal(comparison, operand) => {
+                let value =
+                    decode_amount(field_bytes(field)?).ok_or(PredicateError::InvalidDecimal)?;
+                Ok(comparison.holds(value.cmp(operand)))
+            }
+        }
+    }
+}
+
+fn field_bytes(field: Option<Field<'_>>) -> Result<&[u8], PredicateError> {
+    match field {
+        Some(Field::Bytes(bytes)) => Ok(bytes),
+        None => Ok(&[]),
+        Some(_) => Err(PredicateError::TypeMismatch),
+    }
+}
+
+/// Outcome of a predicate committed by the membership program in place of the value.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PredicateOutput {
+    /// See [`KVPair::key_hash`].
+    pub key_hash: [u8; 32],
+    pub predicate: Predicate,
+    pub result: bool,
+}
+
+impl PredicateOutput {
+    /// Evaluates `predicate` on the value of `kv_pair`.
+    pub fn evaluate(kv_pair: &KVPair, predicate: Predicate) -> Result<Self, PredicateError> {
+        Ok(Self {
+            key_hash: kv_pair.key_hash(),
+            result: predicate.evaluate(&kv_pair.value)?,
+            predicate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babylon::keys::btcstaking::FinalityProvider;
+    use crate::babylon::keys::checkpointing::{RawCheckpoint, RawCheckpointWithMeta};
+    use prost::Message;
+
+    fn predicate(field_path: &[u32], condition: Condition) -> Predicate {
+        Predicate {
+            field_path: field_path.to_vec(),
+            condition,
+        }
+    }
+
+    fn checkpoint() -> Vec<u8> {
+        RawCheckpointWithMeta {
+            ckpt: Some(RawCheckpoint {
+                epoch_num: 42,
+                block_hash: vec![0xab; 32],
+                bitmap: vec![],
+                bls_multi_sig: vec![0xcd; 48],
+            }),
+            status: 2,
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn compares_varints() {
+        let value = checkpoint();
+        assert_eq!(
+            predicate(&[2], Condition::U64(Comparison::Eq, 2)).evaluate(&value),
+            Ok(true)
+        );
+        assert_eq!(
+            predicate(&[1, 1], Condition::U64(Comparison::Ge, 43)).evaluate(&value),
+            Ok(false)
+        );
+        assert_eq!(
+            predicate(&[1, 1], Condition::U64(Comparison::Lt, 43)).evaluate(&value),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn matches_bytes() {
+        let value = checkpoint();
+        assert_eq!(
+            predicate(&[1, 2], Condition::Equals(vec![0xab; 32])).evaluate(&value),
+            Ok(true)
+        );
+        assert_eq!(
+            predicate(&[1, 4], Condition::HasPrefix(vec![0xcd, 0xcd])).evaluate(&value),
+            Ok(true)
+        );
+        assert_eq!(
+            predicate(&[1, 4], Condition::HasPrefix(vec![0xab])).evaluate(&value),
+            Ok(false)
+        );
+        assert_eq!(
+            predicate(&[], Condition::Equals(value.clone())).evaluate(&value),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn absent_fields_hold_default_values() {
+        let value = checkpoint();
+        // The empty bitmap is not encoded.
+        assert_eq!(
+            predicate(&[1, 3], Condition::Equals(vec![])).evaluate(&value),
+            Ok(true)
+        );
+        assert_eq!(
+            predicate(&[3, 1], Condition::U64(Comparison::Eq, 0)).evaluate(&value),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_types() {
+        let value = checkpoint();
+        assert_eq!(
+            predicate(&[2, 1], Condition::U64(Comparison::Eq, 0)).evaluate(&value),
+            Err(PredicateError::NotAMessage(1))
+        );
+        assert_eq!(
+            predicate(&[2], Condition::Equals(vec![2])).evaluate(&value),
+            Err(PredicateError::TypeMismatch)
+        );
+        assert_eq!(
+            predicate(&[1, 2], Condition::U64(Comparison::Eq, 0)).evaluate(&value),
+            Err(PredicateError::TypeMismatch)
+        );
+    }
+
+    #[test]
+    fn compares_decimals() {
+        // A commission of 0.05.
+        let value = FinalityProvider {
+            addr: "bbn1".into(),
+            commission: "50000000000000000".into(),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        assert_eq!(
+            predicate(
+                &[3],
+                Condition::Decimal(Comparison::Lt, 100_000_000_000_000_000)
+            )
+            .evaluate(&value),
+            Ok(true)
+        );
+        assert_eq!(
+            predicate(
+                &[3],
+                Condition::Decimal(Comparison::Gt, 50_000_000_000_000_000)
+            )
+            .evaluate(&value),
+            Ok(false)
+        );
+        assert_eq!(
+            predicate(&[1], Condition::Decimal(Comparison::Eq, 0)).evaluate(&value),
+            Err(PredicateError::InvalidDecimal)
+        );
+    }
+
+    #[test]
+    fn reads_the_largest_varint() {
+        let mut value = vec![0x08];
+        value.extend([0xff; 9]);
+        value.push(0x01);
+        assert_eq!(
+            predicate(&[1], Condition::U64(Comparison::Eq, u64::MAX)).evaluate(&value),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        // Truncated length-delimited field.
+        assert_eq!(
+            predicate(&[1], Condition::Equals(vec![])).evaluate(&[0x0a, 0x05, 0x00]),
+            Err(PredicateError::MalformedMessage)
+        );
+        // Unterminated varint.
+        assert_eq!(
+            predicate(&[1], Condition::U64(Comparison::Eq, 0)).evaluate(&[0x08, 0x80]),
+            Err(PredicateError::MalformedMessage)
+        );
+        // Varint overflowing a u64.
+        let mut overflowing = vec![0x08];
+        overflowing.extend([0xff; 9]);
+        overflowing.push(0x02);
+        assert_eq!(
+            predicate(&[1], Condition::U64(Comparison::Eq, 0)).evaluate(&overflowing),
+            Err(PredicateError::MalformedMessage)
+        );
+        // Start group wire type.
+        assert_eq!(
+            predicate(&[1], Condition::U64(Comparison::Eq, 0)).evaluate(&[0x0b]),
+            Err(PredicateError::MalformedMessage)
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Primitives of the programs proving the inclusion of a transaction in a Babylon block.
+//!
+//! The transactions of a block are committed to by the `data_hash` of its header, the root of
+//! the [simple Merkle tree](crate::babylon::merkle) over the raw transactions. Their results are
+//! committed to in the same order by the `last_results_hash` of the next header, which only
+//! covers the deterministic fields of [`ExecTxResult`]: the events emitted by a transaction can
+//! not be proven, the messages it executed are proven by their responses in `data` instead.
+
+use crate::babylon::HistoricalHeader;
+use crate::babylon::merkle::SimpleMerkleProof;
+use crate::sha256_hash;
+use bincode::{Decode, Encode};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_light_client_verifier::types::Header;
+
+/// The input to the transaction inclusion program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxInclusionInput {
+    /// Header of the block including the transaction.
+    pub header: Header,
+    /// Raw bytes of the transaction.
+    pub tx: Vec<u8>,
+    /// Proof of the transaction against the `data_hash` of the header.
+    pub proof: SimpleMerkleProof,
+    /// Result of the transaction, if its execution is to be proven too.
+    pub result: Option<TxResultWitness>,
+}
+
+/// Result of the execution of a transaction (`ExecTxResult` in CometBFT), reduced to the fields
+/// committed to by `last_results_hash`.
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+pub struct ExecTxResult {
+    /// Zero if the transaction succeeded.
+    #[prost(uint32, tag = "1")]
+    pub code: u32,
+    /// Encoded `TxMsgData` of the Cosmos SDK, holding the responses of the executed messages.
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: Vec<u8>,
+    #[prost(int64, tag = "5")]
+    pub gas_wanted: i64,
+    #[prost(int64, tag = "6")]
+    pub gas_used: i64,
+}
+
+/// Witness of the result of a transaction.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxResultWitness {
+    /// Header of the next block, committing to the results of the transactions of the block.
+    pub next_header: Header,
+    pub result: ExecTxResult,
+    /// Proof of the result against the `last_results_hash` of the next header.
+    pub proof: SimpleMerkleProof,
+}
+
+impl TxInclusionInput {
+    /// Verifies that the transaction is included in the block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transaction is not part of the `data_hash` of the header, or if the result
+    /// is not the one of the transaction.
+    pub fn verify(&self) -> TxInclusionOutput {
+        let data_hash = self.header.data_hash.expect("The header has no data hash.");
+        let data_hash: [u8; 32] = data_hash
+            .as_bytes()
+            .try_into()
+            .expect("The data hash must be a SHA256 hash.");
+        assert!(
+            self.proof.verify(data_hash, &self.tx),
+            "The transaction is not included in the block."
+        );
+
+        let header_hash = header_hash(&self.header);
+
+        let result = self.result.as_ref().map(|witness| {
+            let next_header = &witness.next_header;
+            assert_eq!(
+                next_header.height.value(),
+                self.header.height.value() + 1,
+                "The result must be proven by the next header."
+            );
+            let last_block_hash = next_header
+                .last_block_id
+                .as_ref()
+                .expect("The next header has no last block id.")
+                .hash;
+            assert_eq!(
+                last_block_hash.as_bytes(),
+                header_hash.as_slice(),
+                "The next header does not follow the header."
+            );
+            // The results are in the same order as the transactions.
+            assert_eq!(
+                (witness.proof.index, witness.proof.total),
+                (self.proof.index, self.proof.total),
+                "The result is not the one of the transaction."
+            );
+            let last_results_hash: [u8; 32] = next_header
+                .last_results_hash
+                .expect("The next header has no last results hash.")
+                .as_bytes()
+                .try_into()
+                .expect("The last results hash must be a SHA256 hash.");
+            assert!(
+                witness
+                    .proof
+                    .verify(last_results_hash, &witness.result.encode_to_vec()),
+                "The result is not committed to by the next header."
+            );
+
+            TxResultOutput {
+                next_header_hash: self::header_hash(next_header),
+                code: witness.result.code,
+                data: witness.result.data.clone(),
+                gas_wanted: witness.result.gas_wanted,
+                gas_used: witness.result.gas_used,
+            }
+        });
+
+        TxInclusionOutput {
+            height: self.header.height.value(),
+            header_hash,
+            tx_hash: sha256_hash(&self.tx),
+            result,
+        }
+    }
+}
+
+fn header_hash(header: &Header) -> [u8; 32] {
+    header
+        .hash()
+        .as_bytes()
+        .try_into()
+        .expect("The header hash must be a SHA256 hash.")
+}
+
+/// The public values committed by the transaction inclusion program.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxInclusionOutput {
+    /// Height of the block including the transaction.
+    pub height: u64,
+    /// Hash of the header of the block.
+    pub header_hash: [u8; 32],
+    /// SHA256 of the raw transaction, its hash as shown on explorers.
+    pub tx_hash: [u8; 32],
+    /// Result of the transaction, if proven.
+    pub result: Option<TxResultOutput>,
+}
+
+impl TxInclusionOutput {
+    /// Returns the height and the hash of the header to be proven by the consensus proof, the
+    /// next header if the result is proven since it commits to the header.
+    pub fn proven_header(&self) -> (u64, [u8; 32]) {
+        match &self.result {
+            Some(result) => (self.height + 1, result.next_header_hash),
+            None => (self.height, self.header_hash),
+        }
+    }
+}
+
+/// Result of a transaction committed by the transaction inclusion program.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxResultOutput {
+    /// Hash of the header of the next block, committing to the result.
+    pub next_header_hash: [u8; 32],
+    /// Zero if the transaction succeeded.
+    pub code: u32,
+    /// Encoded `TxMsgData` of the Cosmos SDK, holding the responses of the executed messages.
+    pub data: Vec<u8>,
+    pub gas_wanted: i64,
+    pub gas_used: i64,
+}
+
+/// The input to the transaction aggregation program.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxAggregationInput {
+    /// Verification key used for the consensus proof.
+    pub consensus_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TendermintOutput`](crate::babylon::TendermintOutput) committed from the
+    /// consensus proof.
+    pub consensus_public_input: Vec<u8>,
+    /// Verification key used for the transaction inclusion proof.
+    pub tx_inclusion_vkey_u32_hash: [u32; 8],
+    /// Encoded [`TxInclusionOutput`] committed from the transaction inclusion proof.
+    pub tx_inclusion_public_input: Vec<u8>,
+    /// Header of the block including the transaction, when it is older than the target header
+    /// of the consensus proof.
+    pub historical_header: Option<HistoricalHeader>,
+}
+
+/// The public values committed by the transaction aggregation program.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TxAggregationOutput {
+    /// SHA256 of the public values of the Babylon consensus proof covering the block.
+    pub consensus_public_input_hash: [u8; 32],
+    /// Inclusion of the transaction, and its result if proven.
+    pub tx_inclusion: TxInclusionOutput,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babylon::merkle::merkle_root;
+    use crate::babylon::tests::{light_block, validators};
+    use tendermint::{Hash, block};
+
+    const TXS: [&[u8]; 3] = [b"tx0", b"tx1", b"tx2"];
+    /// Index of the proven transaction.
+    const INDEX: usize = 1;
+
+    fn results() -> Vec<Vec<u8>> {
+        (0..TXS.len())
+            .map(|index| {
+                ExecTxResult {
+                    code: index as u32,
+                    gas_used: 10,
+                    ..Default::default()
+                }
+                .encode_to_vec()
+            })
+            .collect()
+    }
+
+    /// Returns the input proving the transaction at `INDEX` of the block at height 2, with its
+    /// result proven by the block at height 3.
+    fn input() -> TxInclusionInput {
+        let validators = validators(&["a", "b", "c"], 10);
+        let mut header = light_block(2, 2, &validators).signed_header.header;
+        header.data_hash = Some(Hash::Sha256(merkle_root(&TXS)));
+
+        let results = results();
+        let mut next_header = light_block(3, 3, &validators).signed_header.header;
+        next_header.last_block_id = Some(block::Id {
+            hash: header.hash(),
+            part_set_header: Default::default(),
+        });
+        next_header.last_results_hash = Some(Hash::Sha256(merkle_root(&results)));
+
+        TxInclusionInput {
+            header,
+            tx: TXS[INDEX].to_vec(),
+            proof: SimpleMerkleProof::generate(&TXS, INDEX),
+            result: Some(TxResultWitness {
+                next_header,
+                result: ExecTxResult::decode(results[INDEX].as_slice()).unwrap(),
+                proof: SimpleMerkleProof::generate(&results, INDEX),
+            }),
+        }
+    }
+
+    fn witness(input: &mut TxInclusionInput) -> &mut TxResultWitness {
+        input.result.as_mut().unwrap()
+    }
+
+    #[test]
+    fn verifies_the_transaction_and_its_result() {
+        let input = input();
+        let output = input.verify();
+        assert_eq!(output.height, 2);
+        assert_eq!(output.header_hash, header_hash(&input.header));
+        assert_eq!(output.tx_hash, sha256_hash(TXS[INDEX]));
+
+        let result = output.result.unwrap();
+        assert_eq!(
+            result.next_header_hash,
+            header_hash(&input.result.unwrap().next_header)
+        );
+        assert_eq!((result.code, result.gas_used), (INDEX as u32, 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction is not included in the block.")]
+    fn rejects_a_transaction_not_in_the_block() {
+        let mut input = input();
+        input.tx = b"tx3".to_vec();
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The result must be proven by the next header.")]
+    fn rejects_a_result_proven_by_another_header() {
+        let mut input = input();
+        witness(&mut input).next_header.height = block::Height::from(4u32);
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The next header does not follow the header.")]
+    fn rejects_a_next_header_not_following_the_header() {
+        let mut input = input();
+        witness(&mut input).next_header.last_block_id = Some(block::Id {
+            hash: Hash::Sha256([0; 32]),
+            part_set_header: Default::default(),
+        });
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The result is not the one of the transaction.")]
+    fn rejects_the_result_of_another_transaction() {
+        let mut input = input();
+        // Valid against the next header, but for the first transaction.
+        let results = results();
+        let witness = witness(&mut input);
+        witness.result = ExecTxResult::decode(results[0].as_slice()).unwrap();
+        witness.proof = SimpleMerkleProof::generate(&results, 0);
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The result is not committed to by the next header.")]
+    fn rejects_a_result_not_committed_to() {
+        let mut input = input();
+        witness(&mut input).result.code = 5;
+        input.verify();
+    }
+
+    #[test]
+    fn result_encoding_is_deterministic() {
+        // Encoding of CometBFT, skipping the log, info, events and codespace.
+        let result = ExecTxResult {
+            code: 5,
+            data: vec![0x01, 0x02],
+            gas_wanted: 100,
+            gas_used: 50,
+        };
+        assert_eq!(hex::encode(result.encode_to_vec()), "08051202010228643032");
+        // Default fields are not encoded.
+        assert!(ExecTxResult::default().encode_to_vec().is_empty());
+    }
+
+    #[test]
+    fn proven_header_is_the_next_one_with_a_result() {
+        let mut output = TxInclusionOutput {
+            height: 10,
+            header_hash: [1; 32],
+            tx_hash: [2; 32],
+            result: None,
+        };
+        assert_eq!(output.proven_header(), (10, [1; 32]));
+
+        output.result = Some(TxResultOutput {
+            next_header_hash: [3; 32],
+            code: 0,
+            data: vec![],
+            gas_wanted: 0,
+            gas_used: 0,
+        });
+        assert_eq!(output.proven_header(), (11, [3; 32]));
+    }
+}
@@ -1,13 +1,21 @@
+pub mod batch_inclusion;
 pub mod block;
 pub mod consensus;
 pub mod inclusion;
+pub mod schnorr;
+pub mod signet;
+pub mod taproot;
+pub mod transaction;
 
-use self::consensus::ConsensusVerifierPublicInput;
+use self::block::CircuitBlock;
+use self::consensus::{ConsensusVerifierPublicInput, header_mmr_leaf};
 use self::inclusion::InclusionPublicInput;
+use crate::mmr::MmrProof;
 use crate::sha256_hash;
 use serde::{Deserialize, Serialize};
 
 pub const MIN_TRUSTED_BLOCK_NUMBER: usize = 11;
+/// Default confirmation depth of the consensus proofs.
 pub const M_CONFIRMATION: usize = 3;
 pub const MAX_BLOCKS: usize = 3000;
 pub const EPOCH_BLOCK_NUMBER: u32 = 2016;
@@ -24,8 +32,14 @@ pub struct AggregationPublicInput {
     pub inclusion_public_input: InclusionPublicInput,
 }
 
+/// Witness linking the transaction of the inclusion proof to a block proven by the consensus proof.
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
-pub struct AggregationWitness {}
+pub struct AggregationWitness {
+    /// Header of the block containing the transaction.
+    pub block: CircuitBlock,
+    /// Proof of `block` against the header MMR committed by the consensus proof.
+    pub header_mmr_proof: MmrProof,
+}
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct AggregationInput {
@@ -39,6 +53,7 @@ impl AggregationInput {
         consensus_vkey_u32_hash: [u32; 8],
         consensus_verifier_public_input: ConsensusVerifierPublicInput,
         inclusion_public_input: InclusionPublicInput,
+        witness: AggregationWitness,
     ) -> Self {
         Self {
             consensus_vkey_u32_hash,
@@ -46,9 +61,42 @@ impl AggregationInput {
                 consensus_verifier_public_input,
                 inclusion_public_input,
             },
-            witness: AggregationWitness::default(),
+            witness,
         }
     }
+
+    /// Checks that the transaction of the inclusion proof is in a historical block covered by the
+    /// consensus proof, and that the block has at least as many confirmations as the confirmation
+    /// depth of the consensus proof.
+    pub fn validate_historical_block(&self) {
+        let AggregationPublicInput {
+            consensus_verifier_public_input,
+            inclusion_public_input,
+        } = &self.public_input;
+        let AggregationWitness {
+            block,
+            header_mmr_proof,
+        } = &self.witness;
+
+        assert!(
+            header_mmr_proof.verify(
+                consensus_verifier_public_input.header_mmr_root,
+                header_mmr_leaf(block.height, block.compute_block_hash()),
+            ),
+            "The block is not part of the proven header MMR."
+        );
+        assert!(
+            block.height + consensus_verifier_public_input.confirmation_depth as u64
+                <= consensus_verifier_public_input.current_block_height,
+            "The block does not have enough confirmations."
+        );
+        // The transaction merkle root in the inclusion proof is in the explorer byte order.
+        assert_eq!(
+            inclusion_public_input.tx_merkle_root,
+            to_little_endian_bytes(block.merkle_root),
+            "The transaction merkle root does not match the block."
+        );
+    }
 }
 
 /// Converts a big-endian hash to little-endian byte order.
@@ -74,8 +122,14 @@ pub fn hash_pairs(hash_1: [u8; 32], hash_2: [u8; 32]) -> [u8; 32] {
     double_sha256_hash(&combined_hashes)
 }
 
-pub fn get_merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+/// Computes the merkle root of `leaves`, along with whether the tree is mutated.
+///
+/// As in Bitcoin Core, a tree is mutated if any level contains two identical sibling nodes.
+/// Duplicating the trailing transactions of a block with an odd number of nodes yields the
+/// same merkle root (CVE-2012-2459), such a tree must never be accepted.
+pub fn compute_merkle_root(leaves: Vec<[u8; 32]>) -> ([u8; 32], bool) {
     let mut current_level = leaves;
+    let mut mutated = false;
     while current_level.len() > 1 {
         let mut next_level = Vec::new();
         let mut i = 0;
@@ -83,6 +137,7 @@ pub fn get_merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
         while i < current_level.len() {
             let left = current_level[i];
             let right = if i + 1 < current_level.len() {
+                mutated |= left == current_level[i + 1];
                 current_level[i + 1]
             } else {
                 left
@@ -95,5 +150,14 @@ pub fn get_merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
         }
         current_level = next_level;
     }
-    current_level[0]
+    (current_level[0], mutated)
+}
+
+pub fn get_merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+    let (merkle_root, mutated) = compute_merkle_root(leaves);
+    assert!(
+        !mutated,
+        "The merkle tree contains identical sibling nodes."
+    );
+    merkle_root
 }
@@ -0,0 +1,192 @@
+//! This module defines the primitives used in the batch inclusion program.
+//!
+//! A batch proves the inclusion of many transactions, possibly across several blocks, with a
+//! single merkle multiproof per block.
+
+use crate::bitcoin::inclusion::{MerkleMultiProof, compute_merkle_multiproof_root};
+use crate::bitcoin::transaction::Transaction;
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Computes the hash of a set of transaction ids, `sha256` of the concatenated ids in ascending
+/// byte order.
+///
+/// # Panics
+///
+/// Panics if a transaction id appears more than once.
+pub fn tx_id_set_hash(tx_ids: &[[u8; 32]]) -> [u8; 32] {
+    let mut sorted = tx_ids.to_vec();
+    sorted.sort_unstable();
+    assert!(
+        sorted.windows(2).all(|pair| pair[0] != pair[1]),
+        "The batch contains duplicate transaction ids."
+    );
+    sha256_hash(sorted.as_flattened())
+}
+
+/// Block committed to by a batch inclusion proof.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchBlock {
+    /// Transaction merkle root in little-endian format (as shown on explorers).
+    pub tx_merkle_root: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BatchInclusionPublicInput {
+    /// Blocks containing the proven transactions, in the order of the witness.
+    pub blocks: Vec<BatchBlock>,
+    /// Hash of the set of all proven transaction ids, see [`tx_id_set_hash`].
+    pub tx_id_set_hash: [u8; 32],
+    /// Number of proven transactions.
+    pub tx_id_count: u32,
+}
+
+impl BatchInclusionPublicInput {
+    pub fn compute_hash(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(44 + 32 * self.blocks.len());
+        // Length-prefixed as in the bincode encoding of the committed public values.
+        bytes.extend_from_slice(&(self.blocks.len() as u64).to_le_bytes());
+        for block in &self.blocks {
+            bytes.extend_from_slice(&block.tx_merkle_root);
+        }
+        bytes.extend_from_slice(&self.tx_id_set_hash);
+        bytes.extend_from_slice(&self.tx_id_count.to_le_bytes());
+        Sha256::digest(bytes).into()
+    }
+}
+
+/// Transactions proven in a single block.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BlockBatchWitness {
+    /// Serialized transactions using the legacy format (no witness), ordered by position.
+    ///
+    /// The transaction ids are computed from the bytes, since the transaction count of the block
+    /// is not bound to its header and a bare id could be an inner node of the merkle tree.
+    pub legacy_txs: Vec<Vec<u8>>,
+    /// Number of transactions in the block, as claimed by the prover.
+    ///
+    /// Not bound to the block header and therefore not committed.
+    pub tx_count: u32,
+    pub merkle_multiproof: MerkleMultiProof,
+}
+
+impl BlockBatchWitness {
+    /// Returns the transaction ids in little-endian format (as shown on explorers).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a transaction is not a valid legacy transaction or is 64 bytes long.
+    pub fn tx_ids(&self) -> Vec<[u8; 32]> {
+        self.legacy_txs
+            .iter()
+            .map(|legacy_tx| {
+                // A 64-byte transaction could be an inner node of the merkle tree.
+                assert_ne!(
+                    legacy_tx.len(),
+                    64,
+                    "64-byte transactions are not supported."
+                );
+                let tx = Transaction::parse(legacy_tx).expect("Failed to parse legacy transaction");
+                assert!(
+                    !tx.has_witness(),
+                    "The legacy transaction must not have witness."
+                );
+                tx.txid()
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BatchInclusionWitness {
+    /// One entry per block of the public input.
+    pub blocks: Vec<BlockBatchWitness>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BatchInclusionInput {
+    pub public_input: BatchInclusionPublicInput,
+    pub witness: BatchInclusionWitness,
+}
+
+impl BatchInclusionInput {
+    /// # Panics
+    ///
+    /// Panics if `blocks` and `witness.blocks` differ in length, a transaction is invalid or a
+    /// transaction id is repeated.
+    pub fn new(blocks: Vec<BatchBlock>, witness: BatchInclusionWitness) -> Self {
+        assert_eq!(
+            blocks.len(),
+            witness.blocks.len(),
+            "The number of blocks does not match the witness."
+        );
+        let tx_ids = witness
+            .blocks
+            .iter()
+            .flat_map(BlockBatchWitness::tx_ids)
+            .collect::<Vec<_>>();
+
+        Self {
+            public_input: BatchInclusionPublicInput {
+                blocks,
+                tx_id_set_hash: tx_id_set_hash(&tx_ids),
+                tx_id_count: tx_ids.len() as u32,
+            },
+            witness,
+        }
+    }
+
+    /// Verifies that every transaction of the batch is included in its block, and that the
+    /// public input commits to the set of proven transactions.
+    pub fn verify(&self) {
+        let BatchInclusionPublicInput {
+            ref blocks,
+            tx_id_set_hash: expected_set_hash,
+            tx_id_count,
+        } = self.public_input;
+
+        assert!(
+            !blocks.is_empty(),
+            "The batch must contain at least one block."
+        );
+        assert_eq!(
+            blocks.len(),
+            self.witness.blocks.len(),
+            "The number of blocks does not match the witness."
+        );
+
+        // Sized from the transactions of the witness, the claimed count is only checked below.
+        let mut tx_ids = Vec::with_capacity(
+            self.witness
+                .blocks
+                .iter()
+                .map(|witness| witness.legacy_txs.len())
+                .sum(),
+        );
+        for (block, witness) in blocks.iter().zip(&self.witness.blocks) {
+            let block_tx_ids = witness.tx_ids();
+            assert_eq!(
+                compute_merkle_multiproof_root(
+                    &block_tx_ids,
+                    witness.tx_count,
+                    &witness.merkle_multiproof
+                ),
+                block.tx_merkle_root,
+                "The merkle multiproof does not match the merkle root."
+            );
+            tx_ids.extend(block_tx_ids);
+        }
+
+        assert_eq!(
+            tx_ids.len(),
+            tx_id_count as usize,
+            "The transaction count does not match the batch."
+        );
+        assert_eq!(
+            tx_id_set_hash(&tx_ids),
+            expected_set_hash,
+            "The transaction id set hash does not match the batch."
+        );
+    }
+}
@@ -1,7 +1,6 @@
 use crate::bitcoin::consensus::ConsensusBlockPublicInput;
 use crate::bitcoin::{
-    EXPECTED_EPOCH_SECONDS, M_CONFIRMATION, MIN_TRUSTED_BLOCK_NUMBER, double_sha256_hash,
-    to_little_endian_bytes,
+    EXPECTED_EPOCH_SECONDS, MIN_TRUSTED_BLOCK_NUMBER, double_sha256_hash, to_little_endian_bytes,
 };
 use crypto_bigint::{CheckedMul, U256};
 use serde::{Deserialize, Serialize};
@@ -27,6 +26,28 @@ pub struct CircuitBlock {
 }
 
 impl CircuitBlock {
+    /// Parses an 80-byte serialized block header, the inverse of [`CircuitBlock::serialize`].
+    pub fn from_bytes(bytes: &[u8; 80], height: u64) -> Self {
+        let field = |offset: usize| -> [u8; 4] {
+            bytes[offset..offset + 4]
+                .try_into()
+                .expect("Length checked by the array type; qed")
+        };
+        Self {
+            height,
+            version: field(0),
+            prev_blockhash: bytes[4..36]
+                .try_into()
+                .expect("Length checked by the array type; qed"),
+            merkle_root: bytes[36..68]
+                .try_into()
+                .expect("Length checked by the array type; qed"),
+            time: field(68),
+            bits: field(72),
+            nonce: field(76),
+        }
+    }
+
     pub fn serialize(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(80);
         bytes.extend_from_slice(&self.version);
@@ -45,6 +66,53 @@ impl CircuitBlock {
     }
 }
 
+/// Error converting between a [`CircuitBlock`] and a [`::bitcoin::block::Header`].
+#[cfg(feature = "bitcoin")]
+#[derive(Debug)]
+pub enum HeaderConversionError {
+    /// The header does not serialize to 80 bytes.
+    InvalidLength(usize),
+    /// The header failed to decode.
+    Decode(::bitcoin::consensus::encode::Error),
+}
+
+#[cfg(feature = "bitcoin")]
+impl core::fmt::Display for HeaderConversionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength(len) => write!(f, "invalid header length: {len}"),
+            Self::Decode(err) => write!(f, "failed to decode header: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+impl core::error::Error for HeaderConversionError {}
+
+/// Converts a header at the given height.
+#[cfg(feature = "bitcoin")]
+impl TryFrom<(&::bitcoin::block::Header, u64)> for CircuitBlock {
+    type Error = HeaderConversionError;
+
+    fn try_from((header, height): (&::bitcoin::block::Header, u64)) -> Result<Self, Self::Error> {
+        let bytes = ::bitcoin::consensus::serialize(header);
+        let bytes: [u8; 80] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| HeaderConversionError::InvalidLength(bytes.len()))?;
+        Ok(Self::from_bytes(&bytes, height))
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+impl TryFrom<&CircuitBlock> for ::bitcoin::block::Header {
+    type Error = HeaderConversionError;
+
+    fn try_from(block: &CircuitBlock) -> Result<Self, Self::Error> {
+        ::bitcoin::consensus::deserialize(&block.serialize()).map_err(HeaderConversionError::Decode)
+    }
+}
+
 // taken from rust-bitcoin
 fn bits_to_target(bits: [u8; 4]) -> U256 {
     let bits = u32::from_le_bytes(bits);
@@ -104,10 +172,12 @@ fn assert_new_target_bits(
 ///     1) prev_block_hash, proposed_block_hash, retarget_block_hash, median_block_hash, proposed_tx_merkle_root, proposed_block_height
 ///       all these need to be asserted with the help of proposed_chain, and retarget_block
 ///     2) current proposed block header should be the last one of proposed_chain
+///     3) the m-deep block is `confirmation_depth` blocks below the proposed block
 pub fn validate_block(
     proposed_chain: Vec<CircuitBlock>,
     retarget_block: CircuitBlock,
     block_public_input: ConsensusBlockPublicInput,
+    confirmation_depth: u32,
 ) {
     let ConsensusBlockPublicInput {
         prev_block_hash,
@@ -124,9 +194,13 @@ pub fn validate_block(
         proposed_chain.len() >= minimum_chain_len,
         "The proposed chain is too short; it must have at least 12 blocks."
     );
+    assert!(
+        proposed_chain.len() > confirmation_depth as usize,
+        "The proposed chain is too short for the confirmation depth."
+    );
     let proposed_block = *proposed_chain.last().unwrap();
     let previous_block = proposed_chain[proposed_chain.len() - 2];
-    let m_deep_block = proposed_chain[proposed_chain.len() - M_CONFIRMATION - 1];
+    let m_deep_block = proposed_chain[proposed_chain.len() - confirmation_depth as usize - 1];
 
     // 2) assertion of proposed block height
     assert!(
@@ -148,7 +222,7 @@ pub fn validate_block(
     );
     assert_eq!(
         proposed_block_height,
-        m_deep_block.height + M_CONFIRMATION as u64,
+        m_deep_block.height + confirmation_depth as u64,
         "The proposed block height does not match the m-deep witness block height."
     );
 
@@ -231,3 +305,64 @@ pub fn validate_block(
         "The merkle root of proposed block's transactions is invalid."
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "bitcoin")]
+    use ::bitcoin::hashes::Hash;
+    use proptest::prelude::*;
+
+    prop_compose! {
+        #[cfg(feature = "bitcoin")]
+        fn circuit_block()(
+            header in any::<[u8; 80]>(),
+            height in any::<u64>(),
+        ) -> CircuitBlock {
+            CircuitBlock::from_bytes(&header, height)
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn from_bytes_round_trips(header in any::<[u8; 80]>(), height in any::<u64>()) {
+            let block = CircuitBlock::from_bytes(&header, height);
+            prop_assert_eq!(block.serialize(), header.to_vec());
+            prop_assert_eq!(block.height, height);
+        }
+
+        #[cfg(feature = "bitcoin")]
+        #[test]
+        fn block_hash_matches_bitcoin(block in circuit_block()) {
+            let header = ::bitcoin::block::Header::try_from(&block).unwrap();
+            prop_assert_eq!(
+                block.compute_block_hash(),
+                to_little_endian_bytes(header.block_hash().to_byte_array())
+            );
+        }
+
+        #[cfg(feature = "bitcoin")]
+        #[test]
+        fn bitcoin_header_round_trips(block in circuit_block()) {
+            let header = ::bitcoin::block::Header::try_from(&block).unwrap();
+            let round_trip = CircuitBlock::try_from((&header, block.height)).unwrap();
+            prop_assert_eq!(round_trip.serialize(), block.serialize());
+            prop_assert_eq!(round_trip.height, block.height);
+            prop_assert_eq!(
+                ::bitcoin::block::Header::try_from(&round_trip).unwrap(),
+                header
+            );
+        }
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn genesis_block_hash() {
+        let genesis = ::bitcoin::blockdata::constants::genesis_block(::bitcoin::Network::Bitcoin);
+        let block = CircuitBlock::try_from((&genesis.header, 0)).unwrap();
+        assert_eq!(
+            block.compute_block_hash(),
+            to_little_endian_bytes(genesis.block_hash().to_byte_array())
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! This module defines the primitives used in the consensus program.
 
 use crate::bitcoin::block::CircuitBlock;
+use crate::bitcoin::signet::SignetWitness;
+use crate::mmr::MerkleMountainRange;
 use crate::sha256_hash;
 use serde::{Deserialize, Serialize};
 
@@ -56,6 +58,20 @@ impl ConsensusBlockPublicInput {
     pub fn compute_hash(&self) -> [u8; 32] {
         sha256_hash(&self.serialize())
     }
+
+    /// Returns the leaf of the proposed block in the header MMR.
+    pub fn header_mmr_leaf(&self) -> [u8; 32] {
+        header_mmr_leaf(self.proposed_block_height, self.proposed_block_hash)
+    }
+}
+
+/// Computes the header MMR leaf of a block, binding the block hash to its height.
+pub fn header_mmr_leaf(block_height: u64, block_hash: [u8; 32]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(41);
+    bytes.push(0x00);
+    bytes.extend_from_slice(&block_height.to_le_bytes());
+    bytes.extend_from_slice(&block_hash);
+    sha256_hash(&bytes)
 }
 
 /// Note that, current proposed block is last block of `proposed_chain`, and `block_public_input` is all
@@ -68,15 +84,33 @@ pub struct ConsensusWitness {
     /// Current epoch's retarget block when `proposed_block_height % 2016 != 0`,
     /// last epoch's retarget block when `proposed_block_height % 2016 == 0`
     pub retarget_block: CircuitBlock,
+    /// Header MMR committed by the parent proof, the proposed block will be appended to it.
+    ///
+    /// Must be empty when proving the initial block.
+    pub header_mmr: MerkleMountainRange,
+    /// Witness of the signet solution of the proposed block, required on signet networks.
+    pub signet: Option<SignetWitness>,
 }
 
 impl ConsensusWitness {
-    pub fn new(proposed_chain: Vec<CircuitBlock>, retarget_block: CircuitBlock) -> Self {
+    pub fn new(
+        proposed_chain: Vec<CircuitBlock>,
+        retarget_block: CircuitBlock,
+        header_mmr: MerkleMountainRange,
+    ) -> Self {
         Self {
             proposed_chain,
             retarget_block,
+            header_mmr,
+            signet: None,
         }
     }
+
+    /// Adds the witness of the signet solution of the proposed block.
+    pub fn with_signet(mut self, signet: SignetWitness) -> Self {
+        self.signet = Some(signet);
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, Copy)]
@@ -90,6 +124,16 @@ pub struct ConsensusVerifierPublicInput {
     pub m_deep_tx_merkle_root: [u8; 32],
     /// Current block height.
     pub current_block_height: u64,
+    /// Little-endian bytes of the current block hash, the tip the next block must extend.
+    pub current_block_hash: [u8; 32],
+    /// Root of the MMR over all proven blocks, see [`header_mmr_leaf`].
+    ///
+    /// Allows proving the inclusion of a transaction in any historical block covered by the chain.
+    pub header_mmr_root: [u8; 32],
+    /// Number of blocks on top of the m-deep block, identical for all the proofs of the chain.
+    pub confirmation_depth: u32,
+    /// SHA256 of the signet challenge enforced on every block, zero if the chain is not a signet.
+    pub signet_challenge_hash: [u8; 32],
 }
 
 impl ConsensusVerifierPublicInput {
@@ -97,19 +141,31 @@ impl ConsensusVerifierPublicInput {
         compressed_block_public_input: [u8; 32],
         m_deep_tx_merkle_root: [u8; 32],
         current_block_height: u64,
+        current_block_hash: [u8; 32],
+        header_mmr_root: [u8; 32],
+        confirmation_depth: u32,
+        signet_challenge_hash: [u8; 32],
     ) -> Self {
         Self {
             compressed_block_public_input,
             m_deep_tx_merkle_root,
             current_block_height,
+            current_block_hash,
+            header_mmr_root,
+            confirmation_depth,
+            signet_challenge_hash,
         }
     }
 
     pub fn compute_hash(&self) -> [u8; 32] {
-        let mut bytes = Vec::with_capacity(72);
+        let mut bytes = Vec::with_capacity(172);
         bytes.extend_from_slice(&self.compressed_block_public_input);
         bytes.extend_from_slice(&self.m_deep_tx_merkle_root);
         bytes.extend_from_slice(&self.current_block_height.to_le_bytes());
+        bytes.extend_from_slice(&self.current_block_hash);
+        bytes.extend_from_slice(&self.header_mmr_root);
+        bytes.extend_from_slice(&self.confirmation_depth.to_le_bytes());
+        bytes.extend_from_slice(&self.signet_challenge_hash);
         sha256_hash(&bytes)
     }
 }
@@ -127,6 +183,17 @@ pub struct ConsensusInput {
     pub parent_proof_commitment: ConsensusVerifierPublicInput,
     /// Public values of an individual block.
     pub block_public_input: ConsensusBlockPublicInput,
+    /// Number of blocks on top of the m-deep block, `M_CONFIRMATION` by default.
+    ///
+    /// Must match the confirmation depth of the parent proof.
+    pub confirmation_depth: u32,
+    /// Challenge script that the blocks must satisfy, if the chain is a signet (BIP325).
+    ///
+    /// Must match the signet challenge of the parent proof. Only `OP_TRUE`, pay-to-pubkey and
+    /// bare multisig challenges are supported, with a push-only solution script, see
+    /// [`is_supported_signet_challenge`](crate::bitcoin::signet::is_supported_signet_challenge).
+    /// The proof can not be generated for any other block.
+    pub signet_challenge: Option<Vec<u8>>,
     /// Witness for proving an individual block in recursive prover mode.
     pub witness: ConsensusWitness,
 }
@@ -137,6 +204,8 @@ impl ConsensusInput {
         circuit_vkey_u32_hash: [u32; 8],
         parent_proof_commitment: ConsensusVerifierPublicInput,
         block_public_input: ConsensusBlockPublicInput,
+        confirmation_depth: u32,
+        signet_challenge: Option<Vec<u8>>,
         witness: ConsensusWitness,
     ) -> Self {
         Self {
@@ -144,6 +213,8 @@ impl ConsensusInput {
             circuit_vkey_u32_hash,
             parent_proof_commitment,
             block_public_input,
+            confirmation_depth,
+            signet_challenge,
             witness,
         }
     }
@@ -1,37 +1,353 @@
 //! This module defines the primitives used in the inclusion program.
 
+use crate::bitcoin::taproot::{p2tr_script_pubkey, taproot_output_key};
+use crate::bitcoin::transaction::Transaction;
+use crate::bitcoin::{double_sha256_hash, hash_pairs, to_little_endian_bytes};
+use crate::sha256_hash;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+/// Size of an inner node preimage, a transaction of this size could be confused with one.
+const INNER_NODE_SIZE: usize = 64;
+
+/// Index of the input spending the peg-in output.
+pub const PEGIN_INPUT_INDEX: usize = 0;
+/// Index of the output paying to the operator.
+pub const OPERATOR_OUTPUT_INDEX: usize = 0;
+
+/// Prefix of the coinbase output committing to the witness merkle root (BIP141).
+const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
 #[derive(Default, Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct MerkleProofStep {
     pub hash: [u8; 32],
     pub direction: bool,
 }
 
+/// Verifies the merkle proof of `tx_id` at `tx_index` in a block with `tx_count` transactions.
+///
+/// The transaction count is chosen by the prover and is not bound to the block header, so it
+/// does not fix the depth of the tree: an inner node, whose preimage is 64 bytes, could be
+/// presented as `tx_id`. Callers must derive `tx_id` from the transaction bytes and reject 64-byte
+/// transactions. Identical sibling nodes are rejected unless the node is the last one of an odd
+/// level, which rules out the mutated trees of CVE-2012-2459.
+pub fn verify_merkle_proof(
+    tx_id: [u8; 32],
+    tx_index: u32,
+    tx_count: u32,
+    merkle_proof: &[MerkleProofStep],
+    merkle_root: [u8; 32],
+) {
+    assert_eq!(
+        compute_merkle_proof_root(tx_id, tx_index, tx_count, merkle_proof),
+        merkle_root,
+        "The merkle proof does not match the merkle root."
+    );
+}
+
+/// Computes the merkle root from the merkle proof of `tx_id`, see [`verify_merkle_proof`].
+pub fn compute_merkle_proof_root(
+    tx_id: [u8; 32],
+    tx_index: u32,
+    tx_count: u32,
+    merkle_proof: &[MerkleProofStep],
+) -> [u8; 32] {
+    assert!(
+        tx_index < tx_count,
+        "The transaction index exceeds the transaction count."
+    );
+
+    let mut index = tx_index;
+    let mut level_width = tx_count;
+    let mut current = tx_id;
+    let mut steps = merkle_proof.iter();
+
+    while level_width > 1 {
+        let step = steps.next().expect("The merkle proof is too short.");
+        let is_left = index % 2 == 0;
+        assert_eq!(
+            step.direction, is_left,
+            "The merkle proof direction does not match the transaction index."
+        );
+
+        if is_left && index + 1 == level_width {
+            assert_eq!(
+                step.hash, current,
+                "The last node of an odd level must be paired with itself."
+            );
+        } else {
+            assert_ne!(
+                step.hash, current,
+                "The merkle proof contains identical sibling nodes."
+            );
+        }
+
+        current = if is_left {
+            hash_pairs(current, step.hash)
+        } else {
+            hash_pairs(step.hash, current)
+        };
+        index /= 2;
+        level_width = level_width.div_ceil(2);
+    }
+
+    assert!(steps.next().is_none(), "The merkle proof is too long.");
+    current
+}
+
+/// Merkle proof of several transactions of the same block, sharing the common nodes.
+#[derive(Default, Serialize, Deserialize, Clone, Debug)]
+pub struct MerkleMultiProof {
+    /// Positions of the proven transactions in the block, in strictly increasing order.
+    pub tx_indices: Vec<u32>,
+    /// Nodes which can not be computed from the proven transactions, ordered from the leaves
+    /// up to the root and from left to right within a level.
+    pub hashes: Vec<[u8; 32]>,
+}
+
+/// Computes the merkle root from the multiproof of `tx_ids`, which are at the positions given by
+/// `merkle_multiproof.tx_indices` in a block with `tx_count` transactions.
+///
+/// The same checks as in [`compute_merkle_proof_root`] apply to every pair of sibling nodes.
+pub fn compute_merkle_multiproof_root(
+    tx_ids: &[[u8; 32]],
+    tx_count: u32,
+    merkle_multiproof: &MerkleMultiProof,
+) -> [u8; 32] {
+    let tx_indices = &merkle_multiproof.tx_indices;
+    assert!(
+        !tx_ids.is_empty(),
+        "The multiproof must prove at least one transaction."
+    );
+    assert_eq!(
+        tx_ids.len(),
+        tx_indices.len(),
+        "The number of transaction ids does not match the number of indices."
+    );
+    assert!(
+        tx_indices.windows(2).all(|pair| pair[0] < pair[1]),
+        "The transaction indices must be strictly increasing."
+    );
+    assert!(
+        tx_indices[tx_indices.len() - 1] < tx_count,
+        "The transaction index exceeds the transaction count."
+    );
+
+    let mut level: Vec<(u32, [u8; 32])> = tx_indices
+        .iter()
+        .copied()
+        .zip(tx_ids.iter().copied())
+        .collect();
+    let mut level_width = tx_count;
+    let mut hashes = merkle_multiproof.hashes.iter();
+
+    while level_width > 1 {
+        let mut next_level = Vec::with_capacity(level.len());
+        let mut nodes = level.iter().peekable();
+        while let Some(&(index, current)) = nodes.next() {
+            let (left, right) = if index % 2 == 1 {
+                let sibling = *hashes.next().expect("The merkle multiproof is too short.");
+                (sibling, current)
+            } else if index + 1 == level_width {
+                // The last node of an odd level is paired with itself.
+                (current, current)
+            } else if let Some(&(_, sibling)) = nodes.next_if(|(next, _)| *next == index + 1) {
+                (current, sibling)
+            } else {
+                let sibling = *hashes.next().expect("The merkle multiproof is too short.");
+                (current, sibling)
+            };
+
+            if index % 2 == 1 || index + 1 != level_width {
+                assert_ne!(
+                    left, right,
+                    "The merkle multiproof contains identical sibling nodes."
+                );
+            }
+
+            next_level.push((index / 2, hash_pairs(left, right)));
+        }
+        level = next_level;
+        level_width = level_width.div_ceil(2);
+    }
+
+    assert!(
+        hashes.next().is_none(),
+        "The merkle multiproof is too long."
+    );
+    level[0].1
+}
+
+/// Returns the index of the coinbase output holding the witness commitment (BIP141).
+///
+/// If several outputs match the commitment pattern, the one with the highest index is used.
+pub fn witness_commitment_index(coinbase: &Transaction) -> Option<usize> {
+    coinbase.outputs.iter().rposition(|output| {
+        let script = &output.script_pubkey;
+        script.len() >= 38 && script.starts_with(&WITNESS_COMMITMENT_HEADER)
+    })
+}
+
+/// Returns the witness commitment of the coinbase transaction (BIP141).
+pub fn witness_commitment(coinbase: &Transaction) -> Option<[u8; 32]> {
+    witness_commitment_index(coinbase).map(|index| {
+        coinbase.outputs[index].script_pubkey[6..38]
+            .try_into()
+            .expect("Length checked by witness_commitment_index; qed")
+    })
+}
+
+/// Returns the outpoint (txid and output index) of the peg-in spent by `tx` and the operator
+/// public key it pays to.
+///
+/// The peg-in outpoint is the null outpoint for a coinbase transaction, and the operator public
+/// key is empty if the operator output is neither P2TR nor P2PK.
+pub fn extract_pegin_and_operator(tx: &Transaction) -> ([u8; 32], u32, Vec<u8>) {
+    let (pegin_txid, pegin_vout) = tx
+        .inputs
+        .get(PEGIN_INPUT_INDEX)
+        .map(|input| (input.prev_txid, input.prev_vout))
+        .unwrap_or_default();
+    let operator_pubkey = tx
+        .outputs
+        .get(OPERATOR_OUTPUT_INDEX)
+        .and_then(|output| output.locked_pubkey())
+        .map(|key| key.to_vec())
+        .unwrap_or_default();
+    (pegin_txid, pegin_vout, operator_pubkey)
+}
+
+/// Expected peg-in output of the proven transaction.
+///
+/// The output must pay to the taproot address derived from `internal_key` and
+/// `script_tree_root` (BIP341), with an amount of at least `min_amount`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PegInTemplate {
+    /// Index of the peg-in output in the transaction.
+    pub output_index: u32,
+    /// X-only internal key of the taproot output.
+    pub internal_key: [u8; 32],
+    /// Merkle root of the taproot script tree, `None` for a key-path only output.
+    pub script_tree_root: Option<[u8; 32]>,
+    /// Minimum amount in satoshis.
+    pub min_amount: u64,
+}
+
+impl PegInTemplate {
+    /// Returns the expected script pubkey of the peg-in output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal key is not a valid x-only public key.
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        let output_key = taproot_output_key(self.internal_key, self.script_tree_root)
+            .expect("The peg-in internal key is not a valid x-only public key.");
+        p2tr_script_pubkey(output_key)
+    }
+
+    /// Checks the peg-in output of `tx` against the template and returns its amount.
+    pub fn check(&self, tx: &Transaction) -> u64 {
+        let output = tx
+            .outputs
+            .get(self.output_index as usize)
+            .expect("The transaction has no output at the peg-in output index.");
+        assert_eq!(
+            output.script_pubkey,
+            self.script_pubkey(),
+            "The peg-in output does not pay to the expected taproot address."
+        );
+        assert!(
+            output.value >= self.min_amount,
+            "The peg-in amount is below the minimum amount."
+        );
+        output.value
+    }
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.output_index.to_le_bytes());
+        bytes.extend_from_slice(&self.internal_key);
+        match self.script_tree_root {
+            Some(root) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&root);
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&self.min_amount.to_le_bytes());
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InclusionPublicInput {
     pub tx_merkle_root: [u8; 32],
     pub tx_id: [u8; 32],
+    /// Witness transaction id, all zeros unless proven with [`SegwitInclusionWitness`].
+    pub wtx_id: [u8; 32],
+    /// Little-endian bytes of the peg-in txid spent by the transaction.
+    pub pegin_txid: [u8; 32],
+    /// Index of the peg-in output spent by the transaction.
+    pub pegin_vout: u32,
+    /// Operator public key extracted from the transaction output.
+    pub operator_pubkey: Vec<u8>,
+    /// Peg-in output template enforced on the transaction, if any.
+    pub pegin_template: Option<PegInTemplate>,
+    /// Amount of the peg-in output, zero unless a template is enforced.
+    pub pegin_amount: u64,
 }
 
 impl InclusionPublicInput {
     pub fn compute_hash(&self) -> [u8; 32] {
-        let mut bytes = Vec::with_capacity(64);
+        let mut bytes = Vec::with_capacity(226 + self.operator_pubkey.len());
         bytes.extend_from_slice(&self.tx_merkle_root);
         bytes.extend_from_slice(&self.tx_id);
+        bytes.extend_from_slice(&self.wtx_id);
+        bytes.extend_from_slice(&self.pegin_txid);
+        bytes.extend_from_slice(&self.pegin_vout.to_le_bytes());
+        // Length-prefixed as in the bincode encoding of the committed public values.
+        bytes.extend_from_slice(&(self.operator_pubkey.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.operator_pubkey);
+        // Options are prefixed with a tag byte, as in the bincode encoding.
+        match &self.pegin_template {
+            Some(template) => {
+                bytes.push(1);
+                template.write_bytes(&mut bytes);
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&self.pegin_amount.to_le_bytes());
         Sha256::digest(bytes).into()
     }
 }
 
+/// Witness for proving the witness transaction id against the coinbase witness commitment.
+///
+/// This makes the witness of the transaction (e.g. a taproot script-path spend) part of what is
+/// proven, which the transaction id alone does not cover.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SegwitInclusionWitness {
+    /// Bitcoin transaction serialized with its witness.
+    pub segwit_tx: Vec<u8>,
+    /// Merkle proof of the wtxid in the witness merkle tree, at the same index as the txid.
+    pub wtx_merkle_proof: Vec<MerkleProofStep>,
+    /// Coinbase transaction serialized with its witness, which holds the witness reserved value.
+    pub coinbase_tx: Vec<u8>,
+    /// Merkle proof of the coinbase transaction in the transaction merkle tree.
+    pub coinbase_merkle_proof: Vec<MerkleProofStep>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InclusionWitness {
     /// Serialized Bitcoin transaction using the legacy format (only input and output, no witness).
     pub legacy_tx: Vec<u8>,
+    /// Position of the transaction in the block.
+    pub tx_index: u32,
+    /// Number of transactions in the block, as claimed by the prover.
+    ///
+    /// Not bound to the block header and therefore not committed, see [`verify_merkle_proof`].
+    pub tx_count: u32,
     pub tx_merkle_proof: Vec<MerkleProofStep>,
-    // TODO: extract pubkey and txid from `legacy_tx`, which already contains these data.
-    pub operator_pubkey: Vec<u8>,
-    pub pegin_txid: [u8; 32],
+    /// Witness for proving the wtxid, `None` if only the txid is proven.
+    pub segwit: Option<SegwitInclusionWitness>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -41,24 +357,519 @@ pub struct InclusionInput {
 }
 
 impl InclusionInput {
+    /// # Panics
+    ///
+    /// Panics if `legacy_tx` is not a valid transaction.
     pub fn new(
         legacy_tx: Vec<u8>,
+        tx_index: u32,
         tx_merkle_proof: Vec<MerkleProofStep>,
         tx_id: [u8; 32],
         tx_merkle_root: [u8; 32],
+        tx_count: u32,
     ) -> Self {
+        let tx = Transaction::parse(&legacy_tx).expect("Failed to parse legacy transaction");
+        let (pegin_txid, pegin_vout, operator_pubkey) = extract_pegin_and_operator(&tx);
+
         Self {
             public_input: InclusionPublicInput {
                 tx_id,
                 tx_merkle_root,
+                wtx_id: [0u8; 32],
+                pegin_txid,
+                pegin_vout,
+                operator_pubkey,
+                pegin_template: None,
+                pegin_amount: 0,
             },
             witness: InclusionWitness {
                 legacy_tx,
+                tx_index,
+                tx_count,
                 tx_merkle_proof,
-                // TODO: proper value
-                operator_pubkey: Vec::from([0u8; 33]),
-                pegin_txid: [0u8; 32],
+                segwit: None,
             },
         }
     }
+
+    /// Additionally proves the witness transaction id of a segwit transaction.
+    pub fn with_segwit_witness(mut self, wtx_id: [u8; 32], segwit: SegwitInclusionWitness) -> Self {
+        self.public_input.wtx_id = wtx_id;
+        self.witness.segwit = Some(segwit);
+        self
+    }
+
+    /// Additionally enforces that the transaction pays to the peg-in output `template`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transaction does not match the template.
+    pub fn with_pegin_template(mut self, template: PegInTemplate) -> Self {
+        let tx = Transaction::parse(&self.witness.legacy_tx)
+            .expect("Failed to parse legacy transaction");
+        self.public_input.pegin_amount = template.check(&tx);
+        self.public_input.pegin_template = Some(template);
+        self
+    }
+
+    /// Verifies that the transaction is included in the block committed to by the public input.
+    pub fn verify(&self) {
+        let InclusionPublicInput {
+            tx_merkle_root,
+            tx_id,
+            wtx_id,
+            pegin_txid,
+            pegin_vout,
+            ref operator_pubkey,
+            pegin_template,
+            pegin_amount,
+        } = self.public_input;
+
+        // The depth of the tree is not bound, the transaction must not pass for an inner node.
+        assert_ne!(
+            self.witness.legacy_tx.len(),
+            INNER_NODE_SIZE,
+            "64-byte transactions are not supported."
+        );
+        assert_eq!(
+            double_sha256_hash(&self.witness.legacy_tx),
+            tx_id,
+            "The transaction id does not match the transaction."
+        );
+
+        let tx = Transaction::parse(&self.witness.legacy_tx)
+            .expect("Failed to parse legacy transaction");
+        assert!(
+            !tx.has_witness(),
+            "The legacy transaction must not have witness."
+        );
+        let (expected_pegin_txid, expected_pegin_vout, expected_operator_pubkey) =
+            extract_pegin_and_operator(&tx);
+        assert_eq!(
+            (pegin_txid, pegin_vout),
+            (expected_pegin_txid, expected_pegin_vout),
+            "The peg-in outpoint does not match the transaction."
+        );
+        assert_eq!(
+            operator_pubkey, &expected_operator_pubkey,
+            "The operator public key does not match the transaction."
+        );
+        match pegin_template {
+            Some(template) => assert_eq!(
+                template.check(&tx),
+                pegin_amount,
+                "The peg-in amount does not match the transaction."
+            ),
+            None => assert_eq!(
+                pegin_amount, 0,
+                "The peg-in amount must be zero without a template."
+            ),
+        }
+
+        verify_merkle_proof(
+            tx_id,
+            self.witness.tx_index,
+            self.witness.tx_count,
+            &self.witness.tx_merkle_proof,
+            tx_merkle_root,
+        );
+
+        match &self.witness.segwit {
+            Some(segwit) => self.verify_segwit(segwit),
+            None => assert_eq!(wtx_id, [0u8; 32], "The wtxid must be zero when not proven."),
+        }
+    }
+
+    fn verify_segwit(&self, segwit: &SegwitInclusionWitness) {
+        let InclusionPublicInput {
+            tx_merkle_root,
+            wtx_id,
+            ..
+        } = self.public_input;
+        let tx_count = self.witness.tx_count;
+
+        // As for the txid, the wtxid must not pass for an inner node of the witness tree.
+        assert_ne!(
+            segwit.segwit_tx.len(),
+            INNER_NODE_SIZE,
+            "64-byte transactions are not supported."
+        );
+        let tx = Transaction::parse(&segwit.segwit_tx).expect("Failed to parse segwit transaction");
+        assert_eq!(
+            tx.serialize_legacy(),
+            self.witness.legacy_tx,
+            "The segwit transaction does not match the legacy transaction."
+        );
+        assert_eq!(
+            tx.wtxid(),
+            wtx_id,
+            "The wtxid does not match the segwit transaction."
+        );
+
+        // The coinbase wtxid is defined as zero, its witness can not be proven.
+        assert_ne!(
+            self.witness.tx_index, 0,
+            "The coinbase wtxid can not be proven."
+        );
+        let witness_root = compute_merkle_proof_root(
+            wtx_id,
+            self.witness.tx_index,
+            tx_count,
+            &segwit.wtx_merkle_proof,
+        );
+
+        let coinbase =
+            Transaction::parse(&segwit.coinbase_tx).expect("Failed to parse coinbase transaction");
+        verify_merkle_proof(
+            coinbase.txid(),
+            0,
+            tx_count,
+            &segwit.coinbase_merkle_proof,
+            tx_merkle_root,
+        );
+
+        let witness_reserved_value = match coinbase.inputs.as_slice() {
+            [input] => match input.witness.as_slice() {
+                [value] if value.len() == 32 => value.as_slice(),
+                _ => panic!("The coinbase witness must be a single 32-byte reserved value."),
+            },
+            _ => panic!("The coinbase transaction must have exactly one input."),
+        };
+
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(&to_little_endian_bytes(witness_root));
+        bytes.extend_from_slice(witness_reserved_value);
+        assert_eq!(
+            witness_commitment(&coinbase),
+            Some(sha256_hash(&sha256_hash(&bytes))),
+            "The witness merkle root does not match the coinbase witness commitment."
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::get_merkle_root;
+    use crate::bitcoin::transaction::tests::{LEGACY_TX, hex_hash};
+    use crate::bitcoin::transaction::{TxIn, TxOut};
+
+    fn leaves(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| sha256_hash(&[i])).collect()
+    }
+
+    /// Returns the merkle proof of the leaf at `index`, pairing the last node of odd levels with
+    /// itself.
+    fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Vec<MerkleProofStep> {
+        let mut level = leaves.to_vec();
+        let mut index = index;
+        let mut proof = Vec::new();
+        while level.len() > 1 {
+            let direction = index % 2 == 0;
+            let sibling = if direction { index + 1 } else { index - 1 };
+            proof.push(MerkleProofStep {
+                hash: level[sibling.min(level.len() - 1)],
+                direction,
+            });
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pairs(pair[0], *pair.last().unwrap()))
+                .collect();
+            index /= 2;
+        }
+        proof
+    }
+
+    #[test]
+    fn merkle_proofs_verify_in_odd_width_trees() {
+        let leaves = leaves(7);
+        let root = get_merkle_root(leaves.clone());
+        for (index, leaf) in leaves.iter().enumerate() {
+            verify_merkle_proof(*leaf, index as u32, 7, &merkle_proof(&leaves, index), root);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The merkle proof contains identical sibling nodes.")]
+    fn rejects_a_duplicated_last_leaf() {
+        // CVE-2012-2459: [a, b, c, c] has the same root as [a, b, c].
+        let leaves = leaves(3);
+        let root = get_merkle_root(leaves.clone());
+        let proof = vec![
+            MerkleProofStep {
+                hash: leaves[2],
+                direction: false,
+            },
+            MerkleProofStep {
+                hash: hash_pairs(leaves[0], leaves[1]),
+                direction: false,
+            },
+        ];
+        verify_merkle_proof(leaves[2], 3, 4, &proof, root);
+    }
+
+    #[test]
+    #[should_panic(expected = "The merkle proof contains identical sibling nodes.")]
+    fn rejects_a_leaf_paired_with_its_duplicate() {
+        let leaves = leaves(3);
+        let root = get_merkle_root(leaves.clone());
+        let proof = vec![
+            MerkleProofStep {
+                hash: leaves[2],
+                direction: true,
+            },
+            MerkleProofStep {
+                hash: hash_pairs(leaves[0], leaves[1]),
+                direction: false,
+            },
+        ];
+        verify_merkle_proof(leaves[2], 2, 4, &proof, root);
+    }
+
+    #[test]
+    #[should_panic(expected = "The last node of an odd level must be paired with itself.")]
+    fn rejects_a_last_node_paired_with_another_node() {
+        let leaves = leaves(3);
+        let mut proof = merkle_proof(&leaves, 2);
+        proof[0].hash = leaves[1];
+        compute_merkle_proof_root(leaves[2], 2, 3, &proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction index exceeds the transaction count.")]
+    fn rejects_an_out_of_range_tx_index() {
+        let leaves = leaves(3);
+        let root = get_merkle_root(leaves.clone());
+        verify_merkle_proof(leaves[2], 3, 3, &merkle_proof(&leaves, 2), root);
+    }
+
+    #[test]
+    #[should_panic(expected = "The merkle multiproof contains identical sibling nodes.")]
+    fn multiproof_rejects_a_duplicated_last_leaf() {
+        // CVE-2012-2459: [a, b, c, c] has the same root as [a, b, c].
+        let leaves = leaves(3);
+        let multiproof = MerkleMultiProof {
+            tx_indices: vec![2, 3],
+            hashes: vec![hash_pairs(leaves[0], leaves[1])],
+        };
+        assert_eq!(
+            compute_merkle_multiproof_root(&[leaves[2], leaves[2]], 4, &multiproof),
+            get_merkle_root(leaves)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction indices must be strictly increasing.")]
+    fn multiproof_rejects_unsorted_indices() {
+        let leaves = leaves(4);
+        let multiproof = MerkleMultiProof {
+            tx_indices: vec![2, 1],
+            hashes: vec![leaves[0], leaves[3]],
+        };
+        compute_merkle_multiproof_root(&[leaves[2], leaves[1]], 4, &multiproof);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction indices must be strictly increasing.")]
+    fn multiproof_rejects_duplicate_indices() {
+        let leaves = leaves(4);
+        let multiproof = MerkleMultiProof {
+            tx_indices: vec![1, 1],
+            hashes: vec![leaves[0], hash_pairs(leaves[2], leaves[3])],
+        };
+        compute_merkle_multiproof_root(&[leaves[1], leaves[1]], 4, &multiproof);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction index exceeds the transaction count.")]
+    fn multiproof_rejects_an_out_of_range_index() {
+        let leaves = leaves(3);
+        let multiproof = MerkleMultiProof {
+            tx_indices: vec![1, 3],
+            hashes: vec![leaves[0]],
+        };
+        compute_merkle_multiproof_root(&[leaves[1], leaves[2]], 3, &multiproof);
+    }
+
+    fn tx_paying_to(script_pubkey: Vec<u8>) -> Transaction {
+        Transaction {
+            version: 2,
+            inputs: vec![TxIn {
+                prev_txid: [1u8; 32],
+                prev_vout: 3,
+                ..Default::default()
+            }],
+            outputs: vec![TxOut {
+                value: 10_000,
+                script_pubkey,
+            }],
+            lock_time: 0,
+        }
+    }
+
+    #[test]
+    fn extracts_pegin_and_operator() {
+        let key = [2u8; 32];
+        let mut p2tr = vec![0x51, 0x20];
+        p2tr.extend_from_slice(&key);
+        assert_eq!(
+            extract_pegin_and_operator(&tx_paying_to(p2tr)),
+            ([1u8; 32], 3, key.to_vec())
+        );
+
+        let mut p2pk = vec![0x21, 0x02];
+        p2pk.extend_from_slice(&key);
+        p2pk.push(0xac);
+        assert_eq!(
+            extract_pegin_and_operator(&tx_paying_to(p2pk.clone())),
+            ([1u8; 32], 3, p2pk[1..34].to_vec())
+        );
+
+        let mut p2wpkh = vec![0x00, 0x14];
+        p2wpkh.extend_from_slice(&[3u8; 20]);
+        assert_eq!(
+            extract_pegin_and_operator(&tx_paying_to(p2wpkh)),
+            ([1u8; 32], 3, Vec::new())
+        );
+    }
+
+    #[test]
+    fn inclusion_input_commits_pegin_and_operator() {
+        let legacy_tx = hex::decode(LEGACY_TX).unwrap();
+        let tx_id = double_sha256_hash(&legacy_tx);
+        let input = InclusionInput::new(legacy_tx, 0, Vec::new(), tx_id, tx_id, 1);
+        assert_eq!(
+            input.public_input.pegin_txid,
+            hex_hash("550b131da77c446e27bbde2a7c5d7a7bf6539fe2a44b6de233a7325317814f7e")
+        );
+        // The operator output is P2PKH, which does not reveal the key.
+        assert!(input.public_input.operator_pubkey.is_empty());
+        input.verify();
+    }
+
+    fn pegin_spending_input() -> InclusionInput {
+        let legacy_tx = tx_paying_to(vec![0x51]).serialize_legacy();
+        let tx_id = double_sha256_hash(&legacy_tx);
+        InclusionInput::new(legacy_tx, 0, Vec::new(), tx_id, tx_id, 1)
+    }
+
+    #[test]
+    fn inclusion_input_commits_the_pegin_outpoint() {
+        let input = pegin_spending_input();
+        assert_eq!(input.public_input.pegin_txid, [1u8; 32]);
+        assert_eq!(input.public_input.pegin_vout, 3);
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The peg-in outpoint does not match the transaction.")]
+    fn rejects_a_mismatched_pegin_vout() {
+        let mut input = pegin_spending_input();
+        input.public_input.pegin_vout = 0;
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction id does not match the transaction.")]
+    fn rejects_a_mismatched_tx_id() {
+        let legacy_tx = hex::decode(LEGACY_TX).unwrap();
+        let tx_id = double_sha256_hash(&legacy_tx);
+        let mut input = InclusionInput::new(legacy_tx, 0, Vec::new(), tx_id, tx_id, 1);
+        input.public_input.tx_id = [0u8; 32];
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The operator public key does not match the transaction.")]
+    fn rejects_a_mismatched_operator_pubkey() {
+        let legacy_tx = hex::decode(LEGACY_TX).unwrap();
+        let tx_id = double_sha256_hash(&legacy_tx);
+        let mut input = InclusionInput::new(legacy_tx, 0, Vec::new(), tx_id, tx_id, 1);
+        input.public_input.operator_pubkey = vec![2u8; 33];
+        input.verify();
+    }
+
+    /// Template of the second `scriptPubKey` vector of BIP341.
+    fn pegin_template() -> PegInTemplate {
+        PegInTemplate {
+            output_index: 1,
+            internal_key: hex_hash(
+                "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+            ),
+            script_tree_root: Some(hex_hash(
+                "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21",
+            )),
+            min_amount: 100_000,
+        }
+    }
+
+    fn pegin_tx(amount: u64) -> Transaction {
+        let mut tx = tx_paying_to(vec![0x51, 0x20, 0x00]);
+        tx.outputs.push(TxOut {
+            value: amount,
+            script_pubkey: hex::decode(
+                "5120147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+            )
+            .unwrap(),
+        });
+        tx
+    }
+
+    #[test]
+    fn pegin_template_accepts_the_expected_output() {
+        assert_eq!(pegin_template().check(&pegin_tx(100_000)), 100_000);
+
+        let legacy_tx = pegin_tx(250_000).serialize_legacy();
+        let tx_id = double_sha256_hash(&legacy_tx);
+        let input = InclusionInput::new(legacy_tx, 0, Vec::new(), tx_id, tx_id, 1)
+            .with_pegin_template(pegin_template());
+        assert_eq!(input.public_input.pegin_amount, 250_000);
+        input.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The peg-in output does not pay to the expected taproot address.")]
+    fn pegin_template_rejects_another_output_index() {
+        let template = PegInTemplate {
+            output_index: 0,
+            ..pegin_template()
+        };
+        template.check(&pegin_tx(100_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The transaction has no output at the peg-in output index.")]
+    fn pegin_template_rejects_a_missing_output() {
+        let template = PegInTemplate {
+            output_index: 2,
+            ..pegin_template()
+        };
+        template.check(&pegin_tx(100_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The peg-in output does not pay to the expected taproot address.")]
+    fn pegin_template_rejects_another_script_tree_root() {
+        let template = PegInTemplate {
+            script_tree_root: Some([1u8; 32]),
+            ..pegin_template()
+        };
+        template.check(&pegin_tx(100_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The peg-in output does not pay to the expected taproot address.")]
+    fn pegin_template_rejects_a_key_path_only_output() {
+        let template = PegInTemplate {
+            script_tree_root: None,
+            ..pegin_template()
+        };
+        template.check(&pegin_tx(100_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The peg-in amount is below the minimum amount.")]
+    fn pegin_template_rejects_an_amount_below_the_minimum() {
+        pegin_template().check(&pegin_tx(99_999));
+    }
 }
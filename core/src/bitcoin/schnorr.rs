@@ -0,0 +1,255 @@
+//! Verification of BIP340 Schnorr signatures.
+//!
+//! The curve arithmetic is done by `k256`, which the workspace patches with the SP1 secp256k1
+//! precompiles: verification is accelerated inside the zkVM and runs natively on the host.
+
+use k256::schnorr::{Signature, VerifyingKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchnorrError {
+    /// The public key is not the x coordinate of a point on the curve.
+    InvalidPublicKey,
+    /// The signature is not encoded as `r || s` with `r` a field element and `s` a scalar.
+    InvalidSignature,
+    /// The signature does not match the public key and message.
+    VerificationFailed,
+}
+
+impl core::fmt::Display for SchnorrError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidPublicKey => write!(f, "invalid x-only public key"),
+            Self::InvalidSignature => write!(f, "invalid schnorr signature encoding"),
+            Self::VerificationFailed => write!(f, "schnorr signature verification failed"),
+        }
+    }
+}
+
+impl core::error::Error for SchnorrError {}
+
+/// Verifies the BIP340 signature of `message` by the x-only `pubkey`.
+///
+/// The message is not hashed beforehand, as specified by BIP340 it can be of any length.
+pub fn verify_schnorr_signature(
+    pubkey: &[u8; 32],
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<(), SchnorrError> {
+    let verifying_key =
+        VerifyingKey::from_bytes(pubkey).map_err(|_| SchnorrError::InvalidPublicKey)?;
+    let signature =
+        Signature::try_from(signature.as_slice()).map_err(|_| SchnorrError::InvalidSignature)?;
+    verifying_key
+        .verify_raw(message, &signature)
+        .map_err(|_| SchnorrError::VerificationFailed)
+}
+
+/// Test vectors shared with the tests running the verification inside the zkVM.
+#[cfg(any(test, feature = "test-vectors"))]
+pub mod test_vectors {
+    use super::SchnorrError;
+
+    /// Test vector of BIP340, with hex-encoded fields.
+    pub struct Vector {
+        pub index: u8,
+        pub public_key: &'static str,
+        pub message: &'static str,
+        pub signature: &'static str,
+        pub result: Result<(), SchnorrError>,
+    }
+
+    /// Test vectors of BIP340 (`test-vectors.csv`), with the reason of each failure.
+    pub const BIP340_VECTORS: &[Vector] = &[
+        Vector {
+            index: 0,
+            public_key: "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            message: "0000000000000000000000000000000000000000000000000000000000000000",
+            signature: "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA8215\
+                25F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+            result: Ok(()),
+        },
+        Vector {
+            index: 1,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE3341\
+                8906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+            result: Ok(()),
+        },
+        Vector {
+            index: 2,
+            public_key: "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+            message: "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+            signature: "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1B\
+                AB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+            result: Ok(()),
+        },
+        // test fails if msg is reduced modulo p or n
+        Vector {
+            index: 3,
+            public_key: "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+            message: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            signature: "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC\
+                97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+            result: Ok(()),
+        },
+        Vector {
+            index: 4,
+            public_key: "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+            message: "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+            signature: "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C63\
+                76AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+            result: Ok(()),
+        },
+        // public key not on the curve
+        Vector {
+            index: 5,
+            public_key: "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            result: Err(SchnorrError::InvalidPublicKey),
+        },
+        // has_even_y(R) is false
+        Vector {
+            index: 6,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A1460297556\
+                3CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+            result: Err(SchnorrError::VerificationFailed),
+        },
+        // negated message
+        Vector {
+            index: 7,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F\
+                28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+            result: Err(SchnorrError::VerificationFailed),
+        },
+        // negated s value
+        Vector {
+            index: 8,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+            result: Err(SchnorrError::VerificationFailed),
+        },
+        // sG - eP is infinite, with x(inf) defined as 0, `k256` rejects r = 0 when decoding
+        Vector {
+            index: 9,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "0000000000000000000000000000000000000000000000000000000000000000\
+                123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+            result: Err(SchnorrError::InvalidSignature),
+        },
+        // sG - eP is infinite, with x(inf) defined as 1
+        Vector {
+            index: 10,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "0000000000000000000000000000000000000000000000000000000000000001\
+                7615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+            result: Err(SchnorrError::VerificationFailed),
+        },
+        // sig[0:32] is not an x coordinate on the curve
+        Vector {
+            index: 11,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D\
+                69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            result: Err(SchnorrError::VerificationFailed),
+        },
+        // sig[0:32] is equal to the field size
+        Vector {
+            index: 12,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F\
+                69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            result: Err(SchnorrError::InvalidSignature),
+        },
+        // sig[32:64] is equal to the curve order
+        Vector {
+            index: 13,
+            public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            result: Err(SchnorrError::InvalidSignature),
+        },
+        // public key exceeds the field size
+        Vector {
+            index: 14,
+            public_key: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+            message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            signature: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            result: Err(SchnorrError::InvalidPublicKey),
+        },
+        // empty message
+        Vector {
+            index: 15,
+            public_key: "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            message: "",
+            signature: "71535DB165ECD9FBBC046E5FFAEA61186BB6AD436732FCCC25291A55895464CF\
+                6069CE26BF03466228F19A3A62DB8A649F2D560FAC652827D1AF0574E427AB63",
+            result: Ok(()),
+        },
+        // 1-byte message
+        Vector {
+            index: 16,
+            public_key: "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            message: "11",
+            signature: "08A20A0AFEF64124649232E0693C583AB1B9934AE63B4C3511F3AE1134C6A303\
+                EA3173BFEA6683BD101FA5AA5DBC1996FE7CACFC5A577D33EC14564CEC2BACBF",
+            result: Ok(()),
+        },
+        // 17-byte message
+        Vector {
+            index: 17,
+            public_key: "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            message: "0102030405060708090A0B0C0D0E0F1011",
+            signature: "5130F39A4059B43BC7CAC09A19ECE52B5D8699D1A71E3C52DA9AFDB6B50AC370\
+                C4A482B77BF960F8681540E25B6771ECE1E5A37FD80E5A51897C5566A97EA5A5",
+            result: Ok(()),
+        },
+        // 100-byte message
+        Vector {
+            index: 18,
+            public_key: "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            message: "9999999999999999999999999999999999999999999999999999999999999999\
+                9999999999999999999999999999999999999999999999999999999999999999\
+                9999999999999999999999999999999999999999999999999999999999999999\
+                99999999",
+            signature: "403B12B0D8555A344175EA7EC746566303321E5DBFA8BE6F091635163ECA79A8\
+                585ED3E3170807E7C03B720FC54C7B23897FCBA0E9D0B4A06894CFD249F22367",
+            result: Ok(()),
+        },
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_vectors::BIP340_VECTORS;
+    use super::*;
+
+    #[test]
+    fn bip340_vectors() {
+        for vector in BIP340_VECTORS {
+            let public_key = hex::decode(vector.public_key).unwrap().try_into().unwrap();
+            let message = hex::decode(vector.message).unwrap();
+            let signature = hex::decode(vector.signature).unwrap().try_into().unwrap();
+            assert_eq!(
+                verify_schnorr_signature(&public_key, &message, &signature),
+                vector.result,
+                "vector {}",
+                vector.index
+            );
+        }
+    }
+}
@@ -0,0 +1,637 @@
+//! Verification of the block signature of signet (BIP325).
+//!
+//! A signet block is valid only if the solution embedded in its coinbase satisfies the challenge
+//! script of the network. The solution spends a virtual `to_spend` transaction paying to the
+//! challenge, which commits to the block header with the solution itself removed.
+//!
+//! Only the challenge scripts used in practice are supported: bare multisig (as in the default
+//! signet), pay-to-pubkey and `OP_TRUE`, with a push-only solution script as produced by the
+//! signet miner of Bitcoin Core. Scripts are not evaluated in general, so a block whose solution
+//! is valid by consensus but uses other opcodes can not be proven.
+
+use crate::bitcoin::block::CircuitBlock;
+use crate::bitcoin::inclusion::{
+    MerkleProofStep, compute_merkle_proof_root, witness_commitment_index,
+};
+use crate::bitcoin::to_little_endian_bytes;
+use crate::bitcoin::transaction::{Reader, Transaction, TxIn, TxOut};
+use crate::sha256_hash;
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the push holding the signet solution in the witness commitment output.
+pub const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+
+/// Challenge of the default signet, a 1-of-2 bare multisig.
+pub const DEFAULT_SIGNET_CHALLENGE: [u8; 71] = [
+    0x51, 0x21, 0x03, 0xad, 0x5e, 0x0e, 0xda, 0xd1, 0x8c, 0xb1, 0xf0, 0xfc, 0x0d, 0x28, 0xa3, 0xd4,
+    0xf1, 0xf3, 0xe4, 0x45, 0x64, 0x03, 0x37, 0x48, 0x9a, 0xbb, 0x10, 0x40, 0x4f, 0x2d, 0x1e, 0x08,
+    0x6b, 0xe4, 0x30, 0x21, 0x03, 0x59, 0xef, 0x50, 0x21, 0x96, 0x4f, 0xe2, 0x2d, 0x6f, 0x8e, 0x05,
+    0xb2, 0x46, 0x3c, 0x95, 0x40, 0xce, 0x96, 0x88, 0x3f, 0xe3, 0xb2, 0x78, 0x76, 0x0f, 0x04, 0x8f,
+    0x51, 0x89, 0xf2, 0xe6, 0xc4, 0x52, 0xae,
+];
+
+const OP_0: u8 = 0x00;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_1: u8 = 0x51;
+const OP_16: u8 = 0x60;
+const OP_RETURN: u8 = 0x6a;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+const SIGHASH_NONE: u8 = 0x02;
+
+/// Witness for verifying the signet solution of a block.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SignetWitness {
+    /// Coinbase transaction of the block, its witness is ignored.
+    pub coinbase_tx: Vec<u8>,
+    /// Merkle proof of the coinbase transaction in the transaction merkle tree.
+    pub coinbase_merkle_proof: Vec<MerkleProofStep>,
+    /// Number of transactions in the block.
+    pub tx_count: u32,
+}
+
+/// Reads the next script operation at `pc`, returning the opcode and the pushed data.
+///
+/// Returns `None` at the end of the script or if a push exceeds the script.
+fn read_op(script: &[u8], pc: &mut usize) -> Option<(u8, Vec<u8>)> {
+    let opcode = *script.get(*pc)?;
+    *pc += 1;
+
+    let len = match opcode {
+        0..OP_PUSHDATA1 => opcode as usize,
+        OP_PUSHDATA1 | OP_PUSHDATA2 | OP_PUSHDATA4 => {
+            let size_len = match opcode {
+                OP_PUSHDATA1 => 1,
+                OP_PUSHDATA2 => 2,
+                _ => 4,
+            };
+            let size = script.get(*pc..*pc + size_len)?;
+            *pc += size_len;
+            size.iter()
+                .rev()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize)
+        }
+        _ => return Some((opcode, Vec::new())),
+    };
+
+    let data = script.get(*pc..pc.checked_add(len)?)?.to_vec();
+    *pc += len;
+    Some((opcode, data))
+}
+
+/// Appends a push of `data` using the smallest push opcode, as `CScript::operator<<`.
+fn write_push(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        len if len < OP_PUSHDATA1 as usize => script.push(len as u8),
+        len if len <= 0xff => script.extend_from_slice(&[OP_PUSHDATA1, len as u8]),
+        len if len <= 0xffff => {
+            script.push(OP_PUSHDATA2);
+            script.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        len => {
+            script.push(OP_PUSHDATA4);
+            script.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+    }
+    script.extend_from_slice(data);
+}
+
+/// Extracts the signet solution from the witness commitment script, and returns the script with
+/// the solution removed (`FetchAndClearCommitmentSection` in Bitcoin Core).
+///
+/// Returns `None` if the script holds no solution.
+pub fn fetch_and_clear_signet_solution(script: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut replacement = Vec::with_capacity(script.len());
+    let mut solution = None;
+    let mut pc = 0;
+
+    while let Some((opcode, mut data)) = read_op(script, &mut pc) {
+        if data.is_empty() {
+            replacement.push(opcode);
+            continue;
+        }
+        // The push only counts if it has the header and some data.
+        if solution.is_none()
+            && data.len() > SIGNET_HEADER.len()
+            && data.starts_with(&SIGNET_HEADER)
+        {
+            solution = Some(data.split_off(SIGNET_HEADER.len()));
+        }
+        write_push(&mut replacement, &data);
+    }
+
+    solution.map(|solution| (replacement, solution))
+}
+
+/// Returns the data pushed by a push-only script.
+fn push_only_stack(script: &[u8]) -> Vec<Vec<u8>> {
+    let mut stack = Vec::new();
+    let mut pc = 0;
+    while pc < script.len() {
+        let (opcode, data) =
+            read_op(script, &mut pc).expect("The signet solution script is malformed.");
+        match opcode {
+            OP_0..=OP_PUSHDATA4 => stack.push(data),
+            OP_1..=OP_16 => stack.push(vec![opcode - OP_1 + 1]),
+            _ => panic!("The signet solution script must be push-only."),
+        }
+    }
+    stack
+}
+
+/// Computes the legacy signature hash of the single input of `tx` (`SignatureHash` in Bitcoin
+/// Core), given that `tx` has exactly one input and one output.
+fn legacy_sighash(tx: &Transaction, script_code: &[u8], sighash_type: u8) -> [u8; 32] {
+    let mut tx = tx.clone();
+    tx.inputs[0].script_sig = script_code.to_vec();
+    if sighash_type & 0x1f == SIGHASH_NONE {
+        tx.outputs.clear();
+    }
+    // SIGHASH_SINGLE and SIGHASH_ANYONECANPAY are no-ops for a single input and output.
+    let mut bytes = tx.serialize_legacy();
+    bytes.extend_from_slice(&(sighash_type as u32).to_le_bytes());
+    sha256_hash(&sha256_hash(&bytes))
+}
+
+/// Checks an ECDSA signature with appended sighash type against `pubkey`.
+fn check_signature(sig: &[u8], pubkey: &[u8], tx: &Transaction, script_code: &[u8]) -> bool {
+    let Some((&sighash_type, der)) = sig.split_last() else {
+        return false;
+    };
+    let (Ok(signature), Ok(verifying_key)) = (
+        Signature::from_der(der),
+        VerifyingKey::from_sec1_bytes(pubkey),
+    ) else {
+        return false;
+    };
+    // High-S signatures are valid by consensus, but rejected by `k256`.
+    let signature = signature.normalize_s().unwrap_or(signature);
+    let sighash = legacy_sighash(tx, script_code, sighash_type);
+    verifying_key.verify_prehash(&sighash, &signature).is_ok()
+}
+
+/// Signet challenge script, restricted to the supported forms.
+enum Challenge {
+    /// `OP_TRUE`
+    True,
+    /// `<pubkey> OP_CHECKSIG`
+    PubKey(Vec<u8>),
+    /// `OP_m <pubkey>... OP_n OP_CHECKMULTISIG`
+    Multisig {
+        required: usize,
+        pubkeys: Vec<Vec<u8>>,
+    },
+}
+
+impl Challenge {
+    /// Returns `None` if the challenge is malformed or not one of the supported forms.
+    fn parse(challenge: &[u8]) -> Option<Self> {
+        let mut ops = Vec::new();
+        let mut pc = 0;
+        while pc < challenge.len() {
+            ops.push(read_op(challenge, &mut pc)?);
+        }
+
+        match ops.as_slice() {
+            [(OP_1, _)] => Some(Self::True),
+            [(_, pubkey), (OP_CHECKSIG, _)] if !pubkey.is_empty() => {
+                Some(Self::PubKey(pubkey.clone()))
+            }
+            [
+                (m @ OP_1..=OP_16, _),
+                keys @ ..,
+                (n @ OP_1..=OP_16, _),
+                (OP_CHECKMULTISIG, _),
+            ] if keys.len() == (n - OP_1 + 1) as usize
+                && keys.iter().all(|(_, pubkey)| !pubkey.is_empty())
+                && m <= n =>
+            {
+                Some(Self::Multisig {
+                    required: (m - OP_1 + 1) as usize,
+                    pubkeys: keys.iter().map(|(_, pubkey)| pubkey.clone()).collect(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns whether `challenge` is one of the supported challenge scripts, see the module
+/// documentation.
+///
+/// Blocks of a signet with another challenge can not be proven, hosts should check it up front.
+pub fn is_supported_signet_challenge(challenge: &[u8]) -> bool {
+    Challenge::parse(challenge).is_some()
+}
+
+/// Evaluates `challenge` against the stack produced by the solution.
+fn check_challenge(challenge: &[u8], stack: &[Vec<u8>], tx: &Transaction) -> bool {
+    match Challenge::parse(challenge).expect("The signet challenge script is not supported.") {
+        Challenge::True => true,
+        Challenge::PubKey(pubkey) => stack
+            .last()
+            .is_some_and(|sig| check_signature(sig, &pubkey, tx, challenge)),
+        Challenge::Multisig { required, pubkeys } => {
+            // The extra element popped by OP_CHECKMULTISIG must be empty (BIP147).
+            let Some(first) = stack.len().checked_sub(required + 1) else {
+                return false;
+            };
+            if !stack[first].is_empty() {
+                return false;
+            }
+
+            // Signatures must be in the same order as the keys.
+            let mut pubkeys = pubkeys.iter();
+            stack[first + 1..]
+                .iter()
+                .all(|sig| pubkeys.any(|pubkey| check_signature(sig, pubkey, tx, challenge)))
+        }
+    }
+}
+
+/// Verifies the signet solution of `block` against `challenge`.
+///
+/// # Panics
+///
+/// Panics if the block has no valid solution.
+pub fn verify_signet_solution(block: &CircuitBlock, challenge: &[u8], witness: &SignetWitness) {
+    let mut coinbase =
+        Transaction::parse(&witness.coinbase_tx).expect("Failed to parse coinbase transaction");
+    // A 64-byte transaction could be an inner node of the merkle tree.
+    assert_ne!(
+        coinbase.serialize_legacy().len(),
+        64,
+        "The coinbase transaction must not be 64 bytes."
+    );
+
+    assert_eq!(
+        compute_merkle_proof_root(
+            coinbase.txid(),
+            0,
+            witness.tx_count,
+            &witness.coinbase_merkle_proof
+        ),
+        to_little_endian_bytes(block.merkle_root),
+        "The coinbase transaction is not part of the block."
+    );
+
+    let commitment_index =
+        witness_commitment_index(&coinbase).expect("The signet block has no witness commitment.");
+    let commitment_script = &mut coinbase.outputs[commitment_index].script_pubkey;
+
+    // Without a solution, the challenge is evaluated against an empty scriptSig and witness.
+    let (script_sig, solution_witness) = match fetch_and_clear_signet_solution(commitment_script) {
+        Some((replacement, solution)) => {
+            *commitment_script = replacement;
+            let mut reader = Reader::new(&solution);
+            let script_sig = reader
+                .read_var_bytes()
+                .expect("Failed to parse the signet solution.");
+            let witness = reader
+                .read_vec(Reader::read_var_bytes)
+                .expect("Failed to parse the signet solution.");
+            assert!(reader.is_empty(), "The signet solution has trailing bytes.");
+            (script_sig, witness)
+        }
+        None => (Vec::new(), Vec::new()),
+    };
+
+    // The supported challenges are not witness programs, hence the witness must be empty.
+    assert!(
+        solution_witness.is_empty(),
+        "The signet solution must not have a witness."
+    );
+
+    let signet_merkle_root = compute_merkle_proof_root(
+        coinbase.txid(),
+        0,
+        witness.tx_count,
+        &witness.coinbase_merkle_proof,
+    );
+
+    let mut block_data = Vec::with_capacity(72);
+    block_data.extend_from_slice(&block.version);
+    block_data.extend_from_slice(&block.prev_blockhash);
+    block_data.extend_from_slice(&to_little_endian_bytes(signet_merkle_root));
+    block_data.extend_from_slice(&block.time);
+
+    let mut to_spend_script_sig = vec![OP_0];
+    write_push(&mut to_spend_script_sig, &block_data);
+    let to_spend = Transaction {
+        version: 0,
+        inputs: vec![TxIn {
+            prev_txid: [0u8; 32],
+            prev_vout: u32::MAX,
+            script_sig: to_spend_script_sig,
+            sequence: 0,
+            witness: Vec::new(),
+        }],
+        outputs: vec![TxOut {
+            value: 0,
+            script_pubkey: challenge.to_vec(),
+        }],
+        lock_time: 0,
+    };
+
+    let to_sign = Transaction {
+        version: 0,
+        inputs: vec![TxIn {
+            prev_txid: to_spend.txid(),
+            prev_vout: 0,
+            script_sig,
+            sequence: 0,
+            witness: Vec::new(),
+        }],
+        outputs: vec![TxOut {
+            value: 0,
+            script_pubkey: vec![OP_RETURN],
+        }],
+        lock_time: 0,
+    };
+
+    let stack = push_only_stack(&to_sign.inputs[0].script_sig);
+    assert!(
+        check_challenge(challenge, &stack, &to_sign),
+        "The signet solution does not satisfy the challenge."
+    );
+}
+
+/// Returns the hash of the signet challenge committed by the consensus proofs.
+pub fn signet_challenge_hash(challenge: &[u8]) -> [u8; 32] {
+    sha256_hash(challenge)
+}
+
+#[cfg(all(test, feature = "bitcoin"))]
+mod tests {
+    use super::*;
+    use ::bitcoin::blockdata::opcodes::all::{
+        OP_CHECKMULTISIG, OP_PUSHNUM_1, OP_PUSHNUM_2, OP_RETURN as RETURN,
+    };
+    use ::bitcoin::consensus::serialize;
+    use ::bitcoin::hashes::Hash;
+    use ::bitcoin::script::{Builder, PushBytesBuf};
+    use ::bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+    use ::bitcoin::sighash::{EcdsaSighashType, SighashCache};
+    use ::bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Witness};
+
+    const HEIGHT: u64 = 1000;
+
+    fn push_bytes(bytes: &[u8]) -> PushBytesBuf {
+        PushBytesBuf::try_from(bytes.to_vec()).unwrap()
+    }
+
+    /// Returns the keys of a 1-of-2 bare multisig challenge, shaped like the default signet one.
+    fn challenge_keys() -> [SecretKey; 2] {
+        [[1u8; 32], [2u8; 32]].map(|key| SecretKey::from_slice(&key).unwrap())
+    }
+
+    fn challenge() -> ScriptBuf {
+        let secp = Secp256k1::new();
+        let [first, second] = challenge_keys().map(|key| key.public_key(&secp).serialize());
+        Builder::new()
+            .push_opcode(OP_PUSHNUM_1)
+            .push_slice(first)
+            .push_slice(second)
+            .push_opcode(OP_PUSHNUM_2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    /// Returns a coinbase transaction whose witness commitment output carries `signet_push`.
+    fn coinbase(signet_push: &[u8]) -> ::bitcoin::Transaction {
+        let mut commitment = vec![0xaa, 0x21, 0xa9, 0xed];
+        commitment.extend([7u8; 32]);
+        ::bitcoin::Transaction {
+            version: ::bitcoin::transaction::Version::TWO,
+            lock_time: ::bitcoin::absolute::LockTime::ZERO,
+            input: vec![::bitcoin::TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new().push_int(HEIGHT as i64).into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[[0u8; 32]]),
+            }],
+            output: vec![
+                ::bitcoin::TxOut {
+                    value: Amount::from_sat(5_000_000_000),
+                    script_pubkey: ScriptBuf::new_op_return([1u8; 20]),
+                },
+                ::bitcoin::TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: Builder::new()
+                        .push_opcode(RETURN)
+                        .push_slice(push_bytes(&commitment))
+                        .push_slice(push_bytes(signet_push))
+                        .into_script(),
+                },
+            ],
+        }
+    }
+
+    fn other_tx() -> ::bitcoin::Transaction {
+        ::bitcoin::Transaction {
+            version: ::bitcoin::transaction::Version::TWO,
+            lock_time: ::bitcoin::absolute::LockTime::ZERO,
+            input: vec![::bitcoin::TxIn {
+                previous_output: OutPoint::new(::bitcoin::Txid::from_byte_array([3u8; 32]), 1),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[vec![4u8; 72], vec![5u8; 33]]),
+            }],
+            output: vec![::bitcoin::TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new_op_return([6u8; 20]),
+            }],
+        }
+    }
+
+    fn merkle_root(coinbase: &::bitcoin::Transaction) -> ::bitcoin::TxMerkleNode {
+        ::bitcoin::merkle_tree::calculate_root(
+            [coinbase.compute_txid(), other_tx().compute_txid()]
+                .into_iter()
+                .map(|txid| txid.to_raw_hash()),
+        )
+        .map(::bitcoin::TxMerkleNode::from_raw_hash)
+        .unwrap()
+    }
+
+    /// Signs `header` for `challenge` as the signet miner does (BIP325), returning the solution.
+    fn sign_block(header: &::bitcoin::block::Header, challenge: &ScriptBuf) -> Vec<u8> {
+        let mut block_data = serialize(&header.version);
+        block_data.extend(serialize(&header.prev_blockhash));
+        block_data.extend(serialize(&merkle_root(&coinbase(&SIGNET_HEADER))));
+        block_data.extend(serialize(&header.time));
+
+        let to_spend = ::bitcoin::Transaction {
+            version: ::bitcoin::transaction::Version(0),
+            lock_time: ::bitcoin::absolute::LockTime::ZERO,
+            input: vec![::bitcoin::TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new()
+                    .push_int(0)
+                    .push_slice(push_bytes(&block_data))
+                    .into_script(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![::bitcoin::TxOut {
+                value: Amount::ZERO,
+                script_pubkey: challenge.clone(),
+            }],
+        };
+        let to_sign = ::bitcoin::Transaction {
+            version: ::bitcoin::transaction::Version(0),
+            lock_time: ::bitcoin::absolute::LockTime::ZERO,
+            input: vec![::bitcoin::TxIn {
+                previous_output: OutPoint::new(to_spend.compute_txid(), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![::bitcoin::TxOut {
+                value: Amount::ZERO,
+                script_pubkey: Builder::new().push_opcode(RETURN).into_script(),
+            }],
+        };
+
+        let sighash = SighashCache::new(&to_sign)
+            .legacy_signature_hash(0, challenge, EcdsaSighashType::All.to_u32())
+            .unwrap();
+        let signature = Secp256k1::new().sign_ecdsa(
+            &Message::from_digest(sighash.to_byte_array()),
+            &challenge_keys()[1],
+        );
+        let mut signature = signature.serialize_der().to_vec();
+        signature.push(EcdsaSighashType::All as u8);
+
+        let script_sig = Builder::new()
+            .push_int(0)
+            .push_slice(push_bytes(&signature))
+            .into_script();
+        let mut solution = serialize(&script_sig);
+        solution.extend(serialize(&Witness::new()));
+        solution
+    }
+
+    /// Builds a block signed for `challenge`, returning its header and signet witness.
+    fn signed_block(challenge: &ScriptBuf) -> (CircuitBlock, SignetWitness, Vec<u8>) {
+        let mut header = ::bitcoin::block::Header {
+            version: ::bitcoin::block::Version::from_consensus(0x2000_0000),
+            prev_blockhash: ::bitcoin::BlockHash::from_byte_array([8u8; 32]),
+            merkle_root: ::bitcoin::TxMerkleNode::all_zeros(),
+            time: 1_700_000_000,
+            bits: ::bitcoin::CompactTarget::from_consensus(0x1e0377ae),
+            nonce: 42,
+        };
+        let solution = sign_block(&header, challenge);
+        let signet_push = [SIGNET_HEADER.as_slice(), &solution].concat();
+        let mut coinbase = coinbase(&signet_push);
+        header.merkle_root = merkle_root(&coinbase);
+
+        let other_txid = Transaction::parse(&serialize(&other_tx())).unwrap().txid();
+        coinbase.input[0].witness.clear();
+        let witness = SignetWitness {
+            coinbase_tx: serialize(&coinbase),
+            coinbase_merkle_proof: vec![MerkleProofStep {
+                hash: other_txid,
+                direction: true,
+            }],
+            tx_count: 2,
+        };
+        let block = CircuitBlock::try_from((&header, HEIGHT)).unwrap();
+        (block, witness, signet_push)
+    }
+
+    #[test]
+    fn fetch_and_clear_keeps_the_header() {
+        let script = coinbase(&[SIGNET_HEADER.as_slice(), &[9, 9]].concat())
+            .output
+            .swap_remove(1)
+            .script_pubkey;
+        let (replacement, solution) = fetch_and_clear_signet_solution(script.as_bytes()).unwrap();
+        assert_eq!(solution, [9, 9]);
+        assert_eq!(
+            replacement,
+            coinbase(&SIGNET_HEADER).output[1].script_pubkey.as_bytes()
+        );
+
+        // The header alone is not a solution.
+        let script = coinbase(&SIGNET_HEADER).output.swap_remove(1).script_pubkey;
+        assert!(fetch_and_clear_signet_solution(script.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn valid_solution() {
+        let challenge = challenge();
+        let (block, witness, _) = signed_block(&challenge);
+        verify_signet_solution(&block, challenge.as_bytes(), &witness);
+    }
+
+    #[test]
+    fn op_true_challenge_needs_no_solution() {
+        let (block, witness, _) = signed_block(&challenge());
+        verify_signet_solution(&block, &[OP_1], &witness);
+    }
+
+    #[test]
+    #[should_panic(expected = "The signet solution does not satisfy the challenge.")]
+    fn tampered_solution() {
+        let challenge = challenge();
+        let (block, mut witness, signet_push) = signed_block(&challenge);
+
+        // Flip a bit of the signature, the block is recommitted to the tampered solution.
+        let mut tampered_push = signet_push.clone();
+        tampered_push[signet_push.len() - 10] ^= 1;
+        let mut coinbase = coinbase(&tampered_push);
+        let mut header = ::bitcoin::block::Header::try_from(&block).unwrap();
+        header.merkle_root = merkle_root(&coinbase);
+        coinbase.input[0].witness.clear();
+        witness.coinbase_tx = serialize(&coinbase);
+
+        let block = CircuitBlock::try_from((&header, HEIGHT)).unwrap();
+        verify_signet_solution(&block, challenge.as_bytes(), &witness);
+    }
+
+    #[test]
+    #[should_panic(expected = "The signet solution does not satisfy the challenge.")]
+    fn solution_of_another_header() {
+        let challenge = challenge();
+        let (mut block, witness, _) = signed_block(&challenge);
+        block.time = (1_700_000_001u32).to_le_bytes();
+        verify_signet_solution(&block, challenge.as_bytes(), &witness);
+    }
+
+    #[test]
+    #[should_panic(expected = "The signet solution does not satisfy the challenge.")]
+    fn solution_for_another_challenge() {
+        let (block, witness, _) = signed_block(&challenge());
+        verify_signet_solution(&block, &DEFAULT_SIGNET_CHALLENGE, &witness);
+    }
+
+    #[test]
+    #[should_panic(expected = "The coinbase transaction is not part of the block.")]
+    fn coinbase_not_in_block() {
+        let challenge = challenge();
+        let (mut block, witness, _) = signed_block(&challenge);
+        block.merkle_root = [0u8; 32];
+        verify_signet_solution(&block, challenge.as_bytes(), &witness);
+    }
+
+    #[test]
+    fn supported_challenges() {
+        assert!(is_supported_signet_challenge(challenge().as_bytes()));
+        assert!(is_supported_signet_challenge(&DEFAULT_SIGNET_CHALLENGE));
+        assert!(is_supported_signet_challenge(&[OP_1]));
+
+        // P2WPKH, a bare OP_2 and a truncated push.
+        assert!(!is_supported_signet_challenge(
+            &[[0x00, 0x14].as_slice(), &[0u8; 20]].concat()
+        ));
+        assert!(!is_supported_signet_challenge(&[OP_1 + 1]));
+        assert!(!is_supported_signet_challenge(&[0x21, 0x02]));
+    }
+}
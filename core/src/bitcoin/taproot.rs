@@ -0,0 +1,122 @@
+//! Taproot (BIP341) primitives.
+
+use crate::sha256_hash;
+use k256::elliptic_curve::PrimeField;
+use k256::elliptic_curve::point::{AffineCoordinates, DecompressPoint};
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+
+/// Computes the tagged hash of BIP340, `sha256(sha256(tag) || sha256(tag) || data)`.
+pub fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = sha256_hash(tag);
+    let mut bytes = Vec::with_capacity(64 + data.iter().map(|d| d.len()).sum::<usize>());
+    bytes.extend_from_slice(&tag_hash);
+    bytes.extend_from_slice(&tag_hash);
+    data.iter().for_each(|d| bytes.extend_from_slice(d));
+    sha256_hash(&bytes)
+}
+
+/// Returns the point with the given x coordinate and an even y coordinate (`lift_x` in BIP340).
+pub fn lift_x(x: &[u8; 32]) -> Option<AffinePoint> {
+    AffinePoint::decompress(x.into(), 0u8.into()).into()
+}
+
+/// Computes the x-only output key of a taproot output from its internal key and the merkle root
+/// of its script tree, `None` for a key-path only output.
+///
+/// Returns `None` if the internal key is not a valid x-only key.
+pub fn taproot_output_key(
+    internal_key: [u8; 32],
+    script_tree_root: Option<[u8; 32]>,
+) -> Option<[u8; 32]> {
+    let internal_point = lift_x(&internal_key)?;
+
+    let tweak = match script_tree_root {
+        Some(root) => tagged_hash(b"TapTweak", &[&internal_key, &root]),
+        None => tagged_hash(b"TapTweak", &[&internal_key]),
+    };
+    let tweak = Option::<Scalar>::from(Scalar::from_repr(tweak.into()))?;
+
+    let output_point =
+        (ProjectivePoint::from(internal_point) + ProjectivePoint::GENERATOR * tweak).to_affine();
+    if output_point == AffinePoint::IDENTITY {
+        return None;
+    }
+
+    Some(output_point.x().into())
+}
+
+/// Returns the script pubkey of a P2TR output, `OP_1 OP_PUSHBYTES_32 <output key>`.
+pub fn p2tr_script_pubkey(output_key: [u8; 32]) -> Vec<u8> {
+    let mut script = Vec::with_capacity(34);
+    script.extend_from_slice(&[0x51, 0x20]);
+    script.extend_from_slice(&output_key);
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_key(key: &str) -> [u8; 32] {
+        hex::decode(key).unwrap().try_into().unwrap()
+    }
+
+    /// `scriptPubKey` test vectors of BIP341: internal key, script tree root, output key.
+    const VECTORS: [(&str, Option<&str>, &str); 7] = [
+        (
+            "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+            None,
+            "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+        ),
+        (
+            "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+            Some("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"),
+            "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+        ),
+        (
+            "93478e9488f956df2396be2ce6c5cced75f900dfa18e7dabd2428aae78451820",
+            Some("c525714a7f49c28aedbbba78c005931a81c234b2f6c99a73e4d06082adc8bf2b"),
+            "e4d810fd50586274face62b8a807eb9719cef49c04177cc6b76a9a4251d5450e",
+        ),
+        (
+            "ee4fe085983462a184015d1f782d6a5f8b9c2b60130aff050ce221ecf3786592",
+            Some("6c2dc106ab816b73f9d07e3cd1ef2c8c1256f519748e0813e4edd2405d277bef"),
+            "712447206d7a5238acc7ff53fbe94a3b64539ad291c7cdbc490b7577e4b17df5",
+        ),
+        (
+            "f9f400803e683727b14f463836e1e78e1c64417638aa066919291a225f0e8dd8",
+            Some("ab179431c28d3b68fb798957faf5497d69c883c6fb1e1cd9f81483d87bac90cc"),
+            "77e30a5522dd9f894c3f8b8bd4c4b2cf82ca7da8a3ea6a239655c39c050ab220",
+        ),
+        (
+            "e0dfe2300b0dd746a3f8674dfd4525623639042569d829c7f0eed9602d263e6f",
+            Some("ccbd66c6f7e8fdab47b3a486f59d28262be857f30d4773f2d5ea47f7761ce0e2"),
+            "91b64d5324723a985170e4dc5a0f84c041804f2cd12660fa5dec09fc21783605",
+        ),
+        (
+            "55adf4e8967fbd2e29f20ac896e60c3b0f1d5b0efa9d34941b5958c7b0a0312d",
+            Some("2f6b2c5397b6d68ca18e09a3f05161668ffe93a988582d55c6f07bd5b3329def"),
+            "75169f4001aa68f15bbed28b218df1d0a62cbbcf1188c6665110c293c907b831",
+        ),
+    ];
+
+    #[test]
+    fn output_keys_match_bip341_vectors() {
+        for (internal_key, script_tree_root, output_key) in VECTORS {
+            let tweaked = taproot_output_key(hex_key(internal_key), script_tree_root.map(hex_key));
+            assert_eq!(tweaked, Some(hex_key(output_key)), "{internal_key}");
+            assert_eq!(
+                hex::encode(p2tr_script_pubkey(tweaked.unwrap())),
+                format!("5120{output_key}")
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_internal_key_not_on_the_curve() {
+        // Public key of BIP340 test vector 5.
+        let internal_key =
+            hex_key("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34");
+        assert_eq!(taproot_output_key(internal_key, None), None);
+    }
+}
@@ -0,0 +1,419 @@
+//! A minimal parser of serialized Bitcoin transactions, usable inside the zkVM programs.
+//!
+//! Only `core` and `alloc` are used, so that the parser does not depend on `std`. The crate
+//! itself requires `std` for the Babylon light client, hence the lints below instead of `no_std`.
+#![deny(clippy::std_instead_of_core, clippy::std_instead_of_alloc)]
+
+use crate::bitcoin::double_sha256_hash;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Marker and flag bytes following the version of a transaction serialized with witness (BIP144).
+const SEGWIT_MARKER: u8 = 0x00;
+const SEGWIT_FLAG: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxParseError {
+    /// The input ended before the transaction was fully decoded.
+    UnexpectedEnd,
+    /// A length prefix is not encoded in its shortest form.
+    NonMinimalCompactSize,
+    /// The segwit flag is not `0x01`.
+    InvalidSegwitFlag(u8),
+    /// The transaction is serialized with witness but all witnesses are empty.
+    EmptyWitness,
+    /// Bytes remain after the transaction.
+    TrailingBytes,
+}
+
+impl core::fmt::Display for TxParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of transaction"),
+            Self::NonMinimalCompactSize => write!(f, "non-minimal compact size"),
+            Self::InvalidSegwitFlag(flag) => write!(f, "invalid segwit flag: {flag}"),
+            Self::EmptyWitness => write!(f, "segwit serialization without witness"),
+            Self::TrailingBytes => write!(f, "trailing bytes after transaction"),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxIn {
+    /// Little-endian bytes of the spent transaction id (as shown on explorers).
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxOut {
+    /// Amount in satoshis.
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+impl TxOut {
+    /// Returns the public key locked by a P2TR (x-only output key) or a P2PK (compressed key)
+    /// output, `None` for any other script.
+    pub fn locked_pubkey(&self) -> Option<&[u8]> {
+        match self.script_pubkey.as_slice() {
+            // OP_1 OP_PUSHBYTES_32 <x-only output key>
+            [0x51, 0x20, key @ ..] if key.len() == 32 => Some(key),
+            // OP_PUSHBYTES_33 <compressed key> OP_CHECKSIG
+            [0x21, key @ .., 0xac] if key.len() == 33 => Some(key),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+/// Reader of consensus-encoded data.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], TxParseError> {
+        if self.bytes.len() < len {
+            return Err(TxParseError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], TxParseError> {
+        Ok(self
+            .read_bytes(N)?
+            .try_into()
+            .expect("Length checked above; qed"))
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(offset).copied()
+    }
+
+    fn read_compact_size(&mut self) -> Result<u64, TxParseError> {
+        let (value, min) = match self.read_array::<1>()?[0] {
+            0xfd => (u16::from_le_bytes(self.read_array()?) as u64, 0xfd),
+            0xfe => (u32::from_le_bytes(self.read_array()?) as u64, 0x10000),
+            0xff => (u64::from_le_bytes(self.read_array()?), 0x100000000),
+            n => return Ok(n as u64),
+        };
+        if value < min {
+            return Err(TxParseError::NonMinimalCompactSize);
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn read_var_bytes(&mut self) -> Result<Vec<u8>, TxParseError> {
+        let len = self.read_compact_size()?;
+        // Avoid allocating an arbitrary amount of memory for malformed input.
+        if len > self.bytes.len() as u64 {
+            return Err(TxParseError::UnexpectedEnd);
+        }
+        Ok(self.read_bytes(len as usize)?.to_vec())
+    }
+
+    pub(crate) fn read_vec<T>(
+        &mut self,
+        read: impl Fn(&mut Self) -> Result<T, TxParseError>,
+    ) -> Result<Vec<T>, TxParseError> {
+        let len = self.read_compact_size()?;
+        if len > self.bytes.len() as u64 {
+            return Err(TxParseError::UnexpectedEnd);
+        }
+        (0..len).map(|_| read(self)).collect()
+    }
+}
+
+pub fn write_compact_size(bytes: &mut Vec<u8>, value: u64) {
+    match value {
+        0..0xfd => bytes.push(value as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        0x10000..=0xffffffff => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+pub fn write_var_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    write_compact_size(bytes, data.len() as u64);
+    bytes.extend_from_slice(data);
+}
+
+impl Transaction {
+    /// Parses a transaction serialized either in the legacy format or with witness (BIP144).
+    pub fn parse(bytes: &[u8]) -> Result<Self, TxParseError> {
+        let mut reader = Reader::new(bytes);
+
+        let version = i32::from_le_bytes(reader.read_array()?);
+
+        // A legacy transaction can not have zero inputs, hence no ambiguity with the marker.
+        let segwit = reader.peek(0) == Some(SEGWIT_MARKER);
+        if segwit {
+            let [_marker, flag] = reader.read_array::<2>()?;
+            if flag != SEGWIT_FLAG {
+                return Err(TxParseError::InvalidSegwitFlag(flag));
+            }
+        }
+
+        let mut inputs = reader.read_vec(|reader| {
+            let mut prev_txid = reader.read_array::<32>()?;
+            prev_txid.reverse();
+            Ok(TxIn {
+                prev_txid,
+                prev_vout: u32::from_le_bytes(reader.read_array()?),
+                script_sig: reader.read_var_bytes()?,
+                sequence: u32::from_le_bytes(reader.read_array()?),
+                witness: Vec::new(),
+            })
+        })?;
+
+        let outputs = reader.read_vec(|reader| {
+            Ok(TxOut {
+                value: u64::from_le_bytes(reader.read_array()?),
+                script_pubkey: reader.read_var_bytes()?,
+            })
+        })?;
+
+        if segwit {
+            for input in inputs.iter_mut() {
+                input.witness = reader.read_vec(Reader::read_var_bytes)?;
+            }
+            if inputs.iter().all(|input| input.witness.is_empty()) {
+                return Err(TxParseError::EmptyWitness);
+            }
+        }
+
+        let lock_time = u32::from_le_bytes(reader.read_array()?);
+
+        if !reader.is_empty() {
+            return Err(TxParseError::TrailingBytes);
+        }
+
+        Ok(Self {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    pub fn has_witness(&self) -> bool {
+        self.inputs.iter().any(|input| !input.witness.is_empty())
+    }
+
+    /// Serializes the transaction, including the witness if any.
+    pub fn serialize(&self) -> Vec<u8> {
+        self.serialize_inner(self.has_witness())
+    }
+
+    /// Serializes the transaction using the legacy format (no witness).
+    pub fn serialize_legacy(&self) -> Vec<u8> {
+        self.serialize_inner(false)
+    }
+
+    fn serialize_inner(&self, with_witness: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        if with_witness {
+            bytes.extend_from_slice(&[SEGWIT_MARKER, SEGWIT_FLAG]);
+        }
+        write_compact_size(&mut bytes, self.inputs.len() as u64);
+        for input in &self.inputs {
+            bytes.extend(input.prev_txid.iter().rev());
+            bytes.extend_from_slice(&input.prev_vout.to_le_bytes());
+            write_var_bytes(&mut bytes, &input.script_sig);
+            bytes.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        write_compact_size(&mut bytes, self.outputs.len() as u64);
+        for output in &self.outputs {
+            bytes.extend_from_slice(&output.value.to_le_bytes());
+            write_var_bytes(&mut bytes, &output.script_pubkey);
+        }
+        if with_witness {
+            for input in &self.inputs {
+                write_compact_size(&mut bytes, input.witness.len() as u64);
+                input
+                    .witness
+                    .iter()
+                    .for_each(|item| write_var_bytes(&mut bytes, item));
+            }
+        }
+        bytes.extend_from_slice(&self.lock_time.to_le_bytes());
+        bytes
+    }
+
+    /// Returns the little-endian bytes of the transaction id (as shown on explorers).
+    pub fn txid(&self) -> [u8; 32] {
+        double_sha256_hash(&self.serialize_legacy())
+    }
+
+    /// Returns the little-endian bytes of the witness transaction id (as shown on explorers).
+    pub fn wtxid(&self) -> [u8; 32] {
+        double_sha256_hash(&self.serialize())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Transaction 1 of testnet block 924634, a P2PKH spend.
+    pub(crate) const LEGACY_TX: &str = "01000000017e4f81175332a733e26d4ba4e29f53f67b7a5d7c2adebb27\
+        6e447ca71d130b55000000006b483045022100cac809cd1a3d9ad5d5e31a84e2e1d8ec5542841e4d14c6b52e8b\
+        38cbe1ff1728022064470b7fb0c2efeccb2e84bfa36ec5f9e434c84b1101c00f7ee32f726371b7410121020e62\
+        280798b6b8c37f068df0915b0865b63fabc401c2457cbc3ef96887dd3647ffffffff02ca2f780c000000001976\
+        a914c6b5545b3592cb477d709896fa705592c9b6113a88ac663b2a06000000001976a914e7c1345fc8f87c6817\
+        0b3aa798a956c2fe6a9eff88ac00000000";
+
+    /// Transaction 1 of mainnet block 702861, a P2WPKH spend.
+    const SEGWIT_TX: &str = "0200000000010153b15e6e3000174bb3d6a71c7531be172dd77b1d0f5a9e6eed6a\
+        9d345c37d5520100000000feffffff021b74060000000000220020de4d09dc9cb0fca2e71f96b79871fc991310\
+        bc0c9eba10e93ca494696face92ad30e23000000000016001430691905e1f530940c645d767013f931cc97c8bb\
+        0247304402202d39fce145204e88fa3a8d398b9ff2ec4c4c8bf02da3b751650a80ddb26fcb310220689651c87e\
+        08674627d82612a3b4040e42991240792743a2cd9b9942482265b9012102dfaba46d2417eee4661d45a6ab44f1\
+        5cf2c77377045c678c926142b6b611ab9e8cb90a00";
+
+    pub(crate) fn hex_hash(hash: &str) -> [u8; 32] {
+        hex::decode(hash).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn parses_legacy_tx() {
+        let bytes = hex::decode(LEGACY_TX).unwrap();
+        let tx = Transaction::parse(&bytes).unwrap();
+        assert_eq!(tx.version, 1);
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(
+            tx.inputs[0].prev_txid,
+            hex_hash("550b131da77c446e27bbde2a7c5d7a7bf6539fe2a44b6de233a7325317814f7e")
+        );
+        assert_eq!(tx.inputs[0].prev_vout, 0);
+        assert_eq!(tx.inputs[0].sequence, u32::MAX);
+        assert_eq!(
+            tx.outputs
+                .iter()
+                .map(|output| output.value)
+                .collect::<Vec<_>>(),
+            [209203146, 103431014]
+        );
+        assert_eq!(tx.lock_time, 0);
+        assert!(!tx.has_witness());
+        assert_eq!(
+            tx.txid(),
+            hex_hash("22dc883714a4536a3360e5ae311fae9fd59b3fc01614fb9a13ecb0fc84b70da1")
+        );
+        assert_eq!(tx.wtxid(), tx.txid());
+        assert_eq!(tx.serialize(), bytes);
+    }
+
+    #[test]
+    fn parses_segwit_tx() {
+        let bytes = hex::decode(SEGWIT_TX).unwrap();
+        let tx = Transaction::parse(&bytes).unwrap();
+        assert_eq!(tx.version, 2);
+        assert_eq!(
+            tx.inputs[0].prev_txid,
+            hex_hash("52d5375c349d6aed6e9e5a0f1d7bd72d17be31751ca7d6b34b1700306e5eb153")
+        );
+        assert_eq!(tx.inputs[0].prev_vout, 1);
+        assert!(tx.inputs[0].script_sig.is_empty());
+        assert_eq!(
+            tx.inputs[0]
+                .witness
+                .iter()
+                .map(|item| item.len())
+                .collect::<Vec<_>>(),
+            [71, 33]
+        );
+        assert_eq!(tx.lock_time, 702860);
+        assert_eq!(
+            tx.txid(),
+            hex_hash("7bf717689b9033eafb2f3272719989b304bb7db616c2bfb5ded2e1b76d50a4f0")
+        );
+        assert_eq!(
+            tx.wtxid(),
+            hex_hash("16280b1cc1ed358983b12745b1a90a9eb1e9bf060f8c7d5ea1f2ebc58be9f3cc")
+        );
+        assert_eq!(tx.serialize(), bytes);
+        assert_eq!(
+            Transaction::parse(&tx.serialize_legacy()).unwrap().txid(),
+            tx.txid()
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_tx() {
+        for tx in [LEGACY_TX, SEGWIT_TX] {
+            let bytes = hex::decode(tx).unwrap();
+            for len in 0..bytes.len() {
+                assert_eq!(
+                    Transaction::parse(&bytes[..len]),
+                    Err(TxParseError::UnexpectedEnd),
+                    "{len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        for tx in [LEGACY_TX, SEGWIT_TX] {
+            let mut bytes = hex::decode(tx).unwrap();
+            bytes.push(0);
+            assert_eq!(Transaction::parse(&bytes), Err(TxParseError::TrailingBytes));
+        }
+    }
+
+    #[test]
+    fn rejects_empty_witness_with_marker() {
+        let tx = Transaction::parse(&hex::decode(LEGACY_TX).unwrap()).unwrap();
+        let mut bytes = tx.serialize_inner(true);
+        assert_eq!(Transaction::parse(&bytes), Err(TxParseError::EmptyWitness));
+
+        bytes[5] = 0x02;
+        assert_eq!(
+            Transaction::parse(&bytes),
+            Err(TxParseError::InvalidSegwitFlag(0x02))
+        );
+    }
+
+    #[test]
+    fn rejects_non_minimal_compact_size() {
+        let mut bytes = hex::decode(LEGACY_TX).unwrap();
+        // Input count of 1 encoded on 3 bytes.
+        bytes.splice(4..5, [0xfd, 0x01, 0x00]);
+        assert_eq!(
+            Transaction::parse(&bytes),
+            Err(TxParseError::NonMinimalCompactSize)
+        );
+    }
+}
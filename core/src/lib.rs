@@ -1,5 +1,8 @@
+extern crate alloc;
+
 pub mod babylon;
 pub mod bitcoin;
+pub mod mmr;
 
 use sha2::{Digest, Sha256};
 
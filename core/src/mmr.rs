@@ -0,0 +1,268 @@
+//! A Merkle Mountain Range (MMR) accumulator used to commit to every proven block.
+//!
+//! Inside the circuit only the peaks are carried around, which is enough to append a new leaf
+//! and to recompute the root. Membership proofs are generated on the host, which has access to
+//! the full list of leaves.
+
+use crate::sha256_hash;
+use serde::{Deserialize, Serialize};
+
+/// Domain separation prefix of the inner nodes.
+const NODE_PREFIX: u8 = 0x01;
+
+fn hash_nodes(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = [0u8; 65];
+    bytes[0] = NODE_PREFIX;
+    bytes[1..33].copy_from_slice(left);
+    bytes[33..].copy_from_slice(right);
+    sha256_hash(&bytes)
+}
+
+/// Computes the root of an MMR from its peaks.
+///
+/// The leaf count is part of the root so that two accumulators with the same peaks but a
+/// different shape can never share the same root. An empty MMR has an all-zero root.
+pub fn bag_peaks(leaf_count: u64, peaks: &[[u8; 32]]) -> [u8; 32] {
+    if leaf_count == 0 {
+        return [0u8; 32];
+    }
+    let mut bytes = Vec::with_capacity(8 + 32 * peaks.len());
+    bytes.extend_from_slice(&leaf_count.to_le_bytes());
+    peaks.iter().for_each(|peak| bytes.extend_from_slice(peak));
+    sha256_hash(&bytes)
+}
+
+/// Append-only accumulator which only keeps the peaks of the perfect subtrees.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MerkleMountainRange {
+    /// Total number of leaves appended so far.
+    pub leaf_count: u64,
+    /// Peaks of the perfect subtrees, ordered from the highest (leftmost) to the lowest.
+    pub peaks: Vec<[u8; 32]>,
+}
+
+impl MerkleMountainRange {
+    /// Builds an accumulator from the full list of leaves.
+    pub fn from_leaves(leaves: &[[u8; 32]]) -> Self {
+        let mut mmr = Self::default();
+        leaves.iter().for_each(|leaf| mmr.append(*leaf));
+        mmr
+    }
+
+    /// Appends a new leaf, merging the peaks of equal height.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the peaks do not match the leaf count.
+    pub fn append(&mut self, leaf: [u8; 32]) {
+        assert_eq!(
+            self.peaks.len(),
+            self.leaf_count.count_ones() as usize,
+            "The number of MMR peaks does not match the leaf count."
+        );
+        let mut node = leaf;
+        let mut count = self.leaf_count;
+        while count & 1 == 1 {
+            let left = self.peaks.pop().expect("Peak exists as checked above; qed");
+            node = hash_nodes(&left, &node);
+            count >>= 1;
+        }
+        self.peaks.push(node);
+        self.leaf_count += 1;
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        bag_peaks(self.leaf_count, &self.peaks)
+    }
+}
+
+/// Proof that a leaf is part of an MMR with a given root.
+#[derive(Default, Serialize, Deserialize, Clone, Debug)]
+pub struct MmrProof {
+    /// Position of the leaf, starting from 0 for the first appended leaf.
+    pub leaf_index: u64,
+    /// Total number of leaves of the MMR this proof was generated against.
+    pub leaf_count: u64,
+    /// Sibling hashes from the leaf up to its peak.
+    pub siblings: Vec<[u8; 32]>,
+    /// All peaks of the MMR, ordered from the highest to the lowest.
+    pub peaks: Vec<[u8; 32]>,
+}
+
+impl MmrProof {
+    /// Returns `true` if `leaf` is at `leaf_index` of the MMR committed to by `root`.
+    pub fn verify(&self, root: [u8; 32], leaf: [u8; 32]) -> bool {
+        if self.leaf_index >= self.leaf_count
+            || self.peaks.len() != self.leaf_count.count_ones() as usize
+            || bag_peaks(self.leaf_count, &self.peaks) != root
+        {
+            return false;
+        }
+
+        // Locate the perfect subtree containing the leaf, peaks are ordered from the left.
+        let mut offset = 0u64;
+        let mut peak_index = 0usize;
+        for height in (0..u64::BITS).rev() {
+            let size = 1u64 << height;
+            if self.leaf_count & size == 0 {
+                continue;
+            }
+
+            if self.leaf_index < offset + size {
+                if self.siblings.len() != height as usize {
+                    return false;
+                }
+                let mut local_index = self.leaf_index - offset;
+                let mut node = leaf;
+                for sibling in &self.siblings {
+                    node = if local_index & 1 == 0 {
+                        hash_nodes(&node, sibling)
+                    } else {
+                        hash_nodes(sibling, &node)
+                    };
+                    local_index >>= 1;
+                }
+                return node == self.peaks[peak_index];
+            }
+
+            offset += size;
+            peak_index += 1;
+        }
+
+        false
+    }
+}
+
+/// Computes the root of a perfect subtree, recording the siblings on the path to `index`.
+///
+/// Used by the host to generate [`MmrProof`], `leaves.len()` must be a power of two.
+pub fn perfect_subtree_root(
+    leaves: &[[u8; 32]],
+    index: Option<usize>,
+    siblings: &mut Vec<[u8; 32]>,
+) -> [u8; 32] {
+    let mut current_level = leaves.to_vec();
+    let mut index = index;
+    while current_level.len() > 1 {
+        if let Some(i) = index {
+            siblings.push(current_level[i ^ 1]);
+            index = Some(i / 2);
+        }
+        current_level = current_level
+            .chunks(2)
+            .map(|pair| hash_nodes(&pair[0], &pair[1]))
+            .collect();
+    }
+    current_level[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u64) -> Vec<[u8; 32]> {
+        (0..count).map(|i| sha256_hash(&i.to_le_bytes())).collect()
+    }
+
+    #[test]
+    fn empty_root_is_zero() {
+        assert_eq!(MerkleMountainRange::default().root(), [0u8; 32]);
+    }
+
+    #[test]
+    fn peaks_follow_leaf_count() {
+        let mut mmr = MerkleMountainRange::default();
+        for (i, leaf) in leaves(64).into_iter().enumerate() {
+            mmr.append(leaf);
+            assert_eq!(mmr.leaf_count, i as u64 + 1);
+            assert_eq!(mmr.peaks.len(), mmr.leaf_count.count_ones() as usize);
+        }
+    }
+
+    #[test]
+    fn peaks_are_perfect_subtree_roots() {
+        let leaves = leaves(13);
+        let mmr = MerkleMountainRange::from_leaves(&leaves);
+        // 13 = 8 + 4 + 1.
+        let expected = [
+            perfect_subtree_root(&leaves[..8], None, &mut Vec::new()),
+            perfect_subtree_root(&leaves[8..12], None, &mut Vec::new()),
+            leaves[12],
+        ];
+        assert_eq!(mmr.peaks, expected);
+        assert_eq!(mmr.root(), bag_peaks(13, &expected));
+    }
+
+    #[test]
+    fn root_commits_to_leaf_count() {
+        let leaves = leaves(2);
+        let mmr = MerkleMountainRange::from_leaves(&leaves);
+        // A single leaf equal to the peak of the two leaves must not share the root.
+        let forged = MerkleMountainRange::from_leaves(&[mmr.peaks[0]]);
+        assert_eq!(mmr.peaks, forged.peaks);
+        assert_ne!(mmr.root(), forged.root());
+    }
+
+    #[test]
+    fn perfect_subtree_siblings() {
+        let leaves = leaves(4);
+        let mut siblings = Vec::new();
+        let root = perfect_subtree_root(&leaves, Some(2), &mut siblings);
+        assert_eq!(
+            siblings,
+            vec![leaves[3], hash_nodes(&leaves[0], &leaves[1])]
+        );
+        assert_eq!(
+            root,
+            hash_nodes(
+                &hash_nodes(&leaves[0], &leaves[1]),
+                &hash_nodes(&leaves[2], &leaves[3])
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The number of MMR peaks does not match the leaf count.")]
+    fn append_rejects_inconsistent_peaks() {
+        let mut mmr = MerkleMountainRange {
+            leaf_count: 3,
+            peaks: vec![[0u8; 32]],
+        };
+        mmr.append([1u8; 32]);
+    }
+
+    #[test]
+    fn proof_rejects_inconsistent_shape() {
+        let leaves = leaves(3);
+        let mmr = MerkleMountainRange::from_leaves(&leaves);
+        let proof = MmrProof {
+            leaf_index: 2,
+            leaf_count: 3,
+            siblings: vec![],
+            peaks: mmr.peaks.clone(),
+        };
+        assert!(proof.verify(mmr.root(), leaves[2]));
+        assert!(!proof.verify(mmr.root(), leaves[1]));
+        assert!(
+            !MmrProof {
+                leaf_index: 3,
+                ..proof.clone()
+            }
+            .verify(mmr.root(), leaves[2])
+        );
+        assert!(
+            !MmrProof {
+                siblings: vec![leaves[0]],
+                ..proof.clone()
+            }
+            .verify(mmr.root(), leaves[2])
+        );
+        assert!(
+            !MmrProof {
+                peaks: mmr.peaks[1..].to_vec(),
+                ..proof
+            }
+            .verify(mmr.root(), leaves[2])
+        );
+    }
+}
@@ -18,6 +18,7 @@ pub fn main() {
         membership_vkey_u32_hash,
         consensus_public_input,
         membership_public_input,
+        historical_header,
     } = sp1_zkvm::io::read::<AggregationInput>();
 
     sp1_zkvm::lib::verify::verify_sp1_proof(
@@ -40,11 +41,13 @@ pub fn main() {
         bincode::decode_from_slice(&membership_public_input, bincode::config::legacy())
             .expect("failed to decode membership public input");
 
+    // The membership proof may target a historical header covered by the consensus proof.
+    let app_hash = consensus_public_input
+        .resolve_header(historical_header.as_ref())
+        .app_hash;
+
     // Check that the app_hash in the membership proof matches the one in the consensus block header.
-    assert_eq!(
-        consensus_public_input.app_hash,
-        membership_public_input.app_hash
-    );
+    assert_eq!(app_hash, membership_public_input.app_hash);
 
     // TODO: Decide what to commit as public output:
     // - Likely candidates: the header hash, state root, key(s), value(s).
@@ -0,0 +1,117 @@
+//! A program that proves a Babylon epoch checkpoint is submitted to Bitcoin.
+//!
+//! This zkVM program verifies three SP1 proofs:
+//! 1. A **Babylon consensus proof**, verifying that a given Babylon block header is valid.
+//! 2. A **membership proof**, showing that the checkpoint of the epoch is in the `checkpointing`
+//!    store under the application state (`app_hash`) of that header.
+//! 3. A **Bitcoin consensus proof**, committing to the Bitcoin headers proven so far.
+//!
+//! It then checks that the two parts of the checkpoint are carried by Bitcoin transactions included
+//! in proven blocks with enough confirmations, and that they match the checkpoint signed by the
+//! Babylon validators.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::checkpoint::{
+    BtcCheckpoint, BtcCheckpointInput, BtcCheckpointOutput, FIRST_PART_LENGTH, SECOND_PART_LENGTH,
+    find_checkpoint_part,
+};
+use zk_light_client_core::babylon::keys::checkpointing::CheckpointStatus;
+use zk_light_client_core::babylon::{MembershipOutput, TendermintOutput};
+use zk_light_client_core::bitcoin::AggregationInput;
+use zk_light_client_core::bitcoin::transaction::Transaction;
+use zk_light_client_core::sha256_hash;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<BtcCheckpointInput>();
+
+    let babylon_consensus_public_input_hash = sha256_hash(&input.babylon_consensus_public_input);
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.babylon_consensus_vkey_u32_hash,
+        &babylon_consensus_public_input_hash,
+    );
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.membership_vkey_u32_hash,
+        &sha256_hash(&input.membership_public_input),
+    );
+    let btc_consensus_public_input_hash = input.btc_consensus_public_input.compute_hash();
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.btc_consensus_vkey_u32_hash,
+        &btc_consensus_public_input_hash,
+    );
+
+    let (babylon_consensus_public_input, _): (TendermintOutput, _) = bincode::decode_from_slice(
+        &input.babylon_consensus_public_input,
+        bincode::config::legacy(),
+    )
+    .expect("failed to decode consensus public input");
+
+    let (membership_public_input, _): (MembershipOutput, _) =
+        bincode::decode_from_slice(&input.membership_public_input, bincode::config::legacy())
+            .expect("failed to decode membership public input");
+
+    // The membership proof may target a historical header covered by the consensus proof.
+    let app_hash = babylon_consensus_public_input
+        .resolve_header(input.historical_header.as_ref())
+        .app_hash;
+    assert_eq!(app_hash, membership_public_input.app_hash);
+
+    let raw_checkpoint = input.raw_checkpoint(&membership_public_input);
+    let status =
+        CheckpointStatus::try_from(raw_checkpoint.status).expect("Unknown checkpoint status");
+    assert!(
+        status >= CheckpointStatus::Sealed,
+        "The checkpoint is not sealed."
+    );
+    let raw_checkpoint = raw_checkpoint.ckpt.expect("The checkpoint is missing.");
+
+    let mut tx_ids = [[0u8; 32]; 2];
+    let mut btc_block_heights = [0u64; 2];
+    let mut parts = Vec::with_capacity(2);
+    for (index, (part, len)) in input
+        .parts
+        .iter()
+        .zip([FIRST_PART_LENGTH, SECOND_PART_LENGTH])
+        .enumerate()
+    {
+        part.inclusion.verify();
+        AggregationInput::new(
+            input.btc_consensus_vkey_u32_hash,
+            input.btc_consensus_public_input,
+            part.inclusion.public_input.clone(),
+            part.block.clone(),
+        )
+        .validate_historical_block();
+
+        let tx = Transaction::parse(&part.inclusion.witness.legacy_tx)
+            .expect("Failed to parse legacy transaction");
+        parts.push(
+            find_checkpoint_part(&tx, &input.checkpoint_tag, len)
+                .expect("The transaction does not carry the checkpoint part."),
+        );
+        tx_ids[index] = part.inclusion.public_input.tx_id;
+        btc_block_heights[index] = part.block.block.height;
+    }
+
+    let checkpoint = BtcCheckpoint::decode(&input.checkpoint_tag, &parts[0], &parts[1])
+        .expect("Failed to decode the checkpoint.");
+    assert_eq!(
+        checkpoint.epoch, input.epoch,
+        "The checkpoint epoch does not match."
+    );
+    assert!(
+        checkpoint.matches(&raw_checkpoint),
+        "The checkpoint in Bitcoin does not match the one signed by the validators."
+    );
+
+    let output = BtcCheckpointOutput {
+        babylon_consensus_public_input_hash,
+        btc_consensus_public_input_hash,
+        checkpoint_tag: input.checkpoint_tag,
+        checkpoint,
+        tx_ids,
+        btc_block_heights,
+    };
+    sp1_zkvm::io::commit(&output);
+}
@@ -0,0 +1,9 @@
+fn main() {
+    sp1_build::build_program_with_args(
+        "../baby_btc_checkpoint_program",
+        sp1_build::BuildArgs {
+            warning_level: sp1_build::WarningLevel::Minimal,
+            ..Default::default()
+        },
+    );
+}
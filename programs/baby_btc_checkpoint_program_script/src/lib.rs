@@ -0,0 +1,2 @@
+pub const BABY_BTC_CHECKPOINT_PROGRAM_ELF: &[u8] =
+    sp1_sdk::include_elf!("baby_btc_checkpoint_program");
@@ -0,0 +1,66 @@
+//! A program that proves the state of a BTC delegation of Babylon's `btcstaking` module.
+//!
+//! This zkVM program verifies two SP1 proofs:
+//! 1. A **consensus proof**, verifying that a given Babylon block header is valid and committing
+//!    to the headers proven so far.
+//! 2. A **membership proof**, showing the delegation stored under its staking transaction hash,
+//!    its staking parameters and the Bitcoin tip of the `btclightclient` module under the
+//!    application state (`app_hash`) of that header.
+//!
+//! It then derives the status of the delegation, and commits it along with its amount and
+//! finality provider keys.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::btcstaking::{BtcDelegationInput, BtcDelegationOutput};
+use zk_light_client_core::babylon::{MembershipOutput, TendermintOutput};
+use zk_light_client_core::sha256_hash;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<BtcDelegationInput>();
+
+    let consensus_public_input_hash = sha256_hash(&input.consensus_public_input);
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.consensus_vkey_u32_hash,
+        &consensus_public_input_hash,
+    );
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.membership_vkey_u32_hash,
+        &sha256_hash(&input.membership_public_input),
+    );
+
+    let (consensus_public_input, _): (TendermintOutput, _) =
+        bincode::decode_from_slice(&input.consensus_public_input, bincode::config::legacy())
+            .expect("failed to decode consensus public input");
+
+    let (membership_public_input, _): (MembershipOutput, _) =
+        bincode::decode_from_slice(&input.membership_public_input, bincode::config::legacy())
+            .expect("failed to decode membership public input");
+
+    // The membership proof may target a historical header covered by the consensus proof.
+    let header = consensus_public_input.resolve_header(input.historical_header.as_ref());
+    assert_eq!(header.app_hash, membership_public_input.app_hash);
+
+    let delegation = input.btc_delegation(&membership_public_input);
+    let btc_tip_height = input.btc_tip_height(&membership_public_input);
+    let covenant_quorum =
+        input.covenant_quorum(&membership_public_input, delegation.params_version);
+    let status = delegation.status(btc_tip_height, covenant_quorum);
+
+    let output = BtcDelegationOutput {
+        consensus_public_input_hash,
+        height: header.height,
+        app_hash: header.app_hash,
+        staking_tx_hash: input.staking_tx_hash,
+        btc_tip_height,
+        covenant_quorum,
+        status,
+        total_sat: delegation.total_sat,
+        staker_btc_pk: delegation.btc_pk,
+        fp_btc_pk_list: delegation.fp_btc_pk_list,
+        start_height: delegation.start_height,
+        end_height: delegation.end_height,
+    };
+    sp1_zkvm::io::commit(&output);
+}
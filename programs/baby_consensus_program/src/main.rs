@@ -1,43 +1,12 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use core::time::Duration;
-use tendermint_light_client_verifier::options::Options;
-use tendermint_light_client_verifier::types::{LightBlock, TrustThreshold};
-use tendermint_light_client_verifier::{ProdVerifier, Verdict, Verifier};
-use zk_light_client_core::babylon::{ConsensusInput, TendermintOutput};
+use tendermint_light_client_verifier::types::TrustThreshold;
+use zk_light_client_core::babylon::{
+    ConsensusInput, ConsensusWitness, ProvenHeader, TendermintOutput, verify_header,
+};
 use zk_light_client_core::sha256_hash;
 
-fn verify_header(trusted_block: &LightBlock, untrusted_block: &LightBlock) {
-    let vp = ProdVerifier::default();
-    // TODO: double check the values below, trusting_period in particular.
-    let opt = Options {
-        trust_threshold: TrustThreshold::TWO_THIRDS,
-        // 2 week trusting period.
-        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
-        clock_drift: Default::default(),
-    };
-
-    // Verify update header doesn't check this property.
-    assert_eq!(
-        trusted_block.next_validators.hash(),
-        trusted_block.as_trusted_state().next_validators_hash
-    );
-
-    let verify_time = untrusted_block.time() + Duration::from_secs(20);
-    let verdict = vp.verify_update_header(
-        untrusted_block.as_untrusted_state(),
-        trusted_block.as_trusted_state(),
-        &opt,
-        verify_time.unwrap(),
-    );
-
-    match verdict {
-        Verdict::Success => {}
-        v => panic!("Failed to verify light client update: {v:?}"),
-    }
-}
-
 fn main() {
     // Read the entire circuit input from the zkVM's stdin.
     let raw_input = sp1_zkvm::io::read_vec();
@@ -50,10 +19,20 @@ fn main() {
         witness,
     } = serde_cbor::from_slice(&raw_input).unwrap();
 
+    let ConsensusWitness {
+        trusted_block,
+        untrusted_block,
+        mut header_mmr,
+    } = witness;
+
     // Verify proof output by the last prover.
     let current_public_input_hash = current_public_input.compute_hash();
 
     let compressed_block_public_input = if proving_block_index == 0 {
+        assert_eq!(
+            header_mmr.leaf_count, 0,
+            "The header MMR must be empty for the first block."
+        );
         sha256_hash(&current_public_input_hash)
     } else {
         // Verify STARK proof output by last STARK prover with committed public values and vkey hash.
@@ -62,16 +41,22 @@ fn main() {
             &parent_public_input.compute_hash(),
         );
 
+        // The trusted block must be the tip of the parent proof.
+        parent_public_input.verify_target_block(&trusted_block);
+
+        assert_eq!(
+            header_mmr.root(),
+            parent_public_input.header_mmr_root,
+            "The header MMR does not match the parent proof."
+        );
+
         let mut bytes = Vec::with_capacity(64);
         bytes.extend_from_slice(&parent_public_input.compressed_block_public_input);
         bytes.extend_from_slice(&current_public_input_hash);
         sha256_hash(&bytes)
     };
 
-    let trusted_block = witness.trusted_block;
-    let untrusted_block = witness.untrusted_block;
-
-    verify_header(&trusted_block, &untrusted_block);
+    verify_header(&trusted_block, &untrusted_block, TrustThreshold::TWO_THIRDS);
 
     // Now that we have verified our proof, we commit the header hashes to the zkVM to expose
     // them as public values.
@@ -81,14 +66,36 @@ fn main() {
     let target_header_hash: [u8; 32] = target_header_hash.as_bytes().to_vec().try_into().unwrap();
     let app_hash = untrusted_block.signed_header.header.app_hash;
     let app_hash: [u8; 32] = app_hash.as_bytes().to_vec().try_into().unwrap();
+    let target_height = untrusted_block.signed_header.header.height.value();
+    let validators_hash = untrusted_block.signed_header.header.validators_hash;
+    let validators_hash: [u8; 32] = validators_hash.as_bytes().to_vec().try_into().unwrap();
+    let next_validators_hash = untrusted_block.signed_header.header.next_validators_hash;
+    let next_validators_hash: [u8; 32] =
+        next_validators_hash.as_bytes().to_vec().try_into().unwrap();
+
+    header_mmr.append(
+        ProvenHeader {
+            height: target_height,
+            header_hash: target_header_hash,
+            app_hash,
+        }
+        .mmr_leaf(),
+    );
 
     let output = TendermintOutput {
+        version: TendermintOutput::VERSION,
         trusted_height: trusted_block.signed_header.header.height.value(),
-        target_height: untrusted_block.signed_header.header.height.value(),
+        target_height,
         trusted_header_hash,
         target_header_hash,
         compressed_block_public_input,
         app_hash,
+        header_mmr_root: header_mmr.root(),
+        validators_hash,
+        next_validators_hash,
+        time: untrusted_block.time().unix_timestamp(),
+        // The validator set is checked against `validators_hash` by the light client.
+        total_voting_power: untrusted_block.validators.total_voting_power().value(),
     };
 
     sp1_zkvm::io::commit(&output);
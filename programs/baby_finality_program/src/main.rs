@@ -0,0 +1,65 @@
+//! A program that proves a Babylon block is finalized by the finality providers.
+//!
+//! This zkVM program verifies two SP1 proofs:
+//! 1. A **consensus proof**, verifying that a given Babylon block header is valid and committing
+//!    to the headers proven so far.
+//! 2. A **membership proof**, showing the votes and the voting power of the finality providers
+//!    in the `finality` store under the application state (`app_hash`) of that header.
+//!
+//! It then verifies the EOTS signatures of the votes for the finalized block, a historical header
+//! of the consensus proof, and checks that they hold more than two thirds of the voting power.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::finality::{FinalityInput, FinalityOutput};
+use zk_light_client_core::babylon::{MembershipOutput, TendermintOutput};
+use zk_light_client_core::sha256_hash;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<FinalityInput>();
+
+    let consensus_public_input_hash = sha256_hash(&input.consensus_public_input);
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.consensus_vkey_u32_hash,
+        &consensus_public_input_hash,
+    );
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.membership_vkey_u32_hash,
+        &sha256_hash(&input.membership_public_input),
+    );
+
+    let (consensus_public_input, _): (TendermintOutput, _) =
+        bincode::decode_from_slice(&input.consensus_public_input, bincode::config::legacy())
+            .expect("failed to decode consensus public input");
+
+    let (membership_public_input, _): (MembershipOutput, _) =
+        bincode::decode_from_slice(&input.membership_public_input, bincode::config::legacy())
+            .expect("failed to decode membership public input");
+
+    // The membership proof may target a historical header covered by the consensus proof.
+    let app_hash = consensus_public_input
+        .resolve_header(input.historical_header.as_ref())
+        .app_hash;
+    assert_eq!(app_hash, membership_public_input.app_hash);
+
+    assert!(
+        input
+            .finalized_header
+            .verify(consensus_public_input.header_mmr_root),
+        "Finalized header is not part of the proven header MMR"
+    );
+
+    let (voted_power, total_power) = input.verify_votes(&membership_public_input);
+
+    let header = input.finalized_header.header;
+    let output = FinalityOutput {
+        consensus_public_input_hash,
+        height: header.height,
+        header_hash: header.header_hash,
+        app_hash: header.app_hash,
+        voted_power,
+        total_power,
+    };
+    sp1_zkvm::io::commit(&output);
+}
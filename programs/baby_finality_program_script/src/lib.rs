@@ -0,0 +1 @@
+pub const BABY_FINALITY_PROGRAM_ELF: &[u8] = sp1_sdk::include_elf!("baby_finality_program");
@@ -0,0 +1,58 @@
+//! A program that proves the IBC packet entries of a Cosmos-based chain.
+//!
+//! This zkVM program verifies two SP1 proofs:
+//! 1. A **consensus proof**, verifying that a given Babylon block header is valid and committing
+//!    to the headers proven so far.
+//! 2. A **membership proof** of packet commitments, receipts or acknowledgements stored under
+//!    their ICS-24 paths in the application state (`app_hash`) of that header.
+//!
+//! It then commits the identifiers of the packets decoded from the paths along with the proven
+//! values and the header they were proven at.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::ibc::{IbcPacketInput, IbcPacketOutput, ProvenPacket};
+use zk_light_client_core::babylon::{MembershipOutput, TendermintOutput};
+use zk_light_client_core::sha256_hash;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<IbcPacketInput>();
+
+    let consensus_public_input_hash = sha256_hash(&input.consensus_public_input);
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.consensus_vkey_u32_hash,
+        &consensus_public_input_hash,
+    );
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.membership_vkey_u32_hash,
+        &sha256_hash(&input.membership_public_input),
+    );
+
+    let (consensus_public_input, _): (TendermintOutput, _) =
+        bincode::decode_from_slice(&input.consensus_public_input, bincode::config::legacy())
+            .expect("failed to decode consensus public input");
+
+    let (membership_public_input, _): (MembershipOutput, _) =
+        bincode::decode_from_slice(&input.membership_public_input, bincode::config::legacy())
+            .expect("failed to decode membership public input");
+
+    // The membership proof may target a historical header covered by the consensus proof.
+    let header = consensus_public_input.resolve_header(input.historical_header.as_ref());
+    assert_eq!(header.app_hash, membership_public_input.app_hash);
+
+    let packets = membership_public_input
+        .kv_pairs
+        .into_iter()
+        .map(ProvenPacket::from_kv_pair)
+        .collect::<Vec<_>>();
+    assert!(!packets.is_empty(), "No IBC packet entry is proven.");
+
+    let output = IbcPacketOutput {
+        consensus_public_input_hash,
+        height: header.height,
+        app_hash: header.app_hash,
+        packets,
+    };
+    sp1_zkvm::io::commit(&output);
+}
@@ -0,0 +1 @@
+pub const BABY_IBC_PACKET_PROGRAM_ELF: &[u8] = sp1_sdk::include_elf!("baby_ibc_packet_program");
@@ -4,13 +4,16 @@
 //! The program takes as input:
 //! - An `app_hash`: the root of the state tree
 //! - A set of key-value pairs and their associated Merkle proofs
+//! - Optional predicates to evaluate on the values
 //!
 //! If all proofs are valid, the program commits a public output containing
-//! the `app_hash` and the verified key-value pairs.
+//! the `app_hash` and the verified key-value pairs, or only the outcomes of the
+//! predicates along with the hashes of the keys they apply to.
 
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
+use zk_light_client_core::babylon::predicate::PredicateOutput;
 use zk_light_client_core::babylon::{MembershipInput, MembershipOutput, verify_membership_proof};
 
 fn main() {
@@ -18,16 +21,36 @@ fn main() {
     let MembershipInput {
         app_hash,
         merkle_proofs,
+        predicates,
     } = sp1_zkvm::io::read();
 
     verify_membership_proof(app_hash, &merkle_proofs);
 
-    let kv_pairs = merkle_proofs
+    let mut kv_pairs = merkle_proofs
         .into_iter()
         .map(|(kv_pair, _raw_proof)| kv_pair)
         .collect::<Vec<_>>();
 
+    let predicates = predicates
+        .into_iter()
+        .map(|(index, predicate)| {
+            let kv_pair = kv_pairs
+                .get(index as usize)
+                .expect("The predicate refers to a missing key-value pair.");
+            PredicateOutput::evaluate(kv_pair, predicate).expect("Failed to evaluate predicate")
+        })
+        .collect::<Vec<_>>();
+
+    // Only the outcomes of the predicates are committed if any.
+    if !predicates.is_empty() {
+        kv_pairs.clear();
+    }
+
     // Commit the public input.
-    let output = MembershipOutput { app_hash, kv_pairs };
+    let output = MembershipOutput {
+        app_hash,
+        kv_pairs,
+        predicates,
+    };
     sp1_zkvm::io::commit(&output);
 }
@@ -0,0 +1,46 @@
+//! A program that proves misbehaviour of the validators of Babylon.
+//!
+//! This zkVM program verifies a **consensus proof**, committing to the headers proven so far, and
+//! takes a block proven by it as trusted. It then verifies two different blocks at the same
+//! height against the trusted block, each signed by validators holding more than one third of
+//! the trusted voting power, and commits the hash of the evidence.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::TendermintOutput;
+use zk_light_client_core::babylon::misbehaviour::{
+    MisbehaviourInput, MisbehaviourOutput, evidence_hash,
+};
+use zk_light_client_core::sha256_hash;
+
+pub fn main() {
+    // The light blocks are not supported by bincode, hence CBOR as for the consensus program.
+    let raw_input = sp1_zkvm::io::read_vec();
+    let input: MisbehaviourInput = serde_cbor::from_slice(&raw_input).unwrap();
+
+    let consensus_public_input_hash = sha256_hash(&input.consensus_public_input);
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &input.consensus_vkey_u32_hash,
+        &consensus_public_input_hash,
+    );
+
+    let (consensus_public_input, _): (TendermintOutput, _) =
+        bincode::decode_from_slice(&input.consensus_public_input, bincode::config::legacy())
+            .expect("failed to decode consensus public input");
+
+    // The trusted block may be a historical header covered by the consensus proof.
+    let trusted_header = consensus_public_input.resolve_header(input.historical_header.as_ref());
+
+    let (height, header_hashes) = input.verify(&trusted_header);
+
+    let output = MisbehaviourOutput {
+        consensus_public_input_hash,
+        trusted_height: trusted_header.height,
+        trusted_header_hash: trusted_header.header_hash,
+        height,
+        header_hashes,
+        evidence_hash: evidence_hash(height, &trusted_header.header_hash, &header_hashes),
+    };
+    sp1_zkvm::io::commit(&output);
+}
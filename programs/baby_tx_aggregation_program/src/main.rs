@@ -0,0 +1,58 @@
+//! Aggregation program for Babylon transactions.
+//!
+//! This zkVM program aggregates two SP1 proofs:
+//! 1. A **consensus validity proof**, verifying that a given block header is valid.
+//! 2. A **transaction inclusion proof**, showing that a transaction is included in a block.
+//!
+//! The header of the block must be the target header of the consensus proof or a historical
+//! header covered by its header MMR, or the next header if the result of the transaction is
+//! proven. The program commits the transaction inclusion output along with the hash of the
+//! consensus public values.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::TendermintOutput;
+use zk_light_client_core::babylon::tx::{
+    TxAggregationInput, TxAggregationOutput, TxInclusionOutput,
+};
+use zk_light_client_core::sha256_hash;
+
+pub fn main() {
+    let TxAggregationInput {
+        consensus_vkey_u32_hash,
+        consensus_public_input,
+        tx_inclusion_vkey_u32_hash,
+        tx_inclusion_public_input,
+        historical_header,
+    } = sp1_zkvm::io::read::<TxAggregationInput>();
+
+    let consensus_public_input_hash = sha256_hash(&consensus_public_input);
+    sp1_zkvm::lib::verify::verify_sp1_proof(&consensus_vkey_u32_hash, &consensus_public_input_hash);
+
+    sp1_zkvm::lib::verify::verify_sp1_proof(
+        &tx_inclusion_vkey_u32_hash,
+        &sha256_hash(&tx_inclusion_public_input),
+    );
+
+    let (consensus_public_input, _): (TendermintOutput, _) =
+        bincode::decode_from_slice(&consensus_public_input, bincode::config::legacy())
+            .expect("failed to decode consensus public input");
+
+    let (tx_inclusion_public_input, _): (TxInclusionOutput, _) =
+        bincode::decode_from_slice(&tx_inclusion_public_input, bincode::config::legacy())
+            .expect("failed to decode transaction inclusion public input");
+
+    let header = consensus_public_input.resolve_header(historical_header.as_ref());
+
+    assert_eq!(
+        (header.height, header.header_hash),
+        tx_inclusion_public_input.proven_header(),
+        "The transaction is not included in the proven header."
+    );
+
+    sp1_zkvm::io::commit(&TxAggregationOutput {
+        consensus_public_input_hash,
+        tx_inclusion: tx_inclusion_public_input,
+    });
+}
@@ -0,0 +1,2 @@
+pub const BABY_TX_AGGREGATION_PROGRAM_ELF: &[u8] =
+    sp1_sdk::include_elf!("baby_tx_aggregation_program");
@@ -0,0 +1,22 @@
+//! A program that proves the inclusion of a transaction in a block of a Cosmos-based chain.
+//!
+//! The program verifies the transaction against the `data_hash` of the block header and commits
+//! the hash of the transaction along with the height and the hash of the header, to be matched
+//! against a consensus proof by the transaction aggregation program.
+//!
+//! Optionally, the result of the transaction is verified against the `last_results_hash` of the
+//! next header and committed as well, proving that the transaction succeeded.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::babylon::tx::TxInclusionInput;
+
+fn main() {
+    // The header is not supported by bincode, hence CBOR as for the consensus program.
+    let raw_input = sp1_zkvm::io::read_vec();
+    let input: TxInclusionInput = serde_cbor::from_slice(&raw_input).unwrap();
+
+    let output = input.verify();
+    sp1_zkvm::io::commit(&output);
+}
@@ -0,0 +1 @@
+pub const BABY_TX_INCLUSION_PROGRAM_ELF: &[u8] = sp1_sdk::include_elf!("baby_tx_inclusion_program");
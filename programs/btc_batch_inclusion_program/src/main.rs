@@ -0,0 +1,18 @@
+//! A program that proves the inclusion of a batch of Bitcoin transactions.
+//!
+//! The program takes as input the transactions of each block along with a merkle multiproof,
+//! and commits the merkle root of every block together with the hash of the set of proven
+//! transaction ids.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::bitcoin::batch_inclusion::BatchInclusionInput;
+
+fn main() {
+    let batch_inclusion_input = sp1_zkvm::io::read::<BatchInclusionInput>();
+
+    batch_inclusion_input.verify();
+
+    sp1_zkvm::io::commit(&batch_inclusion_input.public_input);
+}
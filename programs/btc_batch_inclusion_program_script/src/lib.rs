@@ -0,0 +1,2 @@
+pub const BTC_BATCH_INCLUSION_PROGRAM_ELF: &[u8] =
+    sp1_sdk::include_elf!("btc_batch_inclusion_program");
@@ -0,0 +1,115 @@
+//! A program that recursively proves the consensus validity of Bitcoin blocks.
+//!
+//! Each proof verifies the proof of the previous block, validates the proposed block against
+//! the witness chain and appends it to the header MMR, so that the latest proof commits to
+//! every block proven so far.
+//!
+//! On a signet, the proposed block must also carry a valid solution of the signet challenge.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::bitcoin::block::validate_block;
+use zk_light_client_core::bitcoin::consensus::{
+    ConsensusInput, ConsensusVerifierPublicInput, ConsensusWitness,
+};
+use zk_light_client_core::bitcoin::signet::{signet_challenge_hash, verify_signet_solution};
+use zk_light_client_core::sha256_hash;
+
+fn main() {
+    let ConsensusInput {
+        seq,
+        circuit_vkey_u32_hash,
+        parent_proof_commitment,
+        block_public_input,
+        confirmation_depth,
+        signet_challenge,
+        witness,
+    } = sp1_zkvm::io::read::<ConsensusInput>();
+
+    let ConsensusWitness {
+        proposed_chain,
+        retarget_block,
+        mut header_mmr,
+        signet,
+    } = witness;
+
+    let signet_challenge_hash = signet_challenge
+        .as_deref()
+        .map_or([0u8; 32], signet_challenge_hash);
+
+    let block_public_input_hash = block_public_input.compute_hash();
+
+    let compressed_block_public_input = if seq == 0 {
+        assert_eq!(
+            header_mmr.leaf_count, 0,
+            "The header MMR must be empty for the initial block."
+        );
+        sha256_hash(&block_public_input_hash)
+    } else {
+        // Verify STARK proof output by last STARK prover with committed public values and vkey hash.
+        sp1_zkvm::lib::verify::verify_sp1_proof(
+            &circuit_vkey_u32_hash,
+            &parent_proof_commitment.compute_hash(),
+        );
+
+        // The proposed block must extend the tip of the parent proof.
+        assert_eq!(
+            block_public_input.proposed_block_height,
+            parent_proof_commitment.current_block_height + 1,
+            "The proposed block does not follow the parent proof."
+        );
+        assert_eq!(
+            block_public_input.prev_block_hash, parent_proof_commitment.current_block_hash,
+            "The proposed block does not extend the tip of the parent proof."
+        );
+
+        assert_eq!(
+            header_mmr.root(),
+            parent_proof_commitment.header_mmr_root,
+            "The header MMR does not match the parent proof."
+        );
+        assert_eq!(
+            confirmation_depth, parent_proof_commitment.confirmation_depth,
+            "The confirmation depth does not match the parent proof."
+        );
+        assert_eq!(
+            signet_challenge_hash, parent_proof_commitment.signet_challenge_hash,
+            "The signet challenge does not match the parent proof."
+        );
+
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(&parent_proof_commitment.compressed_block_public_input);
+        bytes.extend_from_slice(&block_public_input_hash);
+        sha256_hash(&bytes)
+    };
+
+    if let Some(challenge) = &signet_challenge {
+        let proposed_block = proposed_chain
+            .last()
+            .expect("The proposed chain must not be empty.");
+        let signet = signet.expect("The signet witness is required on a signet.");
+        verify_signet_solution(proposed_block, challenge, &signet);
+    }
+
+    validate_block(
+        proposed_chain,
+        retarget_block,
+        block_public_input,
+        confirmation_depth,
+    );
+
+    header_mmr.append(block_public_input.header_mmr_leaf());
+
+    let output = ConsensusVerifierPublicInput::new(
+        compressed_block_public_input,
+        block_public_input.m_deep_tx_merkle_root,
+        block_public_input.proposed_block_height,
+        block_public_input.proposed_block_hash,
+        header_mmr.root(),
+        confirmation_depth,
+        signet_challenge_hash,
+    );
+
+    sp1_zkvm::io::commit(&output);
+}
@@ -0,0 +1 @@
+pub const BTC_CONSENSUS_PROGRAM_ELF: &[u8] = sp1_sdk::include_elf!("btc_consensus_program");
@@ -0,0 +1,21 @@
+//! A program that proves the inclusion of a Bitcoin transaction in a block.
+//!
+//! The program takes as input the legacy serialization of the transaction and its merkle proof,
+//! and commits the transaction id along with the merkle root of the block. The merkle root is
+//! linked to a proven block by the aggregation program.
+//!
+//! When a peg-in template is set, the program also enforces that the transaction pays to the
+//! expected taproot address with at least the minimum amount, and commits the paid amount.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::bitcoin::inclusion::InclusionInput;
+
+fn main() {
+    let inclusion_input = sp1_zkvm::io::read::<InclusionInput>();
+
+    inclusion_input.verify();
+
+    sp1_zkvm::io::commit(&inclusion_input.public_input);
+}
@@ -0,0 +1 @@
+pub const BTC_INCLUSION_PROGRAM_ELF: &[u8] = sp1_sdk::include_elf!("btc_inclusion_program");
@@ -0,0 +1,27 @@
+//! A program that verifies BIP340 Schnorr signatures.
+//!
+//! It is only used to test the signature verification inside the zkVM, where `k256` is replaced
+//! by the SP1 patch. The program takes as input a list of `(public key, message, signature)` and
+//! commits whether each signature is valid.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_light_client_core::bitcoin::schnorr::verify_schnorr_signature;
+
+fn main() {
+    let signatures = sp1_zkvm::io::read::<Vec<([u8; 32], Vec<u8>, Vec<u8>)>>();
+
+    let valid = signatures
+        .iter()
+        .map(|(public_key, message, signature)| {
+            let signature = signature
+                .as_slice()
+                .try_into()
+                .expect("A signature must be 64 bytes.");
+            verify_schnorr_signature(public_key, message, signature).is_ok()
+        })
+        .collect::<Vec<_>>();
+
+    sp1_zkvm::io::commit(&valid);
+}
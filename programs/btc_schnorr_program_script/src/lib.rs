@@ -0,0 +1 @@
+pub const BTC_SCHNORR_PROGRAM_ELF: &[u8] = sp1_sdk::include_elf!("btc_schnorr_program");
@@ -1,13 +1,34 @@
 pub use baby_aggregation_program_script::BABY_AGGREGATION_PROGRAM_ELF;
+pub use baby_btc_checkpoint_program_script::BABY_BTC_CHECKPOINT_PROGRAM_ELF;
+pub use baby_btc_delegation_program_script::BABY_BTC_DELEGATION_PROGRAM_ELF;
 pub use baby_consensus_program_script::BABY_CONSENSUS_PROGRAM_ELF;
+pub use baby_finality_program_script::BABY_FINALITY_PROGRAM_ELF;
+pub use baby_ibc_packet_program_script::BABY_IBC_PACKET_PROGRAM_ELF;
 pub use baby_membership_program_script::BABY_MEMBERSHIP_PROGRAM_ELF;
+pub use baby_misbehaviour_program_script::BABY_MISBEHAVIOUR_PROGRAM_ELF;
+pub use baby_tx_aggregation_program_script::BABY_TX_AGGREGATION_PROGRAM_ELF;
+pub use baby_tx_inclusion_program_script::BABY_TX_INCLUSION_PROGRAM_ELF;
 use bitcoin::Transaction;
 use bitcoin::consensus::encode::Encodable;
 use bitcoin::hashes::Hash;
 use bitcoin::hashes::hex::FromHex;
-use zk_light_client_core::bitcoin::block::CircuitBlock;
-use zk_light_client_core::bitcoin::hash_pairs;
-use zk_light_client_core::bitcoin::inclusion::MerkleProofStep;
+use bitcoin::merkle_tree::{MerkleBlock, MerkleBlockError, PartialMerkleTree};
+pub use btc_batch_inclusion_program_script::BTC_BATCH_INCLUSION_PROGRAM_ELF;
+pub use btc_consensus_program_script::BTC_CONSENSUS_PROGRAM_ELF;
+pub use btc_inclusion_program_script::BTC_INCLUSION_PROGRAM_ELF;
+use zk_light_client_core::bitcoin::batch_inclusion::{
+    BatchBlock, BatchInclusionInput, BatchInclusionWitness, BlockBatchWitness,
+};
+use zk_light_client_core::bitcoin::block::{CircuitBlock, HeaderConversionError};
+use zk_light_client_core::bitcoin::consensus::ConsensusBlockPublicInput;
+use zk_light_client_core::bitcoin::inclusion::{
+    InclusionInput, MerkleMultiProof, MerkleProofStep, SegwitInclusionWitness,
+};
+use zk_light_client_core::bitcoin::signet::SignetWitness;
+use zk_light_client_core::bitcoin::{
+    MIN_TRUSTED_BLOCK_NUMBER, double_sha256_hash, hash_pairs, to_little_endian_bytes,
+};
+use zk_light_client_core::mmr::{MmrProof, perfect_subtree_root};
 
 pub fn load_hex_bytes(file: &str) -> Vec<u8> {
     let hex_string = std::fs::read_to_string(file).expect("Failed to read file");
@@ -39,20 +60,124 @@ pub fn serialize_legacy_tx(tx: &Transaction) -> Vec<u8> {
     buffer
 }
 
-/// Converts a [`bitcoin::block::Header`] into a [`CircuitBlock`].
-pub fn to_circuit_block(header: &bitcoin::block::Header, height: u64) -> CircuitBlock {
-    CircuitBlock {
-        height,
-        version: header.version.to_consensus().to_le_bytes(),
-        prev_blockhash: header.prev_blockhash.to_raw_hash().to_byte_array(),
-        merkle_root: header.merkle_root.to_raw_hash().to_byte_array(),
-        time: header.time.to_le_bytes(),
-        bits: header.bits.to_consensus().to_le_bytes(),
-        nonce: header.nonce.to_le_bytes(),
+/// Generates the inclusion input of the transaction at `tx_index` in `block`.
+///
+/// For segwit transactions, the wtxid is proven as well against the coinbase witness commitment.
+///
+/// # Panics
+///
+/// Panics if `tx_index` is out of bounds.
+pub fn generate_inclusion_input(block: &bitcoin::Block, tx_index: usize) -> InclusionInput {
+    let tx = &block.txdata[tx_index];
+    let tx_id = to_little_endian_bytes(tx.compute_txid().to_byte_array());
+    let txids = block
+        .txdata
+        .iter()
+        .map(|tx| to_little_endian_bytes(tx.compute_txid().to_byte_array()))
+        .collect::<Vec<_>>();
+    let (tx_merkle_proof, tx_merkle_root) = generate_merkle_proof_and_root(txids.clone(), tx_id);
+    let tx_count = txids.len() as u32;
+
+    let inclusion_input = InclusionInput::new(
+        serialize_legacy_tx(tx),
+        tx_index as u32,
+        tx_merkle_proof,
+        tx_id,
+        tx_merkle_root,
+        tx_count,
+    );
+
+    // The wtxid of the coinbase is zero and can not be proven.
+    if tx_index == 0 || tx.input.iter().all(|input| input.witness.is_empty()) {
+        return inclusion_input;
+    }
+
+    let wtx_id = to_little_endian_bytes(tx.compute_wtxid().to_byte_array());
+    let wtxids = block
+        .txdata
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            if index == 0 {
+                [0u8; 32]
+            } else {
+                to_little_endian_bytes(tx.compute_wtxid().to_byte_array())
+            }
+        })
+        .collect::<Vec<_>>();
+    let (wtx_merkle_proof, _) = generate_merkle_proof_and_root(wtxids, wtx_id);
+    let (coinbase_merkle_proof, _) = generate_merkle_proof_and_root(txids.clone(), txids[0]);
+
+    inclusion_input.with_segwit_witness(
+        wtx_id,
+        SegwitInclusionWitness {
+            segwit_tx: bitcoin::consensus::serialize(tx),
+            wtx_merkle_proof,
+            coinbase_tx: bitcoin::consensus::serialize(&block.txdata[0]),
+            coinbase_merkle_proof,
+        },
+    )
+}
+
+/// Generates the witness of the signet solution of `block`, proving its coinbase transaction.
+pub fn generate_signet_witness(block: &bitcoin::Block) -> SignetWitness {
+    let txids = block
+        .txdata
+        .iter()
+        .map(|tx| to_little_endian_bytes(tx.compute_txid().to_byte_array()))
+        .collect::<Vec<_>>();
+    let (coinbase_merkle_proof, _) = generate_merkle_proof_and_root(txids.clone(), txids[0]);
+
+    SignetWitness {
+        coinbase_tx: serialize_legacy_tx(&block.txdata[0]),
+        coinbase_merkle_proof,
+        tx_count: txids.len() as u32,
     }
 }
 
+/// Generates the public input of the last block of `proposed_chain`, as checked by
+/// `validate_block`.
+///
+/// # Panics
+///
+/// Panics if `proposed_chain` has less than `MIN_TRUSTED_BLOCK_NUMBER + 1` blocks, or not more
+/// blocks than `confirmation_depth`.
+pub fn generate_consensus_block_public_input(
+    proposed_chain: &[CircuitBlock],
+    retarget_block: &CircuitBlock,
+    confirmation_depth: u32,
+) -> ConsensusBlockPublicInput {
+    let chain_len = proposed_chain.len();
+    assert!(
+        chain_len > MIN_TRUSTED_BLOCK_NUMBER,
+        "Proposed chain must have at least {} blocks",
+        MIN_TRUSTED_BLOCK_NUMBER + 1
+    );
+    assert!(
+        chain_len > confirmation_depth as usize,
+        "Proposed chain must have more blocks than the confirmation depth"
+    );
+    let proposed_block = &proposed_chain[chain_len - 1];
+
+    // Sorted the same way as in the circuit, so that the same block is picked on equal timestamps.
+    let mut observing_blocks =
+        proposed_chain[chain_len - MIN_TRUSTED_BLOCK_NUMBER - 1..chain_len - 1].to_vec();
+    observing_blocks.sort_by_key(|block| u32::from_le_bytes(block.time));
+    let median_block = &observing_blocks[MIN_TRUSTED_BLOCK_NUMBER / 2];
+
+    ConsensusBlockPublicInput::new(
+        proposed_chain[chain_len - 2].compute_block_hash(),
+        proposed_block.compute_block_hash(),
+        retarget_block.compute_block_hash(),
+        median_block.compute_block_hash(),
+        proposed_chain[chain_len - confirmation_depth as usize - 1].merkle_root,
+        proposed_block.height,
+    )
+}
+
 // Expects leaves to be in little-endian format (as shown on explorers)
+//
+// Panics if the leaves form a mutated tree (CVE-2012-2459), i.e. two sibling nodes are identical.
 pub fn generate_merkle_proof_and_root(
     leaves: Vec<[u8; 32]>,
     desired_leaf: [u8; 32],
@@ -71,6 +196,11 @@ pub fn generate_merkle_proof_and_root(
         while i < current_level.len() {
             let left = current_level[i];
             let right = if i + 1 < current_level.len() {
+                assert_ne!(
+                    left,
+                    current_level[i + 1],
+                    "Mutated merkle tree: identical sibling nodes"
+                );
                 current_level[i + 1]
             } else {
                 left
@@ -104,3 +234,780 @@ pub fn generate_merkle_proof_and_root(
     let merkle_root = current_level[0];
     (proof, merkle_root)
 }
+
+/// Generates the merkle multiproof of the leaves at `leaf_indices` and the merkle root.
+///
+/// Expects leaves to be in little-endian format (as shown on explorers), the indices are sorted
+/// and deduplicated.
+///
+/// # Panics
+///
+/// Panics if an index is out of bounds, or if the leaves form a mutated tree (CVE-2012-2459).
+pub fn generate_merkle_multiproof(
+    leaves: Vec<[u8; 32]>,
+    leaf_indices: &[u32],
+) -> (MerkleMultiProof, [u8; 32]) {
+    let mut tx_indices = leaf_indices.to_vec();
+    tx_indices.sort_unstable();
+    tx_indices.dedup();
+    assert!(
+        tx_indices
+            .last()
+            .is_some_and(|&index| (index as usize) < leaves.len()),
+        "Leaf index out of bounds"
+    );
+
+    let mut current_level = leaves;
+    let mut known = tx_indices.clone();
+    let mut hashes = Vec::new();
+
+    while current_level.len() > 1 {
+        let width = current_level.len() as u32;
+        let mut i = 0;
+        while i < known.len() {
+            let index = known[i];
+            if index % 2 == 1 {
+                hashes.push(current_level[index as usize - 1]);
+            } else if index + 1 == width {
+                // The last node of an odd level is paired with itself.
+            } else if known.get(i + 1) == Some(&(index + 1)) {
+                i += 1;
+            } else {
+                hashes.push(current_level[index as usize + 1]);
+            }
+            i += 1;
+        }
+
+        current_level = current_level
+            .chunks(2)
+            .map(|pair| {
+                let right = pair.get(1).copied().unwrap_or(pair[0]);
+                if pair.len() == 2 {
+                    assert_ne!(
+                        pair[0], right,
+                        "Mutated merkle tree: identical sibling nodes"
+                    );
+                }
+                hash_pairs(pair[0], right)
+            })
+            .collect();
+        known = known.into_iter().map(|index| index / 2).collect();
+        known.dedup();
+    }
+
+    (MerkleMultiProof { tx_indices, hashes }, current_level[0])
+}
+
+/// Generates the batch inclusion input of the transactions at the given indices of each block.
+///
+/// # Panics
+///
+/// Panics if an index is out of bounds or a transaction appears in several blocks.
+pub fn generate_batch_inclusion_input(blocks: &[(&bitcoin::Block, &[u32])]) -> BatchInclusionInput {
+    let (batch_blocks, witnesses) = blocks
+        .iter()
+        .map(|(block, tx_indices)| {
+            let txids = block
+                .txdata
+                .iter()
+                .map(|tx| to_little_endian_bytes(tx.compute_txid().to_byte_array()))
+                .collect::<Vec<_>>();
+            let tx_count = txids.len() as u32;
+            let (merkle_multiproof, tx_merkle_root) =
+                generate_merkle_multiproof(txids.clone(), tx_indices);
+            let legacy_txs = merkle_multiproof
+                .tx_indices
+                .iter()
+                .map(|&index| serialize_legacy_tx(&block.txdata[index as usize]))
+                .collect();
+
+            (
+                BatchBlock { tx_merkle_root },
+                BlockBatchWitness {
+                    legacy_txs,
+                    tx_count,
+                    merkle_multiproof,
+                },
+            )
+        })
+        .unzip();
+
+    BatchInclusionInput::new(batch_blocks, BatchInclusionWitness { blocks: witnesses })
+}
+
+/// Generates the proof of the leaf at `leaf_index` against the MMR built from `leaves`.
+///
+/// # Panics
+///
+/// Panics if `leaf_index` is out of bounds.
+pub fn generate_mmr_proof(leaves: &[[u8; 32]], leaf_index: u64) -> MmrProof {
+    let leaf_count = leaves.len() as u64;
+    assert!(leaf_index < leaf_count, "MMR leaf index out of bounds");
+
+    let mut siblings = Vec::new();
+    let mut peaks = Vec::new();
+    let mut offset = 0u64;
+    for height in (0..u64::BITS).rev() {
+        let size = 1u64 << height;
+        if leaf_count & size == 0 {
+            continue;
+        }
+        let subtree = &leaves[offset as usize..(offset + size) as usize];
+        let local_index = (offset..offset + size)
+            .contains(&leaf_index)
+            .then(|| (leaf_index - offset) as usize);
+        peaks.push(perfect_subtree_root(subtree, local_index, &mut siblings));
+        offset += size;
+    }
+
+    MmrProof {
+        leaf_index,
+        leaf_count,
+        siblings,
+        peaks,
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TxOutProofError {
+    #[error("Failed to decode merkle block: {0}")]
+    Decode(#[from] bitcoin::consensus::encode::Error),
+    #[error("Invalid partial merkle tree: {0}")]
+    MerkleBlock(#[from] MerkleBlockError),
+    #[error("Invalid block header: {0}")]
+    Header(#[from] HeaderConversionError),
+}
+
+/// A transaction matched by a partial merkle tree.
+#[derive(Debug, Clone)]
+pub struct MatchedTx {
+    /// Transaction id in little-endian format (as shown on explorers).
+    pub tx_id: [u8; 32],
+    /// Position of the transaction in the block.
+    pub tx_index: u32,
+    pub tx_merkle_proof: Vec<MerkleProofStep>,
+}
+
+/// The `merkleblock` returned by Bitcoin Core's `gettxoutproof`, converted into circuit types.
+#[derive(Debug, Clone)]
+pub struct TxOutProof {
+    pub block: CircuitBlock,
+    /// Transaction merkle root in little-endian format (as shown on explorers).
+    pub tx_merkle_root: [u8; 32],
+    /// Number of transactions in the block.
+    pub tx_count: u32,
+    pub matched_txs: Vec<MatchedTx>,
+}
+
+impl TxOutProof {
+    /// Builds the inclusion input of `tx`, returns `None` if `tx` is not matched by the proof.
+    pub fn inclusion_input(&self, tx: &Transaction) -> Option<InclusionInput> {
+        let legacy_tx = serialize_legacy_tx(tx);
+        let tx_id = double_sha256_hash(&legacy_tx);
+        let matched_tx = self.matched_txs.iter().find(|m| m.tx_id == tx_id)?;
+        Some(InclusionInput::new(
+            legacy_tx,
+            matched_tx.tx_index,
+            matched_tx.tx_merkle_proof.clone(),
+            tx_id,
+            self.tx_merkle_root,
+            self.tx_count,
+        ))
+    }
+}
+
+/// Parses the hex-decoded output of `gettxoutproof` (a BIP37 `merkleblock`).
+///
+/// The partial merkle tree is validated against the block header before the merkle proof of each
+/// matched transaction is extracted.
+pub fn parse_tx_out_proof(
+    merkle_block: &[u8],
+    block_height: u64,
+) -> Result<TxOutProof, TxOutProofError> {
+    let merkle_block: MerkleBlock = bitcoin::consensus::deserialize(merkle_block)?;
+
+    // Validates the structure of the partial merkle tree, including CVE-2012-2459.
+    merkle_block.extract_matches(&mut Vec::new(), &mut Vec::new())?;
+
+    let pmt = &merkle_block.txn;
+    let tx_count = pmt.num_transactions();
+    let mut tree_height = 0;
+    while partial_tree_width(tx_count, tree_height) > 1 {
+        tree_height += 1;
+    }
+
+    let mut traversal = PartialTreeTraversal {
+        pmt,
+        bits_used: 0,
+        hashes_used: 0,
+    };
+    let mut matched_txs = Vec::new();
+    let tx_merkle_root = traversal.traverse(tree_height, 0, &mut matched_txs);
+
+    Ok(TxOutProof {
+        block: CircuitBlock::try_from((&merkle_block.header, block_height))?,
+        tx_merkle_root,
+        tx_count,
+        matched_txs,
+    })
+}
+
+/// Number of nodes at `height` of a merkle tree with `tx_count` leaves.
+fn partial_tree_width(tx_count: u32, height: u32) -> u32 {
+    (tx_count + (1 << height) - 1) >> height
+}
+
+/// Depth-first traversal of a partial merkle tree, as specified in BIP37.
+struct PartialTreeTraversal<'a> {
+    pmt: &'a PartialMerkleTree,
+    bits_used: usize,
+    hashes_used: usize,
+}
+
+impl PartialTreeTraversal<'_> {
+    /// Returns the hash of the node at (`height`, `pos`), recording the matched leaves below it.
+    fn traverse(&mut self, height: u32, pos: u32, matched_txs: &mut Vec<MatchedTx>) -> [u8; 32] {
+        let parent_of_match = self.pmt.bits()[self.bits_used];
+        self.bits_used += 1;
+
+        if height == 0 || !parent_of_match {
+            let hash = to_little_endian_bytes(
+                self.pmt.hashes()[self.hashes_used]
+                    .to_raw_hash()
+                    .to_byte_array(),
+            );
+            self.hashes_used += 1;
+            if height == 0 && parent_of_match {
+                matched_txs.push(MatchedTx {
+                    tx_id: hash,
+                    tx_index: pos,
+                    tx_merkle_proof: Vec::new(),
+                });
+            }
+            return hash;
+        }
+
+        let first_left = matched_txs.len();
+        let left = self.traverse(height - 1, pos * 2, matched_txs);
+        let first_right = matched_txs.len();
+        let right = if pos * 2 + 1 < partial_tree_width(self.pmt.num_transactions(), height - 1) {
+            self.traverse(height - 1, pos * 2 + 1, matched_txs)
+        } else {
+            left
+        };
+
+        matched_txs[first_left..first_right]
+            .iter_mut()
+            .for_each(|matched_tx| {
+                matched_tx.tx_merkle_proof.push(MerkleProofStep {
+                    hash: right,
+                    direction: true,
+                })
+            });
+        matched_txs[first_right..]
+            .iter_mut()
+            .for_each(|matched_tx| {
+                matched_tx.tx_merkle_proof.push(MerkleProofStep {
+                    hash: left,
+                    direction: false,
+                })
+            });
+
+        hash_pairs(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zk_light_client_core::bitcoin::inclusion::{verify_merkle_proof, witness_commitment_index};
+    use zk_light_client_core::mmr::MerkleMountainRange;
+    use zk_light_client_core::sha256_hash;
+
+    #[test]
+    fn mmr_proofs_verify_for_every_leaf() {
+        let leaves = (0u64..40)
+            .map(|i| sha256_hash(&i.to_le_bytes()))
+            .collect::<Vec<_>>();
+        for leaf_count in 1..=leaves.len() {
+            let leaves = &leaves[..leaf_count];
+            let root = MerkleMountainRange::from_leaves(leaves).root();
+            for (leaf_index, leaf) in leaves.iter().enumerate() {
+                let proof = generate_mmr_proof(leaves, leaf_index as u64);
+                assert!(proof.verify(root, *leaf), "{leaf_index} of {leaf_count}");
+                assert!(!proof.verify(root, [0u8; 32]));
+                assert!(!proof.verify([0u8; 32], *leaf));
+            }
+        }
+    }
+
+    #[test]
+    fn mmr_proof_is_bound_to_leaf_index() {
+        let leaves = (0u64..7)
+            .map(|i| sha256_hash(&i.to_le_bytes()))
+            .collect::<Vec<_>>();
+        let root = MerkleMountainRange::from_leaves(&leaves).root();
+        let proof = generate_mmr_proof(&leaves, 1);
+        assert!(!proof.verify(root, leaves[0]));
+        assert!(
+            !MmrProof {
+                leaf_index: 0,
+                ..proof
+            }
+            .verify(root, leaves[1])
+        );
+    }
+
+    #[test]
+    fn batch_inclusion_of_genesis_coinbase() {
+        let block = bitcoin::blockdata::constants::genesis_block(bitcoin::Network::Bitcoin);
+        let input = generate_batch_inclusion_input(&[(&block, &[0])]);
+        input.verify();
+        assert_eq!(input.witness.blocks[0].tx_count, 1);
+        assert_eq!(
+            input.public_input.blocks[0].tx_merkle_root,
+            to_little_endian_bytes(block.header.merkle_root.to_byte_array())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "64-byte transactions are not supported.")]
+    fn batch_inclusion_rejects_inner_nodes() {
+        let block = bitcoin::blockdata::constants::genesis_block(bitcoin::Network::Bitcoin);
+        let mut input = generate_batch_inclusion_input(&[(&block, &[0])]);
+        input.witness.blocks[0].legacy_txs[0] = vec![0u8; 64];
+        input.verify();
+    }
+
+    /// Synthetic block of a coinbase committing to the witnesses, followed by a segwit, a legacy
+    /// and another segwit transaction.
+    fn segwit_block() -> bitcoin::Block {
+        use bitcoin::absolute::LockTime;
+        use bitcoin::transaction::Version;
+        use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Txid, Witness};
+
+        let tx = |n: u8, script_sig: Vec<u8>, witness: &[Vec<u8>]| Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), n.into()),
+                script_sig: ScriptBuf::from_bytes(script_sig),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::from_slice(witness),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(1_000 * u64::from(n)),
+                script_pubkey: ScriptBuf::from_bytes([[0x00, 0x14].as_slice(), &[n; 20]].concat()),
+            }],
+        };
+        let coinbase = Transaction {
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                // BIP34 height.
+                script_sig: ScriptBuf::from_bytes(vec![0x03, 0x8d, 0xb9, 0x0a]),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[[0u8; 32]]),
+            }],
+            ..tx(50, Vec::new(), &[])
+        };
+
+        let block = bitcoin::Block {
+            header: bitcoin::block::Header {
+                version: bitcoin::block::Version::from_consensus(0x2000_0000),
+                prev_blockhash: bitcoin::BlockHash::all_zeros(),
+                merkle_root: bitcoin::TxMerkleNode::all_zeros(),
+                time: 1_700_000_000,
+                bits: bitcoin::CompactTarget::from_consensus(0x207f_ffff),
+                nonce: 0,
+            },
+            txdata: vec![
+                coinbase,
+                tx(1, Vec::new(), &[vec![1; 72], vec![2; 33]]),
+                tx(2, [[0x48].as_slice(), &[2; 72]].concat(), &[]),
+                tx(3, Vec::new(), &[vec![3; 71], vec![2; 33]]),
+            ],
+        };
+        let commitment =
+            bitcoin::Block::compute_witness_commitment(&block.witness_root().unwrap(), &[0u8; 32]);
+        with_coinbase(block, |coinbase| {
+            coinbase
+                .output
+                .push(witness_commitment_output(commitment.to_byte_array()));
+        })
+    }
+
+    /// Applies `mutate` to the coinbase transaction of `block` and updates its merkle root.
+    fn with_coinbase(
+        mut block: bitcoin::Block,
+        mutate: impl FnOnce(&mut Transaction),
+    ) -> bitcoin::Block {
+        mutate(&mut block.txdata[0]);
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        block
+    }
+
+    /// Returns a witness commitment output committing to `commitment`.
+    fn witness_commitment_output(commitment: [u8; 32]) -> bitcoin::TxOut {
+        let mut script_pubkey = vec![0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+        script_pubkey.extend_from_slice(&commitment);
+        bitcoin::TxOut {
+            value: bitcoin::Amount::ZERO,
+            script_pubkey: bitcoin::ScriptBuf::from_bytes(script_pubkey),
+        }
+    }
+
+    #[test]
+    fn wtxid_of_a_segwit_tx_verifies() {
+        let block = segwit_block();
+        let tx = &block.txdata[1];
+        assert!(tx.input.iter().any(|input| !input.witness.is_empty()));
+
+        let input = generate_inclusion_input(&block, 1);
+        assert!(input.witness.segwit.is_some());
+        assert_eq!(
+            input.public_input.wtx_id,
+            to_little_endian_bytes(tx.compute_wtxid().to_byte_array())
+        );
+        assert_eq!(
+            input.public_input.tx_merkle_root,
+            to_little_endian_bytes(block.header.merkle_root.to_byte_array())
+        );
+        input.verify();
+    }
+
+    #[test]
+    fn witness_commitment_index_prefers_the_last_commitment() {
+        let block = segwit_block();
+        let parse = |coinbase: &Transaction| {
+            zk_light_client_core::bitcoin::transaction::Transaction::parse(
+                &bitcoin::consensus::serialize(coinbase),
+            )
+            .unwrap()
+        };
+        let index = witness_commitment_index(&parse(&block.txdata[0])).unwrap();
+
+        let mut coinbase = block.txdata[0].clone();
+        coinbase.output.push(witness_commitment_output([1u8; 32]));
+        assert_eq!(
+            witness_commitment_index(&parse(&coinbase)),
+            Some(coinbase.output.len() - 1)
+        );
+
+        coinbase.output.truncate(index);
+        assert_eq!(witness_commitment_index(&parse(&coinbase)), None);
+    }
+
+    #[test]
+    fn wtxid_verifies_with_an_earlier_altered_commitment() {
+        let block = with_coinbase(segwit_block(), |coinbase| {
+            coinbase
+                .output
+                .insert(0, witness_commitment_output([1u8; 32]));
+        });
+        generate_inclusion_input(&block, 1).verify();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The witness merkle root does not match the coinbase witness commitment."
+    )]
+    fn rejects_a_tampered_witness_reserved_value() {
+        let block = with_coinbase(segwit_block(), |coinbase| {
+            coinbase.input[0].witness = bitcoin::Witness::from_slice(&[[1u8; 32]]);
+        });
+        generate_inclusion_input(&block, 1).verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "The coinbase witness must be a single 32-byte reserved value.")]
+    fn rejects_a_malformed_witness_reserved_value() {
+        let block = with_coinbase(segwit_block(), |coinbase| {
+            coinbase.input[0].witness = bitcoin::Witness::from_slice(&[[0u8; 32], [0u8; 32]]);
+        });
+        generate_inclusion_input(&block, 1).verify();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The witness merkle root does not match the coinbase witness commitment."
+    )]
+    fn rejects_a_missing_witness_commitment() {
+        let block = with_coinbase(segwit_block(), |coinbase| {
+            let index = coinbase
+                .output
+                .iter()
+                .rposition(|output| output.script_pubkey.as_bytes().starts_with(&[0x6a, 0x24]))
+                .unwrap();
+            coinbase.output.remove(index);
+        });
+        generate_inclusion_input(&block, 1).verify();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The witness merkle root does not match the coinbase witness commitment."
+    )]
+    fn rejects_a_later_altered_commitment() {
+        let block = with_coinbase(segwit_block(), |coinbase| {
+            coinbase.output.push(witness_commitment_output([1u8; 32]));
+        });
+        generate_inclusion_input(&block, 1).verify();
+    }
+
+    #[test]
+    fn transaction_parser_matches_rust_bitcoin() {
+        for block in [testnet_block(), segwit_block()] {
+            for tx in &block.txdata {
+                let bytes = bitcoin::consensus::serialize(tx);
+                let parsed =
+                    zk_light_client_core::bitcoin::transaction::Transaction::parse(&bytes).unwrap();
+                assert_eq!(parsed.version, tx.version.0);
+                assert_eq!(parsed.lock_time, tx.lock_time.to_consensus_u32());
+
+                assert_eq!(parsed.inputs.len(), tx.input.len());
+                for (parsed, input) in parsed.inputs.iter().zip(&tx.input) {
+                    assert_eq!(
+                        parsed.prev_txid,
+                        to_little_endian_bytes(input.previous_output.txid.to_byte_array())
+                    );
+                    assert_eq!(parsed.prev_vout, input.previous_output.vout);
+                    assert_eq!(parsed.script_sig, input.script_sig.as_bytes());
+                    assert_eq!(parsed.sequence, input.sequence.0);
+                    assert_eq!(parsed.witness, input.witness.to_vec());
+                }
+
+                assert_eq!(parsed.outputs.len(), tx.output.len());
+                for (parsed, output) in parsed.outputs.iter().zip(&tx.output) {
+                    assert_eq!(parsed.value, output.value.to_sat());
+                    assert_eq!(parsed.script_pubkey, output.script_pubkey.as_bytes());
+                }
+
+                assert_eq!(
+                    parsed.txid(),
+                    to_little_endian_bytes(tx.compute_txid().to_byte_array())
+                );
+                assert_eq!(
+                    parsed.wtxid(),
+                    to_little_endian_bytes(tx.compute_wtxid().to_byte_array())
+                );
+                assert_eq!(parsed.serialize(), bytes);
+                assert_eq!(parsed.serialize_legacy(), serialize_legacy_tx(tx));
+            }
+        }
+    }
+
+    /// Output of `gettxoutproof` for the transaction at index 1 of a testnet block with 2
+    /// transactions, from the rust-bitcoin documentation.
+    const TX_OUT_PROOF: &str = "01000000ba8b9cda965dd8e536670f9ddec10e53aab14b20bacad27b9137190000\
+        000000190760b278fe7b8565fda3b968b918d5fd997f993b23674c0af3b6fde300b38f33a5914ce6ed5b1b01e3\
+        2f570200000002252bf9d75c4f481ebb6278d708257d1f12beb6dd30301d26c623f789b2ba6fc0e2d32adb5f8c\
+        a820731dff234a84e78ec30bce4ec69dbd562d0b2b8266bf4e5a0105";
+
+    /// Testnet block 924634, with 15 transactions.
+    fn testnet_block() -> bitcoin::Block {
+        bitcoin::consensus::deserialize(include_bytes!(
+            "../test_data/testnet_block_000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b.raw"
+        ))
+        .unwrap()
+    }
+
+    fn hex_hash(hash: &str) -> [u8; 32] {
+        hex::decode(hash).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn parses_tx_out_proof() {
+        let proof = parse_tx_out_proof(&hex::decode(TX_OUT_PROOF).unwrap(), 0).unwrap();
+        assert_eq!(proof.tx_count, 2);
+        assert_eq!(
+            proof.tx_merkle_root,
+            hex_hash("8fb300e3fdb6f30a4c67233b997f99fdd518b968b9a3fd65857bfe78b2600719")
+        );
+        assert_eq!(proof.matched_txs.len(), 1);
+
+        let matched_tx = &proof.matched_txs[0];
+        assert_eq!(
+            matched_tx.tx_id,
+            hex_hash("5a4ebf66822b0b2d56bd9dc64ece0bc38ee7844a23ff1d7320a88c5fdb2ad3e2")
+        );
+        assert_eq!(matched_tx.tx_index, 1);
+        verify_merkle_proof(
+            matched_tx.tx_id,
+            matched_tx.tx_index,
+            proof.tx_count,
+            &matched_tx.tx_merkle_proof,
+            proof.tx_merkle_root,
+        );
+    }
+
+    #[test]
+    fn tx_out_proof_of_several_txs_verifies_against_the_header() {
+        let block = testnet_block();
+        let tx_indices = [0, 5, 6, 13, 14];
+        let tx_ids = tx_indices.map(|index| block.txdata[index].compute_txid());
+        let merkle_block =
+            MerkleBlock::from_block_with_predicate(&block, |txid| tx_ids.contains(txid));
+
+        let proof =
+            parse_tx_out_proof(&bitcoin::consensus::serialize(&merkle_block), 924634).unwrap();
+        assert_eq!(
+            bitcoin::block::Header::try_from(&proof.block).unwrap(),
+            block.header
+        );
+        assert_eq!(
+            proof.tx_merkle_root,
+            to_little_endian_bytes(block.header.merkle_root.to_byte_array())
+        );
+        assert_eq!(proof.tx_count, 15);
+        assert_eq!(proof.matched_txs.len(), tx_indices.len());
+        for (matched_tx, (index, tx_id)) in
+            proof.matched_txs.iter().zip(tx_indices.iter().zip(tx_ids))
+        {
+            assert_eq!(matched_tx.tx_index, *index as u32);
+            assert_eq!(
+                matched_tx.tx_id,
+                to_little_endian_bytes(tx_id.to_byte_array())
+            );
+            verify_merkle_proof(
+                matched_tx.tx_id,
+                matched_tx.tx_index,
+                proof.tx_count,
+                &matched_tx.tx_merkle_proof,
+                proof.tx_merkle_root,
+            );
+        }
+
+        proof.inclusion_input(&block.txdata[5]).unwrap().verify();
+        assert!(proof.inclusion_input(&block.txdata[1]).is_none());
+    }
+
+    /// Replaces the flag bytes of [`TX_OUT_PROOF`], `01 05`, with `flags`.
+    fn tx_out_proof_with_flags(flags: &str) -> Vec<u8> {
+        let proof = TX_OUT_PROOF.strip_suffix("0105").unwrap();
+        hex::decode(format!("{proof}{flags}")).unwrap()
+    }
+
+    #[test]
+    fn rejects_malformed_flag_bits() {
+        for (flags, expected) in [
+            ("00", MerkleBlockError::NotEnoughBits),
+            ("020500", MerkleBlockError::NotAllBitsConsumed),
+        ] {
+            let error = parse_tx_out_proof(&tx_out_proof_with_flags(flags), 0).unwrap_err();
+            assert!(
+                matches!(&error, TxOutProofError::MerkleBlock(error) if *error == expected),
+                "{flags}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_leftover_hashes() {
+        // Only the root is read, the two hashes can not both be consumed.
+        let error = parse_tx_out_proof(&tx_out_proof_with_flags("0100"), 0).unwrap_err();
+        assert!(matches!(
+            error,
+            TxOutProofError::MerkleBlock(MerkleBlockError::NotAllHashesConsumed)
+        ));
+    }
+
+    #[test]
+    fn multiproofs_verify_for_every_subset_of_leaves() {
+        use zk_light_client_core::bitcoin::get_merkle_root;
+        use zk_light_client_core::bitcoin::inclusion::compute_merkle_multiproof_root;
+
+        let leaves = (0u32..11)
+            .map(|i| sha256_hash(&i.to_le_bytes()))
+            .collect::<Vec<_>>();
+        for leaf_count in 1..=leaves.len() {
+            let leaves = &leaves[..leaf_count];
+            let root = get_merkle_root(leaves.to_vec());
+            for subset in 1u32..1 << leaf_count {
+                let indices = (0..leaf_count as u32)
+                    .filter(|index| subset & (1 << index) != 0)
+                    .collect::<Vec<_>>();
+                let (multiproof, multiproof_root) =
+                    generate_merkle_multiproof(leaves.to_vec(), &indices);
+                assert_eq!(multiproof_root, root);
+
+                let tx_ids = indices
+                    .iter()
+                    .map(|&index| leaves[index as usize])
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    compute_merkle_multiproof_root(&tx_ids, leaf_count as u32, &multiproof),
+                    root,
+                    "{indices:?} of {leaf_count}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn batch_inclusion_of_several_txs_in_a_real_block() {
+        let block = testnet_block();
+        // Unsorted with a duplicate, and including the last transaction of the odd-width tree.
+        let input = generate_batch_inclusion_input(&[(&block, &[14, 2, 7, 1, 7])]);
+        input.verify();
+        assert_eq!(
+            input.witness.blocks[0].merkle_multiproof.tx_indices,
+            [1, 2, 7, 14]
+        );
+        assert_eq!(input.public_input.tx_id_count, 4);
+        assert_eq!(
+            input.public_input.blocks[0].tx_merkle_root,
+            to_little_endian_bytes(block.header.merkle_root.to_byte_array())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Mutated merkle tree: identical sibling nodes")]
+    fn multiproof_generation_rejects_a_duplicated_last_leaf() {
+        let leaves = (0u32..3)
+            .map(|i| sha256_hash(&i.to_le_bytes()))
+            .collect::<Vec<_>>();
+        let mut mutated = leaves.clone();
+        mutated.push(leaves[2]);
+        generate_merkle_multiproof(mutated, &[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "MMR leaf index out of bounds")]
+    fn mmr_proof_out_of_bounds() {
+        generate_mmr_proof(&[[0u8; 32]], 1);
+    }
+
+    /// Executes the schnorr program on the BIP340 test vectors of core, to check the patched `k256`
+    /// gives the same results as the host.
+    #[test]
+    fn schnorr_signatures_verify_in_the_zkvm() {
+        use btc_schnorr_program_script::BTC_SCHNORR_PROGRAM_ELF;
+        use sp1_sdk::{ProverClient, SP1Stdin};
+        use zk_light_client_core::bitcoin::schnorr::test_vectors::BIP340_VECTORS;
+
+        let signatures = BIP340_VECTORS
+            .iter()
+            .map(|vector| {
+                (
+                    <[u8; 32]>::try_from(hex::decode(vector.public_key).unwrap()).unwrap(),
+                    hex::decode(vector.message).unwrap(),
+                    hex::decode(vector.signature).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&signatures);
+        let client = ProverClient::builder().cpu().build();
+        let (mut public_values, _) = client
+            .execute(BTC_SCHNORR_PROGRAM_ELF, &stdin)
+            .run()
+            .unwrap();
+        let valid = public_values.read::<Vec<bool>>();
+        assert_eq!(valid.len(), BIP340_VECTORS.len());
+
+        for (vector, valid) in BIP340_VECTORS.iter().zip(valid) {
+            assert_eq!(valid, vector.result.is_ok(), "vector {}", vector.index);
+        }
+    }
+}
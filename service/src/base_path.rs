@@ -58,4 +58,41 @@ impl BasePath {
         });
         path
     }
+
+    /// Returns the directory for storing consensus proof of Bitcoin blocks.
+    ///
+    /// The path looks like `$base_path/proofs/bitcoin/$network/block`.
+    pub fn btc_consensus_proof_path(&self, network: bitcoin::Network) -> PathBuf {
+        let path = self
+            .path
+            .join("proofs")
+            .join("bitcoin")
+            .join(network.to_string())
+            .join("block");
+        std::fs::create_dir_all(&path).unwrap_or_else(|e| {
+            panic!(
+                "Failed to create directory for BTC consensus proofs at {}: {e}",
+                path.display(),
+            )
+        });
+        path
+    }
+
+    /// Returns the directory for storing the headers of the Bitcoin best chain.
+    ///
+    /// The path looks like `$base_path/headers/bitcoin/$network`.
+    pub fn btc_header_store_path(&self, network: bitcoin::Network) -> PathBuf {
+        let path = self
+            .path
+            .join("headers")
+            .join("bitcoin")
+            .join(network.to_string());
+        std::fs::create_dir_all(&path).unwrap_or_else(|e| {
+            panic!(
+                "Failed to create directory for BTC headers at {}: {e}",
+                path.display(),
+            )
+        });
+        path
+    }
 }
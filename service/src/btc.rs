@@ -0,0 +1,34 @@
+//! Bitcoin chain following, backed by the RPC of a bitcoind node.
+
+mod events;
+mod header_store;
+mod rpc;
+
+pub use self::events::{BlockEvent, spawn_block_event_stream};
+pub use self::header_store::{HeaderStore, proposed_chain_depth, retarget_block_height};
+pub use self::rpc::BitcoinRpc;
+#[cfg(test)]
+pub use self::rpc::MockBitcoinRpc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BtcError {
+    #[error("Header at height {0} not found in the header store")]
+    HeaderNotFound(u64),
+    #[error("Block height {0} too low: not enough preceding blocks for the consensus witness")]
+    BlockHeightTooLow(u64),
+    #[error("Header at height {height} does not extend the best chain")]
+    Disconnected { height: u64 },
+    #[error("Header at height {height} has invalid proof of work")]
+    InvalidProofOfWork { height: u64 },
+    #[error("Reorg below the first stored header at height {0}")]
+    ReorgTooDeep(u64),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(#[from] zk_light_client_core::bitcoin::block::HeaderConversionError),
+    #[error(transparent)]
+    Rpc(#[from] bitcoincore_rpc::Error),
+    #[cfg(test)]
+    #[error("Mock RPC: {0}")]
+    MockRpc(String),
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+}
@@ -0,0 +1,115 @@
+use super::BitcoinRpc;
+use bitcoin::BlockHash;
+use bitcoin::hashes::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+use zeromq::{Socket, SocketRecv, SubSocket};
+
+/// Notification of a change of the bitcoind best chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockEvent {
+    /// A new block was connected to the best chain.
+    NewTip(BlockHash),
+}
+
+/// Spawns the stream of block events.
+///
+/// Events come from the `zmqpubhashblock` notifications of bitcoind if `zmq_endpoint` is set, and
+/// from polling the RPC at `poll_interval` otherwise or if the ZMQ subscription fails.
+///
+/// ZMQ notifications may be dropped, consumers must treat an event as a hint to sync up to the
+/// current tip rather than as the only new block.
+pub fn spawn_block_event_stream(
+    zmq_endpoint: Option<String>,
+    rpc: Arc<dyn BitcoinRpc>,
+    poll_interval: Duration,
+) -> async_channel::Receiver<BlockEvent> {
+    let (sender, receiver) = async_channel::unbounded();
+
+    tokio::spawn(async move {
+        if let Some(endpoint) = zmq_endpoint {
+            match subscribe_hashblock(&endpoint, &sender).await {
+                Ok(()) => return,
+                Err(err) => {
+                    tracing::error!(
+                        ?err,
+                        endpoint,
+                        "ZMQ block notifications failed, falling back to RPC polling"
+                    );
+                }
+            }
+        }
+        poll_best_block(rpc, poll_interval, &sender).await;
+    });
+
+    receiver
+}
+
+/// Forwards the `hashblock` notifications published at `endpoint`.
+///
+/// Returns `Ok(())` once the receiver is dropped.
+async fn subscribe_hashblock(
+    endpoint: &str,
+    sender: &async_channel::Sender<BlockEvent>,
+) -> Result<(), zeromq::ZmqError> {
+    let mut socket = SubSocket::new();
+    socket.connect(endpoint).await?;
+    socket.subscribe("hashblock").await?;
+
+    tracing::info!(endpoint, "Subscribed to bitcoind ZMQ block notifications");
+
+    loop {
+        let message = socket.recv().await?;
+
+        // Multipart message: topic, block hash in display order, sequence number.
+        let Some(block_hash) = message
+            .get(1)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes.as_ref()).ok())
+        else {
+            tracing::warn!(?message, "Ignoring malformed ZMQ hashblock message");
+            continue;
+        };
+
+        let mut block_hash = block_hash;
+        block_hash.reverse();
+        let block_hash = BlockHash::from_byte_array(block_hash);
+
+        tracing::debug!(%block_hash, "Received ZMQ hashblock notification");
+
+        if sender.send(BlockEvent::NewTip(block_hash)).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Polls the best block hash of bitcoind and emits an event whenever it changes.
+async fn poll_best_block(
+    rpc: Arc<dyn BitcoinRpc>,
+    poll_interval: Duration,
+    sender: &async_channel::Sender<BlockEvent>,
+) {
+    tracing::info!(?poll_interval, "Polling bitcoind for new blocks");
+
+    let mut best_block_hash = None;
+
+    loop {
+        let rpc = rpc.clone();
+        let result =
+            tokio::task::spawn_blocking(move || rpc.get_block_hash(rpc.get_block_count()?))
+                .await
+                .expect("Polling bitcoind must not panic");
+
+        match result {
+            Ok(block_hash) if best_block_hash != Some(block_hash) => {
+                best_block_hash = Some(block_hash);
+                if sender.send(BlockEvent::NewTip(block_hash)).await.is_err() {
+                    return;
+                }
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!(?err, "Failed to poll the best block"),
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
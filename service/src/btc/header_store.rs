@@ -0,0 +1,409 @@
+use super::{BitcoinRpc, BtcError};
+use bitcoin::block::Header;
+use std::path::{Path, PathBuf};
+use zk_light_client_core::bitcoin::block::CircuitBlock;
+use zk_light_client_core::bitcoin::consensus::{ConsensusBlockPublicInput, ConsensusWitness};
+use zk_light_client_core::bitcoin::{EPOCH_BLOCK_NUMBER, MIN_TRUSTED_BLOCK_NUMBER};
+use zk_light_client_core::mmr::MerkleMountainRange;
+use zk_light_client_programs::generate_consensus_block_public_input;
+
+/// Returns the height of the retarget block required to prove the block at `block_height`.
+///
+/// This is the first block of the current epoch, or of the previous epoch if `block_height`
+/// starts a new epoch.
+pub fn retarget_block_height(block_height: u64) -> u64 {
+    let epoch = EPOCH_BLOCK_NUMBER as u64;
+    if block_height % epoch == 0 {
+        block_height.saturating_sub(epoch)
+    } else {
+        block_height - block_height % epoch
+    }
+}
+
+/// Returns the number of headers preceding a proposed block in its [`ConsensusWitness`].
+///
+/// The witness covers the median time past window and the m-deep block.
+pub fn proposed_chain_depth(confirmation_depth: u32) -> u64 {
+    (MIN_TRUSTED_BLOCK_NUMBER as u64).max(confirmation_depth as u64)
+}
+
+/// Result of a [`HeaderStore::sync`] round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncOutcome {
+    /// Height of the best chain tip after syncing.
+    pub tip_height: u64,
+    /// Height of the first header replaced by a reorg, if any.
+    pub reorg_height: Option<u64>,
+}
+
+/// Persistent store of the Bitcoin headers of the best chain, starting from `base_height`.
+///
+/// Each header is stored in its consensus encoding at `$path/$height.header`.
+#[derive(Debug)]
+pub struct HeaderStore {
+    path: PathBuf,
+    base_height: u64,
+    /// Headers of the best chain, `headers[i]` is at height `base_height + i`.
+    headers: Vec<Header>,
+}
+
+impl HeaderStore {
+    /// Opens the store at `path`, loading the headers persisted from `base_height` onwards.
+    pub fn open(path: PathBuf, base_height: u64) -> Result<Self, BtcError> {
+        let mut headers = Vec::new();
+        loop {
+            let file_path = header_file_path(&path, base_height + headers.len() as u64);
+            if !std::fs::exists(&file_path)? {
+                break;
+            }
+            let bytes = std::fs::read(&file_path)?;
+            let header = bitcoin::consensus::deserialize(&bytes).map_err(|err| {
+                std::io::Error::other(format!(
+                    "Corrupted header file {}: {err}",
+                    file_path.display()
+                ))
+            })?;
+            headers.push(header);
+        }
+
+        tracing::debug!(
+            base_height,
+            count = headers.len(),
+            "Loaded Bitcoin headers from {}",
+            path.display()
+        );
+
+        Ok(Self {
+            path,
+            base_height,
+            headers,
+        })
+    }
+
+    /// Returns the height of the best chain tip, `None` if the store is empty.
+    pub fn tip_height(&self) -> Option<u64> {
+        (!self.headers.is_empty()).then(|| self.base_height + self.headers.len() as u64 - 1)
+    }
+
+    /// Returns the header at `height` in the best chain.
+    pub fn header(&self, height: u64) -> Result<&Header, BtcError> {
+        height
+            .checked_sub(self.base_height)
+            .and_then(|index| self.headers.get(index as usize))
+            .ok_or(BtcError::HeaderNotFound(height))
+    }
+
+    /// Returns the header at `height` in the circuit representation.
+    pub fn circuit_block(&self, height: u64) -> Result<CircuitBlock, BtcError> {
+        Ok(CircuitBlock::try_from((self.header(height)?, height))?)
+    }
+
+    /// Downloads the headers of the best chain of the node up to its tip.
+    ///
+    /// If the node switched to another chain, the headers above the fork point are replaced.
+    pub fn sync(&mut self, rpc: &dyn BitcoinRpc) -> Result<SyncOutcome, BtcError> {
+        let node_tip_height = rpc.get_block_count()?;
+
+        // Walk back to the last header shared with the node's best chain.
+        let mut reorg_height = None;
+        if let Some(tip_height) = self.tip_height() {
+            let mut height = tip_height.min(node_tip_height);
+            loop {
+                if rpc.get_block_hash(height)? == self.header(height)?.block_hash() {
+                    break;
+                }
+                if height == self.base_height {
+                    return Err(BtcError::ReorgTooDeep(self.base_height));
+                }
+                height -= 1;
+            }
+            if height < tip_height {
+                tracing::warn!(
+                    fork_height = height,
+                    old_tip_height = tip_height,
+                    "Bitcoin chain reorganization detected"
+                );
+                self.truncate(height + 1)?;
+                reorg_height = Some(height + 1);
+            }
+        }
+
+        let start_height = self.tip_height().map_or(self.base_height, |tip| tip + 1);
+        for height in start_height..=node_tip_height {
+            let block_hash = rpc.get_block_hash(height)?;
+            let header = rpc.get_block_header(&block_hash)?;
+            self.push(height, header)?;
+        }
+
+        if start_height <= node_tip_height {
+            tracing::info!(
+                from = start_height,
+                to = node_tip_height,
+                "Synced Bitcoin headers"
+            );
+        }
+
+        Ok(SyncOutcome {
+            tip_height: node_tip_height,
+            reorg_height,
+        })
+    }
+
+    /// Appends the header at `height` to the best chain after checking it extends the tip.
+    fn push(&mut self, height: u64, header: Header) -> Result<(), BtcError> {
+        if let Some(tip) = self.headers.last() {
+            if header.prev_blockhash != tip.block_hash() {
+                return Err(BtcError::Disconnected { height });
+            }
+        }
+        if header.validate_pow(header.target()).is_err() {
+            return Err(BtcError::InvalidProofOfWork { height });
+        }
+        std::fs::write(
+            header_file_path(&self.path, height),
+            bitcoin::consensus::serialize(&header),
+        )?;
+        self.headers.push(header);
+        Ok(())
+    }
+
+    /// Removes the headers from `height` onwards.
+    fn truncate(&mut self, height: u64) -> Result<(), BtcError> {
+        let tip_height = self.tip_height().unwrap_or(height);
+        for h in (height..=tip_height).rev() {
+            std::fs::remove_file(header_file_path(&self.path, h))?;
+        }
+        self.headers
+            .truncate(height.saturating_sub(self.base_height) as usize);
+        Ok(())
+    }
+
+    /// Returns the witness for proving the block at `block_height` with `confirmation_depth`, on
+    /// top of the header MMR committed by the parent proof.
+    pub fn consensus_witness(
+        &self,
+        block_height: u64,
+        confirmation_depth: u32,
+        header_mmr: MerkleMountainRange,
+    ) -> Result<ConsensusWitness, BtcError> {
+        if block_height <= MIN_TRUSTED_BLOCK_NUMBER as u64 {
+            return Err(BtcError::BlockHeightTooLow(block_height));
+        }
+        let first_height = block_height
+            .checked_sub(proposed_chain_depth(confirmation_depth))
+            .ok_or(BtcError::BlockHeightTooLow(block_height))?;
+
+        let proposed_chain = (first_height..=block_height)
+            .map(|height| self.circuit_block(height))
+            .collect::<Result<Vec<_>, _>>()?;
+        let retarget_block = self.circuit_block(retarget_block_height(block_height))?;
+
+        Ok(ConsensusWitness::new(
+            proposed_chain,
+            retarget_block,
+            header_mmr,
+        ))
+    }
+
+    /// Returns the public input of the block at `block_height`, matching [`Self::consensus_witness`].
+    pub fn block_public_input(
+        &self,
+        block_height: u64,
+        confirmation_depth: u32,
+    ) -> Result<ConsensusBlockPublicInput, BtcError> {
+        let witness = self.consensus_witness(
+            block_height,
+            confirmation_depth,
+            MerkleMountainRange::default(),
+        )?;
+        Ok(generate_consensus_block_public_input(
+            &witness.proposed_chain,
+            &witness.retarget_block,
+            confirmation_depth,
+        ))
+    }
+}
+
+#[inline]
+fn header_file_path(path: &Path, height: u64) -> PathBuf {
+    path.join(format!("{height}.header"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btc::MockBitcoinRpc;
+    use zk_light_client_core::bitcoin::block::validate_block;
+
+    const CONFIRMATION_DEPTH: u32 = 6;
+
+    fn assert_best_chain(header_store: &HeaderStore, rpc: &MockBitcoinRpc) {
+        let tip_height = rpc.get_block_count().unwrap();
+        assert_eq!(header_store.tip_height(), Some(tip_height));
+        for height in header_store.base_height..=tip_height {
+            assert_eq!(
+                header_store.header(height).unwrap().block_hash(),
+                rpc.get_block_hash(height).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn sync_follows_the_best_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(30);
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 5).unwrap();
+        assert_eq!(header_store.tip_height(), None);
+
+        let outcome = header_store.sync(&rpc).unwrap();
+        assert_eq!(
+            outcome,
+            SyncOutcome {
+                tip_height: 30,
+                reorg_height: None
+            }
+        );
+        assert_best_chain(&header_store, &rpc);
+        assert!(matches!(
+            header_store.header(4),
+            Err(BtcError::HeaderNotFound(4))
+        ));
+
+        rpc.mine(3);
+        let outcome = header_store.sync(&rpc).unwrap();
+        assert_eq!(outcome.tip_height, 33);
+        assert_eq!(outcome.reorg_height, None);
+        assert_best_chain(&header_store, &rpc);
+    }
+
+    #[test]
+    fn headers_persist_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(20);
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+        drop(header_store);
+
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 0).unwrap();
+        assert_best_chain(&header_store, &rpc);
+
+        // Nothing to download after reopening.
+        let outcome = header_store.sync(&rpc).unwrap();
+        assert_eq!(outcome.reorg_height, None);
+        assert_best_chain(&header_store, &rpc);
+    }
+
+    #[test]
+    fn reorg_switches_to_the_new_best_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(20);
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+        let orphaned_hash = header_store.header(18).unwrap().block_hash();
+
+        rpc.reorg(3);
+        rpc.mine(1);
+        let outcome = header_store.sync(&rpc).unwrap();
+        assert_eq!(
+            outcome,
+            SyncOutcome {
+                tip_height: 21,
+                reorg_height: Some(18)
+            }
+        );
+        assert_best_chain(&header_store, &rpc);
+        assert_ne!(header_store.header(18).unwrap().block_hash(), orphaned_hash);
+
+        // The replaced headers are persisted too.
+        drop(header_store);
+        let header_store = HeaderStore::open(dir.path().to_path_buf(), 0).unwrap();
+        assert_best_chain(&header_store, &rpc);
+    }
+
+    #[test]
+    fn reorg_below_the_base_height_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(20);
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 15).unwrap();
+        header_store.sync(&rpc).unwrap();
+
+        rpc.reorg(10);
+        assert!(matches!(
+            header_store.sync(&rpc),
+            Err(BtcError::ReorgTooDeep(15))
+        ));
+    }
+
+    #[test]
+    fn consensus_witness_at_retarget_boundaries() {
+        let epoch = EPOCH_BLOCK_NUMBER as u64;
+        let dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(epoch + 2);
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+
+        for (block_height, retarget_height) in [
+            (epoch - 1, 0),
+            (epoch, 0),
+            (epoch + 1, epoch),
+            (epoch + 2, epoch),
+        ] {
+            assert_eq!(retarget_block_height(block_height), retarget_height);
+
+            let witness = header_store
+                .consensus_witness(
+                    block_height,
+                    CONFIRMATION_DEPTH,
+                    MerkleMountainRange::default(),
+                )
+                .unwrap();
+            let depth = proposed_chain_depth(CONFIRMATION_DEPTH);
+            assert_eq!(witness.proposed_chain.len() as u64, depth + 1);
+            for (block, height) in witness.proposed_chain.iter().zip(block_height - depth..) {
+                assert_eq!(block.height, height);
+            }
+            assert_eq!(witness.retarget_block.height, retarget_height);
+            assert_eq!(
+                witness.retarget_block.compute_block_hash(),
+                header_store
+                    .circuit_block(retarget_height)
+                    .unwrap()
+                    .compute_block_hash()
+            );
+
+            // Regtest does not retarget, the new epoch block can not pass the retarget check.
+            if block_height % epoch != 0 {
+                let public_input = header_store
+                    .block_public_input(block_height, CONFIRMATION_DEPTH)
+                    .unwrap();
+                validate_block(
+                    witness.proposed_chain,
+                    witness.retarget_block,
+                    public_input,
+                    CONFIRMATION_DEPTH,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn consensus_witness_requires_preceding_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(20);
+        let mut header_store = HeaderStore::open(dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+
+        assert!(matches!(
+            header_store.consensus_witness(
+                MIN_TRUSTED_BLOCK_NUMBER as u64,
+                CONFIRMATION_DEPTH,
+                MerkleMountainRange::default()
+            ),
+            Err(BtcError::BlockHeightTooLow(_))
+        ));
+        assert!(matches!(
+            header_store.consensus_witness(21, CONFIRMATION_DEPTH, MerkleMountainRange::default()),
+            Err(BtcError::HeaderNotFound(21))
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+use super::BtcError;
+use bitcoin::block::Header;
+use bitcoin::{Block, BlockHash};
+use bitcoincore_rpc::RpcApi;
+
+/// Subset of the bitcoind RPC needed to follow the best chain.
+pub trait BitcoinRpc: Send + Sync {
+    /// Returns the height of the best chain tip.
+    fn get_block_count(&self) -> Result<u64, BtcError>;
+
+    /// Returns the hash of the block at `height` in the best chain.
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, BtcError>;
+
+    /// Returns the header of the block with `block_hash`.
+    fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, BtcError>;
+
+    /// Returns the block with `block_hash`, including its transactions.
+    fn get_block(&self, block_hash: &BlockHash) -> Result<Block, BtcError>;
+}
+
+impl BitcoinRpc for bitcoincore_rpc::Client {
+    fn get_block_count(&self) -> Result<u64, BtcError> {
+        Ok(RpcApi::get_block_count(self)?)
+    }
+
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, BtcError> {
+        Ok(RpcApi::get_block_hash(self, height)?)
+    }
+
+    fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, BtcError> {
+        Ok(RpcApi::get_block_header(self, block_hash)?)
+    }
+
+    fn get_block(&self, block_hash: &BlockHash) -> Result<Block, BtcError> {
+        Ok(RpcApi::get_block(self, block_hash)?)
+    }
+}
+
+#[cfg(test)]
+pub use self::mock::MockBitcoinRpc;
+
+#[cfg(test)]
+mod mock {
+    use super::{BitcoinRpc, BtcError};
+    use bitcoin::block::Header;
+    use bitcoin::hashes::Hash;
+    use bitcoin::script::Builder;
+    use bitcoin::{
+        Amount, Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn,
+        TxMerkleNode, TxOut, Witness,
+    };
+    use parking_lot::RwLock;
+
+    /// Target bits of regtest, any header meets it within a few nonces.
+    const REGTEST_BITS: u32 = 0x207fffff;
+
+    /// Prefix of the coinbase output committing to the witness merkle root (BIP141).
+    const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+    /// In-memory chain of regtest blocks implementing [`BitcoinRpc`], for testing offline.
+    #[derive(Debug)]
+    pub struct MockBitcoinRpc {
+        /// Blocks of the best chain, starting from the genesis block.
+        chain: RwLock<Vec<Block>>,
+    }
+
+    impl MockBitcoinRpc {
+        /// Creates a chain of `block_count` blocks on top of a genesis block.
+        pub fn new(block_count: u64) -> Self {
+            let genesis = mine_block(BlockHash::all_zeros(), 0, 1_700_000_000);
+            let mock = Self {
+                chain: RwLock::new(vec![genesis]),
+            };
+            mock.mine(block_count);
+            mock
+        }
+
+        /// Extends the best chain with `count` new blocks.
+        pub fn mine(&self, count: u64) {
+            let mut chain = self.chain.write();
+            for _ in 0..count {
+                let tip = &chain.last().expect("Genesis is always present; qed").header;
+                let height = chain.len() as u32;
+                let block = mine_block(tip.block_hash(), height, tip.time + 600);
+                chain.push(block);
+            }
+        }
+
+        /// Replaces the last `depth` blocks of the best chain with as many new blocks.
+        ///
+        /// The new blocks have different timestamps, hence different hashes.
+        pub fn reorg(&self, depth: u64) {
+            let mut chain = self.chain.write();
+            let fork_height = chain.len().saturating_sub(depth as usize).max(1);
+            let replaced = chain.len() - fork_height;
+            chain.truncate(fork_height);
+            for _ in 0..replaced {
+                let tip = &chain.last().expect("Genesis is always present; qed").header;
+                let height = chain.len() as u32;
+                let block = mine_block(tip.block_hash(), height, tip.time + 601);
+                chain.push(block);
+            }
+        }
+    }
+
+    /// Mines a regtest block holding only a coinbase transaction, which commits to the height
+    /// (BIP34) and to the witness merkle root (BIP141) without a signet solution.
+    ///
+    /// The block satisfies the `OP_TRUE` signet challenge.
+    fn mine_block(prev_blockhash: BlockHash, height: u32, time: u32) -> Block {
+        let coinbase = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new()
+                    .push_int(height as i64)
+                    .push_slice(b"mock")
+                    .into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[[0u8; 32]]),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(5_000_000_000),
+                script_pubkey: ScriptBuf::new_op_return([1u8; 20]),
+            }],
+        };
+        let mut block = Block {
+            header: Header {
+                version: bitcoin::block::Version::TWO,
+                prev_blockhash,
+                merkle_root: TxMerkleNode::all_zeros(),
+                time,
+                bits: CompactTarget::from_consensus(REGTEST_BITS),
+                nonce: 0,
+            },
+            txdata: vec![coinbase],
+        };
+
+        let witness_root = block.witness_root().expect("The block has a coinbase; qed");
+        let commitment = Block::compute_witness_commitment(&witness_root, &[0u8; 32]);
+        let mut script_pubkey = WITNESS_COMMITMENT_HEADER.to_vec();
+        script_pubkey.extend_from_slice(&commitment.to_byte_array());
+        block.txdata[0].output.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::from_bytes(script_pubkey),
+        });
+
+        block.header.merkle_root = block
+            .compute_merkle_root()
+            .expect("The block has a coinbase; qed");
+        while block.header.validate_pow(block.header.target()).is_err() {
+            block.header.nonce += 1;
+        }
+        block
+    }
+
+    impl BitcoinRpc for MockBitcoinRpc {
+        fn get_block_count(&self) -> Result<u64, BtcError> {
+            Ok(self.chain.read().len() as u64 - 1)
+        }
+
+        fn get_block_hash(&self, height: u64) -> Result<BlockHash, BtcError> {
+            self.chain
+                .read()
+                .get(height as usize)
+                .map(Block::block_hash)
+                .ok_or_else(|| BtcError::MockRpc(format!("Block height {height} out of range")))
+        }
+
+        fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, BtcError> {
+            self.get_block(block_hash).map(|block| block.header)
+        }
+
+        fn get_block(&self, block_hash: &BlockHash) -> Result<Block, BtcError> {
+            self.chain
+                .read()
+                .iter()
+                .find(|block| block.block_hash() == *block_hash)
+                .cloned()
+                .ok_or_else(|| BtcError::MockRpc(format!("Block {block_hash} not found")))
+        }
+    }
+}
@@ -1 +1,8 @@
 pub mod bench;
+pub mod prove_btc_delegation;
+pub mod prove_finality;
+pub mod prove_ibc_packets;
+pub mod prove_misbehaviour;
+pub mod prove_tx_inclusion;
+pub mod run;
+pub mod sync_headers;
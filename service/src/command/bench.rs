@@ -3,6 +3,7 @@ use crate::provers::{BabyConsensusProver, BabyMembershipProver};
 use clap::Parser;
 use std::sync::Arc;
 use tendermint_rpc::{Client, HttpClient};
+use zk_light_client_core::babylon::keys::epoching;
 
 #[derive(Debug)]
 struct ProofInfo {
@@ -108,11 +109,11 @@ impl BabyProvingBench {
         let membership_prover = BabyMembershipProver::new(client, consensus_proof_path);
 
         // TODO: Support specifying the key and the height from CLI.
-        let mut storage_key = vec![0x11];
-        storage_key.extend(1u64.to_be_bytes());
-        let key_path = vec![b"epoching".to_vec(), storage_key];
+        let key_path = epoching::epoch_key_path(1);
 
-        let membership_proof = membership_prover.prove(vec![key_path], end_height).await?;
+        let membership_proof = membership_prover
+            .prove(vec![key_path], Vec::new(), end_height, end_height)
+            .await?;
 
         println!("Proving time: {}s", membership_proof.proving_time_secs);
 
@@ -0,0 +1,63 @@
+use crate::Args;
+use crate::provers::BabyBtcDelegationProver;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tendermint_rpc::{Client, HttpClient};
+use zk_light_client_core::babylon::btcstaking::BtcDelegationOutput;
+
+#[derive(Parser, Debug)]
+pub struct ProveBtcDelegationCmd {
+    /// The Babylon RPC URL to fetch the delegation and its membership proof from.
+    #[clap(long, default_value = "https://babylon-archive-rpc.polkachu.com")]
+    pub rpc_url: String,
+
+    /// Hash of the staking transaction of the delegation.
+    #[clap(long)]
+    pub staking_tx_hash: bitcoin::Txid,
+
+    /// The Babylon block height whose `app_hash` the delegation is proven against.
+    #[clap(long)]
+    pub block_height: u64,
+
+    /// Height of the consensus proof covering the block, defaults to `block_height`.
+    #[clap(long)]
+    pub consensus_height: Option<u64>,
+
+    /// File to save the proof to.
+    #[clap(long)]
+    pub output: PathBuf,
+}
+
+impl ProveBtcDelegationCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        let client = Arc::new(HttpClient::new(self.rpc_url.as_str())?);
+        let chain_id = client.genesis::<serde_json::Value>().await?.chain_id;
+        let consensus_proof_path = args.base_path().baby_consensus_proof_path(chain_id);
+
+        let prover = BabyBtcDelegationProver::new(client, consensus_proof_path);
+        let mut proof = prover
+            .prove(
+                self.staking_tx_hash,
+                self.block_height,
+                self.consensus_height.unwrap_or(self.block_height),
+            )
+            .await?;
+
+        let output = proof.public_values.read::<BtcDelegationOutput>();
+        tracing::info!(
+            status = ?output.status,
+            btc_tip_height = output.btc_tip_height,
+            covenant_quorum = output.covenant_quorum,
+            total_sat = output.total_sat,
+            finality_providers = output.fp_btc_pk_list.len(),
+            "Proved BTC delegation {}",
+            self.staking_tx_hash
+        );
+
+        proof.save(&self.output)?;
+        println!("Saved proof to {}", self.output.display());
+
+        Ok(())
+    }
+}
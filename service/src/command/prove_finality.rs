@@ -0,0 +1,64 @@
+use crate::Args;
+use crate::provers::BabyFinalityProver;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tendermint_rpc::{Client, HttpClient};
+use zk_light_client_core::babylon::finality::FinalityOutput;
+
+#[derive(Parser, Debug)]
+pub struct ProveFinalityCmd {
+    /// The Babylon RPC URL to fetch the votes and their membership proofs from.
+    #[clap(long, default_value = "https://babylon-archive-rpc.polkachu.com")]
+    pub rpc_url: String,
+
+    /// Height of the Babylon block to prove the finality of.
+    #[clap(long)]
+    pub height: u64,
+
+    /// The Babylon block height whose `app_hash` the votes are proven against.
+    #[clap(long)]
+    pub membership_height: u64,
+
+    /// Height of the consensus proof covering both blocks, defaults to `membership_height`.
+    #[clap(long)]
+    pub consensus_height: Option<u64>,
+
+    /// File to save the proof to.
+    #[clap(long)]
+    pub output: PathBuf,
+}
+
+impl ProveFinalityCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        if self.membership_height <= self.height {
+            anyhow::bail!("The votes can only be proven at a later height");
+        }
+
+        let client = Arc::new(HttpClient::new(self.rpc_url.as_str())?);
+        let chain_id = client.genesis::<serde_json::Value>().await?.chain_id;
+        let consensus_proof_path = args.base_path().baby_consensus_proof_path(chain_id);
+
+        let prover = BabyFinalityProver::new(client, consensus_proof_path);
+        let mut proof = prover
+            .prove(
+                self.height,
+                self.membership_height,
+                self.consensus_height.unwrap_or(self.membership_height),
+            )
+            .await?;
+
+        let output = proof.public_values.read::<FinalityOutput>();
+        tracing::info!(
+            voted_power = output.voted_power,
+            total_power = output.total_power,
+            "Proved finality of block #{}",
+            output.height
+        );
+
+        proof.save(&self.output)?;
+        println!("Saved proof to {}", self.output.display());
+
+        Ok(())
+    }
+}
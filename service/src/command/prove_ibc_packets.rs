@@ -0,0 +1,107 @@
+use crate::Args;
+use crate::provers::BabyIbcPacketProver;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tendermint_rpc::{Client, HttpClient};
+use zk_light_client_core::babylon::ibc::{IbcPacketOutput, PacketId, PacketKind};
+
+/// Kind of packet entry to prove.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PacketKindArg {
+    Commitment,
+    Receipt,
+    Ack,
+}
+
+impl From<PacketKindArg> for PacketKind {
+    fn from(kind: PacketKindArg) -> Self {
+        match kind {
+            PacketKindArg::Commitment => Self::Commitment,
+            PacketKindArg::Receipt => Self::Receipt,
+            PacketKindArg::Ack => Self::Acknowledgement,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct ProveIbcPacketsCmd {
+    /// The Babylon RPC URL to fetch the packet entries and their membership proofs from.
+    #[clap(long, default_value = "https://babylon-archive-rpc.polkachu.com")]
+    pub rpc_url: String,
+
+    /// Kind of the packet entries.
+    #[clap(long, value_enum)]
+    pub kind: PacketKindArg,
+
+    /// Port of the channel end on Babylon.
+    #[clap(long, default_value = "transfer")]
+    pub port_id: String,
+
+    /// Channel end on Babylon.
+    #[clap(long)]
+    pub channel_id: String,
+
+    /// Sequences of the packets.
+    #[clap(long, num_args = 1.., required = true)]
+    pub sequences: Vec<u64>,
+
+    /// The Babylon block height whose `app_hash` the packet entries are proven against.
+    #[clap(long)]
+    pub block_height: u64,
+
+    /// Height of the consensus proof covering the block, defaults to `block_height`.
+    #[clap(long)]
+    pub consensus_height: Option<u64>,
+
+    /// File to save the proof to.
+    #[clap(long)]
+    pub output: PathBuf,
+}
+
+impl ProveIbcPacketsCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        let client = Arc::new(HttpClient::new(self.rpc_url.as_str())?);
+        let chain_id = client.genesis::<serde_json::Value>().await?.chain_id;
+        let consensus_proof_path = args.base_path().baby_consensus_proof_path(chain_id);
+
+        let prover = BabyIbcPacketProver::new(client, consensus_proof_path);
+
+        let packets = self
+            .sequences
+            .iter()
+            .map(|&sequence| {
+                let packet_id = PacketId {
+                    port_id: self.port_id.clone(),
+                    channel_id: self.channel_id.clone(),
+                    sequence,
+                };
+                (self.kind.into(), packet_id)
+            })
+            .collect();
+
+        let mut proof = prover
+            .prove(
+                packets,
+                self.block_height,
+                self.consensus_height.unwrap_or(self.block_height),
+            )
+            .await?;
+
+        let output = proof.public_values.read::<IbcPacketOutput>();
+        for packet in &output.packets {
+            tracing::info!(
+                kind = ?packet.kind,
+                port_id = packet.packet_id.port_id,
+                channel_id = packet.packet_id.channel_id,
+                sequence = packet.packet_id.sequence,
+                "Proved IBC packet entry"
+            );
+        }
+
+        proof.save(&self.output)?;
+        println!("Saved proof to {}", self.output.display());
+
+        Ok(())
+    }
+}
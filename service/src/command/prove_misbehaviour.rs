@@ -0,0 +1,70 @@
+use crate::Args;
+use crate::provers::BabyMisbehaviourProver;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tendermint_rpc::{Client, HttpClient};
+use zk_light_client_core::babylon::misbehaviour::MisbehaviourOutput;
+
+#[derive(Parser, Debug)]
+pub struct ProveMisbehaviourCmd {
+    /// The Babylon RPC URL to fetch the trusted block and the first conflicting block from.
+    #[clap(long, default_value = "https://babylon-archive-rpc.polkachu.com")]
+    pub rpc_url: String,
+
+    /// The RPC URL of a node serving the second conflicting block.
+    #[clap(long)]
+    pub witness_rpc_url: String,
+
+    /// Height of the conflicting blocks.
+    #[clap(long)]
+    pub height: u64,
+
+    /// Height of the block the conflicting blocks are verified against.
+    #[clap(long)]
+    pub trusted_height: u64,
+
+    /// Height of the consensus proof covering the trusted block, defaults to `trusted_height`.
+    #[clap(long)]
+    pub consensus_height: Option<u64>,
+
+    /// File to save the proof to.
+    #[clap(long)]
+    pub output: PathBuf,
+}
+
+impl ProveMisbehaviourCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        if self.height <= self.trusted_height {
+            anyhow::bail!("The conflicting blocks must be later than the trusted block");
+        }
+
+        let client = Arc::new(HttpClient::new(self.rpc_url.as_str())?);
+        let witness_client = HttpClient::new(self.witness_rpc_url.as_str())?;
+        let chain_id = client.genesis::<serde_json::Value>().await?.chain_id;
+        let consensus_proof_path = args.base_path().baby_consensus_proof_path(chain_id);
+
+        let prover = BabyMisbehaviourProver::new(client, consensus_proof_path);
+        let mut proof = prover
+            .prove(
+                &witness_client,
+                self.height,
+                self.trusted_height,
+                self.consensus_height.unwrap_or(self.trusted_height),
+            )
+            .await?;
+
+        let output = proof.public_values.read::<MisbehaviourOutput>();
+        tracing::info!(
+            trusted_height = output.trusted_height,
+            evidence_hash = %tendermint::Hash::Sha256(output.evidence_hash),
+            "Proved misbehaviour at block #{}",
+            output.height
+        );
+
+        proof.save(&self.output)?;
+        println!("Saved proof to {}", self.output.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use crate::Args;
+use crate::provers::BabyTxInclusionProver;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tendermint_rpc::{Client, HttpClient};
+
+#[derive(Parser, Debug)]
+pub struct ProveTxInclusionCmd {
+    /// The Babylon RPC URL to fetch the block of the transaction from.
+    #[clap(long, default_value = "https://babylon-archive-rpc.polkachu.com")]
+    pub rpc_url: String,
+
+    /// Hash of the transaction, as shown on explorers.
+    #[clap(long)]
+    pub tx_hash: tendermint::Hash,
+
+    /// The Babylon block height including the transaction.
+    #[clap(long)]
+    pub block_height: u64,
+
+    /// Also prove the result of the transaction, against the header of the next block.
+    #[clap(long)]
+    pub with_result: bool,
+
+    /// Height of the consensus proof covering the block, defaults to the proven block height.
+    #[clap(long)]
+    pub consensus_height: Option<u64>,
+
+    /// File to save the Groth16 proof to.
+    #[clap(long)]
+    pub output: PathBuf,
+}
+
+impl ProveTxInclusionCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        let tendermint::Hash::Sha256(tx_hash) = self.tx_hash else {
+            anyhow::bail!("Transaction hash must not be empty");
+        };
+
+        let client = Arc::new(HttpClient::new(self.rpc_url.as_str())?);
+        let chain_id = client.genesis::<serde_json::Value>().await?.chain_id;
+        let consensus_proof_path = args.base_path().baby_consensus_proof_path(chain_id);
+
+        // The results of a block are committed to by the next header.
+        let proven_height = self.block_height + self.with_result as u64;
+
+        let prover = BabyTxInclusionProver::new(client, consensus_proof_path);
+        let proof = prover
+            .prove(
+                tx_hash,
+                self.block_height,
+                self.with_result,
+                self.consensus_height.unwrap_or(proven_height),
+            )
+            .await?;
+
+        println!("Proving time: {}s", proof.proving_time_secs);
+
+        if !proof.groth16.verify()? {
+            anyhow::bail!("Failed to verify the generated Groth16 proof");
+        }
+
+        std::fs::write(&self.output, serde_json::to_vec(&proof.groth16)?)?;
+        println!("Saved proof to {}", self.output.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use crate::Args;
+use crate::btc::{
+    BitcoinRpc, HeaderStore, proposed_chain_depth, retarget_block_height, spawn_block_event_stream,
+};
+use crate::provers::{BtcConsensusProofEvent, BtcConsensusProver};
+use bitcoin::{Network, ScriptBuf};
+use clap::Parser;
+use sp1_sdk::ProverClient;
+use std::sync::Arc;
+use std::time::Duration;
+use zk_light_client_core::bitcoin::M_CONFIRMATION;
+use zk_light_client_core::bitcoin::signet::{
+    DEFAULT_SIGNET_CHALLENGE, is_supported_signet_challenge,
+};
+
+#[derive(Parser, Debug)]
+pub struct RunCmd {
+    /// The height of the trusted Bitcoin block to start proving from (exclusive).
+    ///
+    /// The first block to be proven will be `initial_height + 1`.
+    #[clap(long)]
+    pub initial_height: u64,
+
+    /// Number of blocks on top of the m-deep block committed by each proof.
+    ///
+    /// Blocks are proven once they have that many confirmations. Must stay the same across
+    /// restarts, as it is committed by every proof of the chain.
+    #[clap(long, default_value_t = M_CONFIRMATION as u32)]
+    pub confirmation_depth: u32,
+
+    /// Hex-encoded challenge script of the signet, defaults to the challenge of the default
+    /// signet when `--btc-network signet` is used.
+    ///
+    /// Must stay the same across restarts, as it is committed by every proof of the chain.
+    #[clap(long, value_parser = ScriptBuf::from_hex)]
+    pub signet_challenge: Option<ScriptBuf>,
+
+    /// Interval in seconds for polling bitcoind when ZMQ is not configured.
+    #[clap(long, default_value_t = 10)]
+    pub poll_interval: u64,
+}
+
+impl RunCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        let poll_interval = Duration::from_secs(self.poll_interval);
+
+        let rpc = args.btc_rpc_client()?;
+
+        let first_height = self.initial_height + 1;
+        let base_height = retarget_block_height(first_height)
+            .min(first_height.saturating_sub(proposed_chain_depth(self.confirmation_depth)));
+
+        let base_path = args.base_path();
+        let mut header_store = HeaderStore::open(
+            base_path.btc_header_store_path(args.btc_network),
+            base_height,
+        )?;
+        let mut prover = BtcConsensusProver::new(
+            self.initial_height,
+            base_path.btc_consensus_proof_path(args.btc_network),
+            self.confirmation_depth,
+            Arc::new(ProverClient::from_env()),
+        );
+        let signet_challenge = match (self.signet_challenge, args.btc_network) {
+            (Some(challenge), Network::Signet) => Some(challenge.into_bytes()),
+            (Some(_), network) => {
+                anyhow::bail!("A signet challenge can not be used on {network}")
+            }
+            (None, Network::Signet) => Some(DEFAULT_SIGNET_CHALLENGE.to_vec()),
+            (None, _) => None,
+        };
+        if let Some(challenge) = signet_challenge {
+            if !is_supported_signet_challenge(&challenge) {
+                anyhow::bail!(
+                    "The signet challenge must be OP_TRUE, pay-to-pubkey or bare multisig"
+                );
+            }
+            prover = prover.with_signet_challenge(challenge);
+        }
+
+        let proof_events = prover.subscribe();
+        tokio::spawn(async move {
+            while let Ok(event) = proof_events.recv().await {
+                match event {
+                    BtcConsensusProofEvent::Proven {
+                        block_height,
+                        block_hash,
+                    } => {
+                        tracing::info!(block_height, %block_hash, "Bitcoin consensus proof generated");
+                    }
+                    BtcConsensusProofEvent::Orphaned {
+                        block_height,
+                        block_hash,
+                    } => {
+                        // TODO: regenerate the inclusion proofs once they are served by the service.
+                        tracing::warn!(
+                            block_height,
+                            %block_hash,
+                            "Bitcoin consensus proof orphaned, dependent inclusion proofs must be regenerated"
+                        );
+                    }
+                }
+            }
+        });
+
+        let block_events =
+            spawn_block_event_stream(args.zmq_endpoint.clone(), rpc.clone(), poll_interval);
+
+        let confirmation_depth = self.confirmation_depth;
+        loop {
+            // The RPC calls and the proving are blocking, keep them off the async runtime.
+            (header_store, prover) = tokio::task::spawn_blocking({
+                let rpc = rpc.clone();
+                move || -> anyhow::Result<_> {
+                    sync_and_prove(
+                        &mut header_store,
+                        &mut prover,
+                        rpc.as_ref(),
+                        confirmation_depth,
+                    )?;
+                    Ok((header_store, prover))
+                }
+            })
+            .await??;
+
+            let event = block_events.recv().await?;
+            tracing::debug!(?event, "Received Bitcoin block event");
+        }
+    }
+}
+
+/// Syncs `header_store` with the best chain and proves the blocks with enough confirmations.
+fn sync_and_prove(
+    header_store: &mut HeaderStore,
+    prover: &mut BtcConsensusProver,
+    rpc: &dyn BitcoinRpc,
+    confirmation_depth: u32,
+) -> anyhow::Result<()> {
+    let outcome = header_store.sync(rpc)?;
+
+    // Also covers the reorgs which happened while the service was not running.
+    prover.rollback_orphaned_proofs(header_store)?;
+
+    // Only blocks with enough confirmations are proven.
+    let provable_height = outcome.tip_height.saturating_sub(confirmation_depth as u64);
+
+    while prover.next_height() <= provable_height {
+        let block_height = prover.next_height();
+        let proving_time_secs = prover.prove_next(header_store, rpc)?;
+        tracing::debug!(block_height, proving_time_secs, "Proved Bitcoin block");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btc::MockBitcoinRpc;
+    use crate::provers::test_prover_client as prover_client;
+
+    const INITIAL_HEIGHT: u64 = 20;
+    const CONFIRMATION_DEPTH: u32 = 3;
+
+    /// Syncs a chain on which only the block after the trusted block is provable, and proves it
+    /// under the signet `challenge` with the prover selected by `SP1_PROVER` (`mock` in CI).
+    fn sync_and_prove_signet(challenge: Vec<u8>) -> (anyhow::Result<()>, BtcConsensusProver) {
+        let header_dir = tempfile::tempdir().unwrap();
+        let proof_dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(INITIAL_HEIGHT + 1 + CONFIRMATION_DEPTH as u64);
+        let mut header_store = HeaderStore::open(header_dir.path().to_path_buf(), 0).unwrap();
+        let mut prover = BtcConsensusProver::new(
+            INITIAL_HEIGHT,
+            proof_dir.path().to_path_buf(),
+            CONFIRMATION_DEPTH,
+            prover_client(),
+        )
+        .with_signet_challenge(challenge);
+
+        let result = sync_and_prove(&mut header_store, &mut prover, &rpc, CONFIRMATION_DEPTH);
+        (result, prover)
+    }
+
+    #[test]
+    fn sync_and_prove_signet_blocks() {
+        // OP_TRUE, satisfied by the blocks of the mock chain.
+        let (result, prover) = sync_and_prove_signet(vec![0x51]);
+        result.unwrap();
+        assert_eq!(prover.next_height(), INITIAL_HEIGHT + 2);
+    }
+
+    #[test]
+    fn sync_and_prove_rejects_unsigned_signet_blocks() {
+        let (result, prover) = sync_and_prove_signet(DEFAULT_SIGNET_CHALLENGE.to_vec());
+        assert!(result.is_err());
+        assert_eq!(prover.next_height(), INITIAL_HEIGHT + 1);
+    }
+}
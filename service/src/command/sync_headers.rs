@@ -0,0 +1,52 @@
+use crate::Args;
+use crate::btc::HeaderStore;
+use clap::Parser;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+pub struct SyncHeadersCmd {
+    /// The height of the first header to store.
+    ///
+    /// Proving a block requires the 11 preceding headers and the retarget block, so this should
+    /// be at least one epoch below the first block to be proven.
+    #[clap(long)]
+    pub base_height: u64,
+
+    /// Keep following the best chain, polling bitcoind at the given interval in seconds.
+    #[clap(long)]
+    pub follow: Option<u64>,
+}
+
+impl SyncHeadersCmd {
+    pub async fn run(self, args: Args) -> anyhow::Result<()> {
+        let rpc = args.btc_rpc_client()?;
+
+        let header_store_path = args.base_path().btc_header_store_path(args.btc_network);
+        let mut header_store = HeaderStore::open(header_store_path, self.base_height)?;
+
+        loop {
+            // The RPC calls are blocking, keep them off the async runtime.
+            let (synced_store, outcome) = tokio::task::spawn_blocking({
+                let rpc = rpc.clone();
+                move || -> anyhow::Result<_> {
+                    let outcome = header_store.sync(rpc.as_ref())?;
+                    Ok((header_store, outcome))
+                }
+            })
+            .await??;
+            header_store = synced_store;
+            tracing::info!(
+                tip_height = outcome.tip_height,
+                reorg_height = ?outcome.reorg_height,
+                "Bitcoin header store is synced"
+            );
+
+            let Some(interval) = self.follow else {
+                break;
+            };
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+
+        Ok(())
+    }
+}
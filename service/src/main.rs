@@ -1,17 +1,33 @@
 mod base_path;
+mod btc;
 mod command;
 mod provers;
 
 use self::base_path::BasePath;
 use clap::{Parser, ValueEnum};
 use std::path::PathBuf;
+use std::sync::Arc;
 use strum::{Display, EnumString};
 
 #[derive(Debug, Parser)]
 pub enum Cmd {
+    /// Run the prover service.
+    Run(self::command::run::RunCmd),
     /// Measure the time for ZK proof generation.
     #[clap(subcommand)]
     Bench(self::command::bench::BenchCmd),
+    /// Sync the Bitcoin headers of the best chain from bitcoind.
+    SyncHeaders(self::command::sync_headers::SyncHeadersCmd),
+    /// Prove the state of a BTC delegation of Babylon.
+    ProveBtcDelegation(self::command::prove_btc_delegation::ProveBtcDelegationCmd),
+    /// Prove the finality of a Babylon block by the finality providers.
+    ProveFinality(self::command::prove_finality::ProveFinalityCmd),
+    /// Prove IBC packet commitments, receipts or acknowledgements of Babylon.
+    ProveIbcPackets(self::command::prove_ibc_packets::ProveIbcPacketsCmd),
+    /// Prove two conflicting Babylon blocks signed at the same height.
+    ProveMisbehaviour(self::command::prove_misbehaviour::ProveMisbehaviourCmd),
+    /// Prove the inclusion of a transaction in a Babylon block.
+    ProveTxInclusion(self::command::prove_tx_inclusion::ProveTxInclusionCmd),
 }
 
 /// Supported SP1 Prover type.
@@ -35,11 +51,15 @@ pub struct Args {
     #[clap(long)]
     pub btc_rpc_auth: Option<String>,
 
+    /// Specify the Bitcoin network of the bitcoind node.
+    #[clap(long, default_value = "bitcoin")]
+    pub btc_network: bitcoin::Network,
+
     /// Specify the endpoint for Bitcoind node's ZMQ service.
     ///
-    /// Example: `tcp:127.0.0.1:28332`
-    // TODO: Add poll-based stream in bitcoin-chain-events so that this argument can be truly
-    // optional.
+    /// Example: `tcp://127.0.0.1:28332`
+    ///
+    /// The RPC is polled for new blocks if not specified.
     #[clap(long)]
     pub zmq_endpoint: Option<String>,
 
@@ -64,6 +84,39 @@ pub struct Args {
 }
 
 impl Args {
+    pub fn btc_rpc_auth(&self) -> anyhow::Result<bitcoincore_rpc::Auth> {
+        let auth = match &self.btc_rpc_auth {
+            Some(auth) => {
+                let auth = auth.split(':').collect::<Vec<_>>();
+                if auth.len() != 2 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid input for --btc-rpc-auth, expected user:password"
+                    ));
+                }
+                bitcoincore_rpc::Auth::UserPass(auth[0].to_string(), auth[1].to_string())
+            }
+            None => bitcoincore_rpc::Auth::None,
+        };
+
+        Ok(auth)
+    }
+
+    pub fn btc_rpc_client(&self) -> anyhow::Result<Arc<bitcoincore_rpc::Client>> {
+        let auth = self.btc_rpc_auth()?;
+
+        let bitcoin_rpc_client =
+            bitcoincore_rpc::Client::new(&self.btc_rpc_url, auth).map_err(|err| {
+                tracing::error!(?err, "Failed to create Bitcoin Core RPC Client");
+                anyhow::anyhow!(
+                    "Failed to create Bitcoin Core RPC Client, url: {:?}, rpcauth: {:?}",
+                    self.btc_rpc_url,
+                    self.btc_rpc_auth
+                )
+            })?;
+
+        Ok(Arc::new(bitcoin_rpc_client))
+    }
+
     pub fn base_path(&self) -> BasePath {
         match &self.base_path {
             Some(path) => BasePath::new(path.to_path_buf()),
@@ -132,9 +185,30 @@ async fn main() -> anyhow::Result<()> {
     };
 
     match cmd {
+        Cmd::Run(run_cmd) => {
+            run_cmd.run(args).await?;
+        }
         Cmd::Bench(bench_cmd) => {
             bench_cmd.run(args).await?;
         }
+        Cmd::SyncHeaders(sync_headers_cmd) => {
+            sync_headers_cmd.run(args).await?;
+        }
+        Cmd::ProveBtcDelegation(prove_btc_delegation_cmd) => {
+            prove_btc_delegation_cmd.run(args).await?;
+        }
+        Cmd::ProveFinality(prove_finality_cmd) => {
+            prove_finality_cmd.run(args).await?;
+        }
+        Cmd::ProveIbcPackets(prove_ibc_packets_cmd) => {
+            prove_ibc_packets_cmd.run(args).await?;
+        }
+        Cmd::ProveMisbehaviour(prove_misbehaviour_cmd) => {
+            prove_misbehaviour_cmd.run(args).await?;
+        }
+        Cmd::ProveTxInclusion(prove_tx_inclusion_cmd) => {
+            prove_tx_inclusion_cmd.run(args).await?;
+        }
     }
 
     Ok(())
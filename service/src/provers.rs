@@ -1,7 +1,14 @@
 mod babylon;
+mod bitcoin;
 
 pub use self::babylon::{
-    ConsensusProver as BabyConsensusProver, MembershipProver as BabyMembershipProver,
+    BtcDelegationProver as BabyBtcDelegationProver, ConsensusProver as BabyConsensusProver,
+    FinalityProver as BabyFinalityProver, IbcPacketProver as BabyIbcPacketProver,
+    MembershipProver as BabyMembershipProver, MisbehaviourProver as BabyMisbehaviourProver,
+    TxInclusionProver as BabyTxInclusionProver,
+};
+pub use self::bitcoin::{
+    ConsensusProofEvent as BtcConsensusProofEvent, ConsensusProver as BtcConsensusProver,
 };
 use ark_bn254::{Bn254, G1Affine, G2Affine};
 use ark_groth16::r1cs_to_qap::LibsnarkReduction;
@@ -9,12 +16,10 @@ use ark_groth16::{Groth16, Proof};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
-use sp1_core_executor::SP1ReduceProof;
 use sp1_sdk::{
     EnvProver, HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1ProvingKey,
-    SP1Stdin, SP1VerifyingKey,
+    SP1Stdin,
 };
-use sp1_stark::baby_bear_poseidon2::BabyBearPoseidon2;
 use sp1_verifier::{
     ArkGroth16Error, decode_sp1_vkey_hash, hash_public_inputs,
     load_ark_groth16_verifying_key_from_bytes, load_ark_proof_from_bytes,
@@ -38,6 +43,8 @@ pub enum ProverError {
     EmptyWitnessChain,
     #[error("Tendermint block hash is invalid: {0}")]
     InvalidTendermintBlockHash(tendermint::Hash),
+    #[error("Reorg below the trusted Bitcoin block at height {0}, the proofs must be regenerated")]
+    ReorgBelowTrustedBlock(u64),
     #[error("Block height too low: the first provable Tendermint block is height 2")]
     BlockHeightTooLowForTendermint,
     #[error("Failed to verify groth16 proof: {0}")]
@@ -51,6 +58,8 @@ pub enum ProverError {
     #[error("other: {0}")]
     Other(String),
     #[error(transparent)]
+    Btc(#[from] crate::btc::BtcError),
+    #[error(transparent)]
     IO(#[from] std::io::Error),
     #[error(transparent)]
     ArkGroth16(#[from] sp1_verifier::ArkGroth16Error),
@@ -138,19 +147,12 @@ fn load_compressed_proof<PV: Serialize + DeserializeOwned>(
     Ok((proof, public_values))
 }
 
-fn aggregate_stark_proofs_to_groth16<T: serde::Serialize>(
-    stark_proofs: Vec<(SP1ReduceProof<BabyBearPoseidon2>, SP1VerifyingKey)>,
-    aggregation_circuit_input: T,
+/// Proves the aggregation program on `stdin`, holding its input and the STARK proofs it
+/// aggregates, as a Groth16 proof.
+fn aggregate_stark_proofs_to_groth16(
+    stdin: &SP1Stdin,
     aggregation_elf: &[u8],
 ) -> Result<(Groth16Proof, u64), ProverError> {
-    let mut stdin = SP1Stdin::new();
-
-    stdin.write(&aggregation_circuit_input);
-
-    for (stark_proof, vkey) in stark_proofs {
-        stdin.write_proof(stark_proof, vkey.vk);
-    }
-
     let client = ProverClient::from_env();
 
     let (aggregation_pkey, aggregation_vkey) = client.setup(aggregation_elf);
@@ -158,7 +160,7 @@ fn aggregate_stark_proofs_to_groth16<T: serde::Serialize>(
     let now = std::time::Instant::now();
 
     let aggregation_proof = client
-        .prove(&aggregation_pkey, &stdin)
+        .prove(&aggregation_pkey, stdin)
         .groth16() // Must use groth16() as this is the only algo supported in BitVM.
         .run()
         .map_err(ProverError::GenerateProof)?;
@@ -247,3 +249,11 @@ impl Groth16Proof {
             .map_err(|e| ProverError::VerifyGroth16Proof(e.to_string()))
     }
 }
+
+/// Returns the prover client shared by the tests, selected by `SP1_PROVER`.
+#[cfg(test)]
+pub(crate) fn test_prover_client() -> std::sync::Arc<EnvProver> {
+    static CLIENT: std::sync::LazyLock<std::sync::Arc<EnvProver>> =
+        std::sync::LazyLock::new(|| std::sync::Arc::new(ProverClient::from_env()));
+    CLIENT.clone()
+}
@@ -3,28 +3,240 @@ use crate::provers::{
     generate_and_save_compressed_proof, generate_compressed_proof, load_compressed_proof,
     load_sp1_proof_and_public_values,
 };
+use bitcoin::hashes::Hash;
 use ibc_core_commitment_types::merkle::MerkleProof;
 use ibc_core_commitment_types::proto::ics23::CommitmentProof;
 use ibc_proto::Protobuf;
+use ibc_proto::cosmos::tx::v1beta1::{TxBody, TxRaw};
 use p3_baby_bear::BabyBear;
 use prost::Message;
+use sp1_core_executor::SP1ReduceProof;
 use sp1_recursion_core::air::RecursionPublicValues;
-use sp1_sdk::{HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1Stdin};
+use sp1_sdk::{
+    EnvProver, HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1Stdin,
+    SP1VerifyingKey,
+};
+use sp1_stark::baby_bear_poseidon2::BabyBearPoseidon2;
 use std::borrow::Borrow;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tendermint::block::Height;
 use tendermint::validator::Set;
 use tendermint_light_client_verifier::types::{LightBlock, PeerId};
 use tendermint_rpc::{Client, HttpClient, Paging};
+use zk_light_client_core::babylon::btcstaking::{BtcDelegationInput, btc_delegation_key_paths};
+use zk_light_client_core::babylon::finality::{FinalityInput, FinalityVote, finality_key_paths};
+use zk_light_client_core::babylon::ibc::{IbcPacketInput, PacketId, PacketKind};
+use zk_light_client_core::babylon::keys::btcstaking::{BtcDelegation, btc_delegation_key_path};
+use zk_light_client_core::babylon::merkle::SimpleMerkleProof;
+use zk_light_client_core::babylon::misbehaviour::MisbehaviourInput;
+use zk_light_client_core::babylon::predicate::Predicate;
+use zk_light_client_core::babylon::tx::{
+    ExecTxResult, TxAggregationInput, TxInclusionInput, TxResultWitness,
+};
 use zk_light_client_core::babylon::{
-    AggregationInput, ConsensusInput, ConsensusWitness, KVPair, MembershipInput, TendermintOutput,
-    VerifierPublicInput, verify_membership_proof,
+    AggregationInput, ConsensusInput, ConsensusWitness, HistoricalHeader, KVPair, MembershipInput,
+    ProvenHeader, TendermintOutput, VerifierPublicInput, verify_membership_proof,
 };
+use zk_light_client_core::bitcoin::to_little_endian_bytes;
+use zk_light_client_core::mmr::MerkleMountainRange;
+use zk_light_client_core::sha256_hash;
 use zk_light_client_programs::{
-    BABY_AGGREGATION_PROGRAM_ELF, BABY_CONSENSUS_PROGRAM_ELF, BABY_MEMBERSHIP_PROGRAM_ELF,
+    BABY_AGGREGATION_PROGRAM_ELF, BABY_BTC_DELEGATION_PROGRAM_ELF, BABY_CONSENSUS_PROGRAM_ELF,
+    BABY_FINALITY_PROGRAM_ELF, BABY_IBC_PACKET_PROGRAM_ELF, BABY_MEMBERSHIP_PROGRAM_ELF,
+    BABY_MISBEHAVIOUR_PROGRAM_ELF, BABY_TX_AGGREGATION_PROGRAM_ELF, BABY_TX_INCLUSION_PROGRAM_ELF,
+    generate_mmr_proof,
 };
 
+/// Header MMR state persisted next to each consensus proof.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct HeaderMmrCheckpoint {
+    /// Header proven by the consensus proof, i.e. the last leaf of `header_mmr`.
+    header: ProvenHeader,
+    /// Header MMR committed by the consensus proof.
+    header_mmr: MerkleMountainRange,
+}
+
+#[inline]
+fn header_mmr_file_path(consensus_proof_path: &Path, block_height: u64) -> PathBuf {
+    consensus_proof_path.join(format!("{block_height}.mmr"))
+}
+
+fn load_header_mmr_checkpoint(
+    consensus_proof_path: &Path,
+    block_height: u64,
+) -> Result<HeaderMmrCheckpoint, ProverError> {
+    let bytes = std::fs::read(header_mmr_file_path(consensus_proof_path, block_height))?;
+    Ok(serde_cbor::from_slice(&bytes)?)
+}
+
+fn save_header_mmr_checkpoint(
+    consensus_proof_path: &Path,
+    checkpoint: &HeaderMmrCheckpoint,
+) -> Result<(), ProverError> {
+    std::fs::write(
+        header_mmr_file_path(consensus_proof_path, checkpoint.header.height),
+        serde_cbor::to_vec(checkpoint)?,
+    )?;
+    Ok(())
+}
+
+/// Generates the proof of the header at `block_height` against the header MMR committed by the
+/// consensus proof at `consensus_height`.
+fn generate_historical_header(
+    consensus_proof_path: &Path,
+    block_height: u64,
+    consensus_height: u64,
+) -> Result<HistoricalHeader, ProverError> {
+    let leaf_count = load_header_mmr_checkpoint(consensus_proof_path, consensus_height)?
+        .header_mmr
+        .leaf_count;
+
+    // TODO: blocks are currently proven one by one, update this once we prove on epoch basis.
+    let first_proven_height = consensus_height + 1 - leaf_count;
+    if block_height < first_proven_height || block_height > consensus_height {
+        return Err(ProverError::Other(format!(
+            "Block #{block_height} is not covered by the consensus proof at #{consensus_height}"
+        )));
+    }
+
+    let headers = (first_proven_height..=consensus_height)
+        .map(|height| {
+            load_header_mmr_checkpoint(consensus_proof_path, height)
+                .map(|checkpoint| checkpoint.header)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let leaves = headers
+        .iter()
+        .map(ProvenHeader::mmr_leaf)
+        .collect::<Vec<_>>();
+    let leaf_index = block_height - first_proven_height;
+
+    Ok(HistoricalHeader {
+        header: headers[leaf_index as usize],
+        header_mmr_proof: generate_mmr_proof(&leaves, leaf_index),
+    })
+}
+
+/// Sanity check: verify that consensus proof vk hash matches expected vk.
+fn check_consensus_vkey(
+    compressed_consensus_proof: &SP1ReduceProof<BabyBearPoseidon2>,
+    consensus_vkey: &SP1VerifyingKey,
+) -> Result<(), ProverError> {
+    let public_values_to_validate: &RecursionPublicValues<BabyBear> = compressed_consensus_proof
+        .proof
+        .public_values
+        .as_slice()
+        .borrow();
+    let vk_digest_in_public_values = public_values_to_validate.sp1_vk_digest;
+    let consensus_vk_hash = consensus_vkey.vk.hash_babybear();
+    // https://github.com/succinctlabs/sp1/blob/7889ae8ba292f916ef1b0dd74735472f19167c80/crates/prover/src/verify.rs#L327
+    if vk_digest_in_public_values != consensus_vk_hash {
+        tracing::error!(
+            ?vk_digest_in_public_values,
+            ?consensus_vk_hash,
+            "sp1 vk hash mismatch"
+        );
+        return Err(ProverError::Sp1VkeyHashMismatch);
+    }
+    Ok(())
+}
+
+/// Fetches the block at `block_height` along with its validators.
+async fn fetch_light_block(
+    client: &HttpClient,
+    block_height: u64,
+) -> Result<LightBlock, ProverError> {
+    let height = Height::from(block_height as u32);
+    let signed_header = client.commit(height).await?.signed_header;
+    let validators = client.validators(height, Paging::All).await?.validators;
+    let next_validators = client
+        .validators(Height::from(block_height as u32 + 1), Paging::All)
+        .await?
+        .validators;
+    Ok(LightBlock {
+        signed_header,
+        validators: Set::new(validators, None),
+        next_validators: Set::new(next_validators, None),
+        // Dummy peer id is fine here, update to proper value when it's not.
+        provider: PeerId::new([0u8; 20]),
+    })
+}
+
+/// Stdin of a program verifying the consensus proof, along with the values its input commits
+/// to.
+///
+/// Only the proofs are written, the input of the program is left to the prover.
+struct ConsensusStdin {
+    stdin: SP1Stdin,
+    /// Header of the block proven against the consensus proof, when it is older than the target
+    /// header of the consensus proof.
+    historical_header: Option<HistoricalHeader>,
+    consensus_vkey_u32_hash: [u32; 8],
+    consensus_public_input: Vec<u8>,
+}
+
+impl ConsensusStdin {
+    /// Writes the consensus proof at `consensus_height`, which must cover the block at
+    /// `block_height`.
+    fn new(
+        client: &EnvProver,
+        consensus_proof_path: &Path,
+        block_height: u64,
+        consensus_height: u64,
+    ) -> Result<Self, ProverError> {
+        let historical_header = if block_height == consensus_height {
+            None
+        } else {
+            Some(generate_historical_header(
+                consensus_proof_path,
+                block_height,
+                consensus_height,
+            )?)
+        };
+
+        let consensus_proof = load_sp1_proof_and_public_values(
+            consensus_proof_path.join(format!("{consensus_height}.bin")),
+        )?;
+        let SP1Proof::Compressed(compressed_consensus_proof) = consensus_proof.proof else {
+            return Err(ProverError::BadProofType);
+        };
+
+        let (_, consensus_vkey) = client.setup(BABY_CONSENSUS_PROGRAM_ELF);
+        check_consensus_vkey(&compressed_consensus_proof, &consensus_vkey)?;
+
+        let consensus_vkey_u32_hash = consensus_vkey.vk.hash_u32();
+        let mut stdin = SP1Stdin::new();
+        stdin.write_proof(*compressed_consensus_proof, consensus_vkey.vk);
+
+        Ok(Self {
+            stdin,
+            historical_header,
+            consensus_vkey_u32_hash,
+            consensus_public_input: consensus_proof.public_values.to_vec(),
+        })
+    }
+
+    /// Writes `proof` of the program `elf`, and returns the hash of its verification key and its
+    /// public values.
+    fn write_proof(
+        &mut self,
+        client: &EnvProver,
+        proof: SP1ProofWithPublicValues,
+        elf: &[u8],
+    ) -> Result<([u32; 8], Vec<u8>), ProverError> {
+        let SP1Proof::Compressed(compressed_proof) = proof.proof else {
+            return Err(ProverError::BadProofType);
+        };
+
+        let (_, vkey) = client.setup(elf);
+        let vkey_u32_hash = vkey.vk.hash_u32();
+        self.stdin.write_proof(*compressed_proof, vkey.vk);
+
+        Ok((vkey_u32_hash, proof.public_values.to_vec()))
+    }
+}
+
 /// Prover for generating consensus proof for Babylon blocks.
 #[derive(Clone)]
 pub struct ConsensusProver {
@@ -53,33 +265,11 @@ impl ConsensusProver {
             return Err(ProverError::BlockHeightTooLowForTendermint);
         }
 
-        let target_block = self.fetch_light_block(block_height).await?;
-        let trusted_block = self.fetch_light_block(block_height - 1).await?;
+        let target_block = fetch_light_block(&self.client, block_height).await?;
+        let trusted_block = fetch_light_block(&self.client, block_height - 1).await?;
         self.prove_from_blocks(target_block, trusted_block)
     }
 
-    async fn fetch_light_block(&self, block_height: u64) -> Result<LightBlock, ProverError> {
-        let height = Height::from(block_height as u32);
-        let signed_header = self.client.commit(height).await?.signed_header;
-        let validators = self
-            .client
-            .validators(height, Paging::All)
-            .await?
-            .validators;
-        let next_validators = self
-            .client
-            .validators(Height::from(block_height as u32 + 1), Paging::All)
-            .await?
-            .validators;
-        Ok(LightBlock {
-            signed_header,
-            validators: Set::new(validators, None),
-            next_validators: Set::new(next_validators, None),
-            // Dummy peer id is fine here, update to proper value when it's not.
-            provider: PeerId::new([0u8; 20]),
-        })
-    }
-
     /// Proves consensus from given blocks.
     fn prove_from_blocks(
         &mut self,
@@ -94,15 +284,22 @@ impl ConsensusProver {
         // TODO: currently the blocks are proved one by one, we should prove them on epoch basis.
         let proving_block_index = target_height - self.initial_height - 1;
 
-        let (parent_public_input, maybe_parent_proof) = if proving_block_index == 0 {
+        let (parent_public_input, maybe_parent_proof, header_mmr) = if proving_block_index == 0 {
             // The first block to be proven does not have a parent proof.
-            (TendermintOutput::default(), None)
+            (
+                TendermintOutput::default(),
+                None,
+                MerkleMountainRange::default(),
+            )
         } else {
             // TODO: calculate the height of last proven block correctly after upgrading to epoch
             // basis or should we store the metadata on disk?
             let (parent_proof, public_input) =
                 load_compressed_proof(self.proof_file_path(target_height - 1))?;
-            (public_input, Some(parent_proof))
+            let header_mmr =
+                load_header_mmr_checkpoint(&self.consensus_proof_path, target_height - 1)?
+                    .header_mmr;
+            (public_input, Some(parent_proof), header_mmr)
         };
 
         let target_header = &target_block.signed_header.header;
@@ -122,6 +319,14 @@ impl ConsensusProver {
         let parent_compressed_block_public_input =
             parent_public_input.compressed_block_public_input;
 
+        let target_proven_header = ProvenHeader {
+            height: target_height,
+            header_hash: target_header_hash,
+            app_hash,
+        };
+        let mut target_header_mmr = header_mmr.clone();
+        target_header_mmr.append(target_proven_header.mmr_leaf());
+
         let circuit_input = ConsensusInput {
             proving_block_index,
             circuit_vkey_u32_hash: vkey.hash_u32(),
@@ -135,6 +340,7 @@ impl ConsensusProver {
             witness: ConsensusWitness {
                 trusted_block,
                 untrusted_block: target_block,
+                header_mmr,
             },
         };
 
@@ -152,6 +358,14 @@ impl ConsensusProver {
             self.proof_file_path(target_height),
         )?;
 
+        save_header_mmr_checkpoint(
+            &self.consensus_proof_path,
+            &HeaderMmrCheckpoint {
+                header: target_proven_header,
+                header_mmr: target_header_mmr,
+            },
+        )?;
+
         Ok(proof_generation_time)
     }
 
@@ -176,10 +390,11 @@ impl BareMembershipProver {
     async fn prove(
         &self,
         key_paths: Vec<Vec<Vec<u8>>>,
+        predicates: Vec<(u32, Predicate)>,
         block_height: u64,
     ) -> Result<SP1ProofWithPublicValues, ProverError> {
         let membership_input = self
-            .prepare_membership_input(key_paths, block_height)
+            .prepare_membership_input(key_paths, predicates, block_height)
             .await?;
         let client = ProverClient::from_env();
         let (pkey, _) = client.setup(BABY_MEMBERSHIP_PROGRAM_ELF);
@@ -192,6 +407,7 @@ impl BareMembershipProver {
     async fn prepare_membership_input(
         &self,
         key_paths: Vec<Vec<Vec<u8>>>,
+        predicates: Vec<(u32, Predicate)>,
         block_height: u64,
     ) -> Result<MembershipInput, ProverError> {
         let merkle_proofs = futures::future::try_join_all(key_paths.into_iter().map(|key_path| {
@@ -226,9 +442,20 @@ impl BareMembershipProver {
         // sanity check.
         verify_membership_proof(app_hash, &merkle_proofs);
 
+        // Sanity check of the predicates, which must refer to the fetched values.
+        for (index, predicate) in &predicates {
+            let (kv_pair, _) = merkle_proofs.get(*index as usize).ok_or_else(|| {
+                ProverError::Other(format!("Predicate refers to missing key path {index}"))
+            })?;
+            predicate
+                .evaluate(&kv_pair.value)
+                .map_err(|err| ProverError::Other(format!("Invalid predicate: {err}")))?;
+        }
+
         Ok(MembershipInput {
             app_hash,
             merkle_proofs,
+            predicates,
         })
     }
 }
@@ -317,79 +544,549 @@ impl MembershipProver {
     }
 
     /// Generates an aggregated Groth16 proof for a set of storage keys at the given block height.
+    ///
+    /// If `predicates` is not empty, only their outcomes are committed instead of the values of
+    /// the keys, each predicate applying to the key path at the given index.
+    ///
+    /// The block at `block_height` must be covered by the consensus proof at `consensus_height`,
+    /// which can be any later proven height.
     pub async fn prove(
         &self,
         key_paths: Vec<Vec<Vec<u8>>>,
+        predicates: Vec<(u32, Predicate)>,
         block_height: u64,
+        consensus_height: u64,
     ) -> Result<MembershipProof, ProverError> {
-        let consensus_proof_file_path = self
-            .consensus_proof_path
-            .join(format!("{block_height}.bin"));
-
-        // Load the consensus proof from disk.
-        let consensus_proof = load_sp1_proof_and_public_values(consensus_proof_file_path)?;
+        let client = ProverClient::from_env();
+        let mut prepared = ConsensusStdin::new(
+            &client,
+            &self.consensus_proof_path,
+            block_height,
+            consensus_height,
+        )?;
 
         // Generate the bare membership proof.
         let bare_membership_proof = self
             .bare_membership_prover
-            .prove(key_paths, block_height)
+            .prove(key_paths, predicates, block_height)
             .await?;
+        let (membership_vkey_u32_hash, membership_public_input) =
+            prepared.write_proof(&client, bare_membership_proof, BABY_MEMBERSHIP_PROGRAM_ELF)?;
 
-        let SP1Proof::Compressed(compressed_bare_membership_proof) = bare_membership_proof.proof
-        else {
-            return Err(ProverError::BadProofType);
+        let aggregation_input = AggregationInput {
+            consensus_vkey_u32_hash: prepared.consensus_vkey_u32_hash,
+            consensus_public_input: prepared.consensus_public_input,
+            membership_vkey_u32_hash,
+            membership_public_input,
+            historical_header: prepared.historical_header,
         };
+        prepared.stdin.write(&aggregation_input);
 
-        let SP1Proof::Compressed(compressed_consensus_proof) = consensus_proof.proof else {
-            return Err(ProverError::BadProofType);
+        // Aggregate both proofs into a Groth16 proof.
+        let (groth16_proof, proving_time_secs) =
+            aggregate_stark_proofs_to_groth16(&prepared.stdin, BABY_AGGREGATION_PROGRAM_ELF)?;
+
+        Ok(MembershipProof {
+            groth16: groth16_proof,
+            proving_time_secs,
+        })
+    }
+}
+
+/// Prover of the state of a BTC delegation of the `btcstaking` module.
+pub struct BtcDelegationProver {
+    client: Arc<HttpClient>,
+    bare_membership_prover: BareMembershipProver,
+    consensus_proof_path: PathBuf,
+}
+
+impl BtcDelegationProver {
+    pub fn new(client: Arc<HttpClient>, consensus_proof_path: PathBuf) -> Self {
+        Self {
+            bare_membership_prover: BareMembershipProver::new(client.clone()),
+            client,
+            consensus_proof_path,
+        }
+    }
+
+    /// Generates a compressed proof of the delegation of `staking_tx_hash` against the `app_hash`
+    /// of the block at `block_height`, which must be covered by the consensus proof at
+    /// `consensus_height`.
+    ///
+    /// The status is derived from the Bitcoin tip and the staking parameters of the delegation,
+    /// proven along with it.
+    pub async fn prove(
+        &self,
+        staking_tx_hash: bitcoin::Txid,
+        block_height: u64,
+        consensus_height: u64,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        let staking_tx_hash = to_little_endian_bytes(staking_tx_hash.to_byte_array());
+
+        // The version of the staking parameters is only known from the delegation.
+        let (delegation, _) = prove_storage_key_existence(
+            &self.client,
+            &btc_delegation_key_path(staking_tx_hash),
+            block_height,
+        )
+        .await?;
+        let params_version = BtcDelegation::decode(delegation.as_slice())?.params_version;
+
+        let client = ProverClient::from_env();
+        let mut prepared = ConsensusStdin::new(
+            &client,
+            &self.consensus_proof_path,
+            block_height,
+            consensus_height,
+        )?;
+
+        let bare_membership_proof = self
+            .bare_membership_prover
+            .prove(
+                btc_delegation_key_paths(staking_tx_hash, params_version),
+                Vec::new(),
+                block_height,
+            )
+            .await?;
+        let (membership_vkey_u32_hash, membership_public_input) =
+            prepared.write_proof(&client, bare_membership_proof, BABY_MEMBERSHIP_PROGRAM_ELF)?;
+
+        let delegation_input = BtcDelegationInput {
+            consensus_vkey_u32_hash: prepared.consensus_vkey_u32_hash,
+            consensus_public_input: prepared.consensus_public_input,
+            membership_vkey_u32_hash,
+            membership_public_input,
+            historical_header: prepared.historical_header,
+            staking_tx_hash,
         };
+        prepared.stdin.write(&delegation_input);
 
-        // Aggregate both proofs into a Groth16 proof.
-        let (groth16_proof, proving_time_secs) = {
-            let client = ProverClient::from_env();
-
-            let (_, consensus_vkey) = client.setup(BABY_CONSENSUS_PROGRAM_ELF);
-            let (_, membership_vkey) = client.setup(BABY_MEMBERSHIP_PROGRAM_ELF);
-
-            let aggregation_input = AggregationInput {
-                consensus_vkey_u32_hash: consensus_vkey.vk.hash_u32(),
-                consensus_public_input: consensus_proof.public_values.to_vec(),
-                membership_vkey_u32_hash: membership_vkey.vk.hash_u32(),
-                membership_public_input: bare_membership_proof.public_values.to_vec(),
-            };
-
-            // Sanity check: verify that consensus proof vk hash matches expected vk.
-            let public_values_to_validate: &RecursionPublicValues<BabyBear> =
-                compressed_consensus_proof
-                    .proof
-                    .public_values
-                    .as_slice()
-                    .borrow();
-            let vk_digest_in_public_values = public_values_to_validate.sp1_vk_digest;
-            let consensus_vk_hash = consensus_vkey.vk.hash_babybear();
-            // https://github.com/succinctlabs/sp1/blob/7889ae8ba292f916ef1b0dd74735472f19167c80/crates/prover/src/verify.rs#L327
-            if vk_digest_in_public_values != consensus_vk_hash {
-                tracing::error!(
-                    ?vk_digest_in_public_values,
-                    ?consensus_vk_hash,
-                    "sp1 vk hash mismatch"
-                );
-                return Err(ProverError::Sp1VkeyHashMismatch);
+        let (pkey, _) = client.setup(BABY_BTC_DELEGATION_PROGRAM_ELF);
+        generate_compressed_proof(&client, &pkey, &prepared.stdin)
+    }
+}
+
+/// Type URL of the finality votes.
+const MSG_ADD_FINALITY_SIG_TYPE_URL: &str = "/babylon.finality.v1.MsgAddFinalitySig";
+
+/// Finality vote message (`MsgAddFinalitySig` in Babylon), reduced to the fields needed to
+/// verify the vote.
+#[derive(Clone, PartialEq, prost::Message)]
+struct MsgAddFinalitySig {
+    #[prost(bytes = "vec", tag = "2")]
+    fp_btc_pk: Vec<u8>,
+    #[prost(uint64, tag = "3")]
+    block_height: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub_rand: Vec<u8>,
+}
+
+/// Prover of the finality of Babylon blocks by the finality providers.
+pub struct FinalityProver {
+    client: Arc<HttpClient>,
+    bare_membership_prover: BareMembershipProver,
+    consensus_proof_path: PathBuf,
+}
+
+impl FinalityProver {
+    pub fn new(client: Arc<HttpClient>, consensus_proof_path: PathBuf) -> Self {
+        Self {
+            bare_membership_prover: BareMembershipProver::new(client.clone()),
+            client,
+            consensus_proof_path,
+        }
+    }
+
+    /// Generates a compressed proof of the finality of the block at `height`.
+    ///
+    /// The votes are proven against the `finality` store at `membership_height`, both blocks
+    /// must be covered by the consensus proof at `consensus_height`.
+    pub async fn prove(
+        &self,
+        height: u64,
+        membership_height: u64,
+        consensus_height: u64,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        let votes = self.fetch_votes(height, membership_height).await?;
+        tracing::debug!(height, votes = votes.len(), "Fetched finality votes");
+
+        let finalized_header =
+            generate_historical_header(&self.consensus_proof_path, height, consensus_height)?;
+        let client = ProverClient::from_env();
+        let mut prepared = ConsensusStdin::new(
+            &client,
+            &self.consensus_proof_path,
+            membership_height,
+            consensus_height,
+        )?;
+
+        let bare_membership_proof = self
+            .bare_membership_prover
+            .prove(
+                finality_key_paths(height, &votes),
+                Vec::new(),
+                membership_height,
+            )
+            .await?;
+        let (membership_vkey_u32_hash, membership_public_input) =
+            prepared.write_proof(&client, bare_membership_proof, BABY_MEMBERSHIP_PROGRAM_ELF)?;
+
+        let finality_input = FinalityInput {
+            consensus_vkey_u32_hash: prepared.consensus_vkey_u32_hash,
+            consensus_public_input: prepared.consensus_public_input,
+            membership_vkey_u32_hash,
+            membership_public_input,
+            historical_header: prepared.historical_header,
+            finalized_header,
+            votes,
+        };
+        prepared.stdin.write(&finality_input);
+
+        let (pkey, _) = client.setup(BABY_FINALITY_PROGRAM_ELF);
+        generate_compressed_proof(&client, &pkey, &prepared.stdin)
+    }
+
+    /// Collects the votes for the block at `height` from the successful finality vote
+    /// transactions of the blocks up to `membership_height`.
+    ///
+    /// The public randomness is not kept in the state, only in the vote transactions.
+    async fn fetch_votes(
+        &self,
+        height: u64,
+        membership_height: u64,
+    ) -> Result<Vec<FinalityVote>, ProverError> {
+        let mut votes = Vec::<FinalityVote>::new();
+
+        for block_height in height + 1..=membership_height {
+            let txs = self.client.block(block_height as u32).await?.block.data;
+            let results = self
+                .client
+                .block_results(block_height as u32)
+                .await?
+                .txs_results
+                .unwrap_or_default();
+
+            for (tx, result) in txs.iter().zip(results) {
+                if result.code.is_err() {
+                    continue;
+                }
+                // Skip the transactions which are not Cosmos SDK transactions.
+                let Ok(tx_raw) = TxRaw::decode(tx.as_slice()) else {
+                    continue;
+                };
+                let Ok(tx_body) = TxBody::decode(tx_raw.body_bytes.as_slice()) else {
+                    continue;
+                };
+
+                for message in tx_body
+                    .messages
+                    .iter()
+                    .filter(|message| message.type_url == MSG_ADD_FINALITY_SIG_TYPE_URL)
+                {
+                    let message = MsgAddFinalitySig::decode(message.value.as_slice())?;
+                    if message.block_height != height {
+                        continue;
+                    }
+                    let (Ok(fp_btc_pk), Ok(pub_rand)) = (
+                        <[u8; 32]>::try_from(message.fp_btc_pk),
+                        <[u8; 32]>::try_from(message.pub_rand),
+                    ) else {
+                        continue;
+                    };
+                    if votes.iter().all(|vote| vote.fp_btc_pk != fp_btc_pk) {
+                        votes.push(FinalityVote {
+                            fp_btc_pk,
+                            pub_rand,
+                        });
+                    }
+                }
             }
+        }
+
+        Ok(votes)
+    }
+}
 
-            aggregate_stark_proofs_to_groth16(
-                vec![
-                    (*compressed_consensus_proof, consensus_vkey),
-                    (*compressed_bare_membership_proof, membership_vkey),
-                ],
-                aggregation_input,
-                BABY_AGGREGATION_PROGRAM_ELF,
-            )?
+/// Prover of IBC packet commitments, receipts and acknowledgements.
+pub struct IbcPacketProver {
+    bare_membership_prover: BareMembershipProver,
+    consensus_proof_path: PathBuf,
+}
+
+impl IbcPacketProver {
+    pub fn new(client: Arc<HttpClient>, consensus_proof_path: PathBuf) -> Self {
+        Self {
+            bare_membership_prover: BareMembershipProver::new(client),
+            consensus_proof_path,
+        }
+    }
+
+    /// Generates a compressed proof of the entries of `packets` against the `app_hash` of the
+    /// block at `block_height`, which must be covered by the consensus proof at
+    /// `consensus_height`.
+    pub async fn prove(
+        &self,
+        packets: Vec<(PacketKind, PacketId)>,
+        block_height: u64,
+        consensus_height: u64,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        let key_paths = packets
+            .iter()
+            .map(|(kind, packet_id)| packet_id.key_path(*kind))
+            .collect();
+
+        let client = ProverClient::from_env();
+        let mut prepared = ConsensusStdin::new(
+            &client,
+            &self.consensus_proof_path,
+            block_height,
+            consensus_height,
+        )?;
+
+        let bare_membership_proof = self
+            .bare_membership_prover
+            .prove(key_paths, Vec::new(), block_height)
+            .await?;
+        let (membership_vkey_u32_hash, membership_public_input) =
+            prepared.write_proof(&client, bare_membership_proof, BABY_MEMBERSHIP_PROGRAM_ELF)?;
+
+        let packet_input = IbcPacketInput {
+            consensus_vkey_u32_hash: prepared.consensus_vkey_u32_hash,
+            consensus_public_input: prepared.consensus_public_input,
+            membership_vkey_u32_hash,
+            membership_public_input,
+            historical_header: prepared.historical_header,
         };
+        prepared.stdin.write(&packet_input);
 
-        Ok(MembershipProof {
-            groth16: groth16_proof,
+        let (pkey, _) = client.setup(BABY_IBC_PACKET_PROGRAM_ELF);
+        generate_compressed_proof(&client, &pkey, &prepared.stdin)
+    }
+}
+
+/// Prover of misbehaviour of the Babylon validators, signing two different blocks at the same
+/// height.
+pub struct MisbehaviourProver {
+    client: Arc<HttpClient>,
+    consensus_proof_path: PathBuf,
+}
+
+impl MisbehaviourProver {
+    pub fn new(client: Arc<HttpClient>, consensus_proof_path: PathBuf) -> Self {
+        Self {
+            client,
+            consensus_proof_path,
+        }
+    }
+
+    /// Generates a compressed proof of the blocks at `height` served by the client and by
+    /// `witness_client`, verified against the block at `trusted_height`, which must be covered
+    /// by the consensus proof at `consensus_height`.
+    pub async fn prove(
+        &self,
+        witness_client: &HttpClient,
+        height: u64,
+        trusted_height: u64,
+        consensus_height: u64,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        let trusted_block = fetch_light_block(&self.client, trusted_height).await?;
+        let conflicting_blocks = [
+            fetch_light_block(&self.client, height).await?,
+            fetch_light_block(witness_client, height).await?,
+        ];
+        if conflicting_blocks[0].signed_header.header.hash()
+            == conflicting_blocks[1].signed_header.header.hash()
+        {
+            return Err(ProverError::Other(format!(
+                "The witness agrees with the block #{height}"
+            )));
+        }
+
+        let client = ProverClient::from_env();
+        let mut prepared = ConsensusStdin::new(
+            &client,
+            &self.consensus_proof_path,
+            trusted_height,
+            consensus_height,
+        )?;
+
+        let misbehaviour_input = MisbehaviourInput {
+            consensus_vkey_u32_hash: prepared.consensus_vkey_u32_hash,
+            consensus_public_input: prepared.consensus_public_input,
+            historical_header: prepared.historical_header,
+            trusted_block,
+            conflicting_blocks,
+        };
+
+        // The light blocks are not supported by bincode, hence CBOR as for the consensus program.
+        prepared
+            .stdin
+            .write_vec(serde_cbor::to_vec(&misbehaviour_input)?);
+
+        let (pkey, _) = client.setup(BABY_MISBEHAVIOUR_PROGRAM_ELF);
+        generate_compressed_proof(&client, &pkey, &prepared.stdin)
+    }
+}
+
+#[derive(Debug)]
+pub struct TxInclusionProof {
+    pub groth16: Groth16Proof,
+    /// Aggregation proof generation time in seconds.
+    pub proving_time_secs: u64,
+}
+
+/// Final prover that aggregates the consensus proof and the inclusion proof of a transaction into
+/// a Groth16 proof.
+pub struct TxInclusionProver {
+    client: Arc<HttpClient>,
+    consensus_proof_path: PathBuf,
+}
+
+impl TxInclusionProver {
+    pub fn new(client: Arc<HttpClient>, consensus_proof_path: PathBuf) -> Self {
+        Self {
+            client,
+            consensus_proof_path,
+        }
+    }
+
+    /// Generates an aggregated Groth16 proof of the inclusion of the transaction `tx_hash` in the
+    /// block at `block_height`, and of its result if `with_result` is set.
+    ///
+    /// The block at `block_height` must be covered by the consensus proof at `consensus_height`,
+    /// or the next block if the result is proven since its header commits to the results.
+    pub async fn prove(
+        &self,
+        tx_hash: [u8; 32],
+        block_height: u64,
+        with_result: bool,
+        consensus_height: u64,
+    ) -> Result<TxInclusionProof, ProverError> {
+        let proven_height = if with_result {
+            block_height + 1
+        } else {
+            block_height
+        };
+        let client = ProverClient::from_env();
+        let mut prepared = ConsensusStdin::new(
+            &client,
+            &self.consensus_proof_path,
+            proven_height,
+            consensus_height,
+        )?;
+
+        let tx_inclusion_proof = self.prove_bare(tx_hash, block_height, with_result).await?;
+        let (tx_inclusion_vkey_u32_hash, tx_inclusion_public_input) =
+            prepared.write_proof(&client, tx_inclusion_proof, BABY_TX_INCLUSION_PROGRAM_ELF)?;
+
+        let aggregation_input = TxAggregationInput {
+            consensus_vkey_u32_hash: prepared.consensus_vkey_u32_hash,
+            consensus_public_input: prepared.consensus_public_input,
+            tx_inclusion_vkey_u32_hash,
+            tx_inclusion_public_input,
+            historical_header: prepared.historical_header,
+        };
+        prepared.stdin.write(&aggregation_input);
+
+        let (groth16, proving_time_secs) =
+            aggregate_stark_proofs_to_groth16(&prepared.stdin, BABY_TX_AGGREGATION_PROGRAM_ELF)?;
+
+        Ok(TxInclusionProof {
+            groth16,
             proving_time_secs,
         })
     }
+
+    /// Fetches the block and proves the inclusion of the transaction against its `data_hash`.
+    ///
+    /// If `with_result` is set, the result of the transaction is proven against the
+    /// `last_results_hash` of the next block.
+    async fn prove_bare(
+        &self,
+        tx_hash: [u8; 32],
+        block_height: u64,
+        with_result: bool,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        let block = self.client.block(block_height as u32).await?.block;
+
+        let index = block
+            .data
+            .iter()
+            .position(|tx| sha256_hash(tx) == tx_hash)
+            .ok_or_else(|| {
+                ProverError::Other(format!(
+                    "Transaction {} not found in block #{block_height}",
+                    tendermint::Hash::Sha256(tx_hash)
+                ))
+            })?;
+
+        let result = if with_result {
+            Some(self.fetch_tx_result(block_height, index).await?)
+        } else {
+            None
+        };
+
+        let input = TxInclusionInput {
+            proof: SimpleMerkleProof::generate(&block.data, index),
+            tx: block.data[index].clone(),
+            header: block.header,
+            result,
+        };
+
+        // sanity check.
+        input.verify();
+
+        let client = ProverClient::from_env();
+        let (pkey, _) = client.setup(BABY_TX_INCLUSION_PROGRAM_ELF);
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(serde_cbor::to_vec(&input)?);
+        generate_compressed_proof(&client, &pkey, &stdin)
+    }
+
+    /// Fetches the result of the transaction at `index` of the block at `block_height` and its
+    /// proof against the `last_results_hash` of the next block.
+    async fn fetch_tx_result(
+        &self,
+        block_height: u64,
+        index: usize,
+    ) -> Result<TxResultWitness, ProverError> {
+        let next_header = self
+            .client
+            .block(block_height as u32 + 1)
+            .await?
+            .block
+            .header;
+
+        let results = self
+            .client
+            .block_results(block_height as u32)
+            .await?
+            .txs_results
+            .unwrap_or_default()
+            .into_iter()
+            .map(|result| ExecTxResult {
+                code: result.code.value(),
+                data: result.data.to_vec(),
+                gas_wanted: result.gas_wanted,
+                gas_used: result.gas_used,
+            })
+            .collect::<Vec<_>>();
+
+        let encoded_results = results
+            .iter()
+            .map(Message::encode_to_vec)
+            .collect::<Vec<_>>();
+
+        let result = results.get(index).cloned().ok_or_else(|| {
+            ProverError::Other(format!(
+                "Result of transaction #{index} not found in block #{block_height}"
+            ))
+        })?;
+
+        Ok(TxResultWitness {
+            next_header,
+            result,
+            proof: SimpleMerkleProof::generate(&encoded_results, index),
+        })
+    }
 }
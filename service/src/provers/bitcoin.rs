@@ -0,0 +1,477 @@
+use crate::btc::{BitcoinRpc, HeaderStore};
+use crate::provers::{ProverError, generate_and_save_compressed_proof, load_compressed_proof};
+use bitcoin::BlockHash;
+use bitcoin::hashes::Hash;
+use sp1_sdk::{EnvProver, HashableKey, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zk_light_client_core::bitcoin::consensus::{ConsensusInput, ConsensusVerifierPublicInput};
+use zk_light_client_core::bitcoin::signet::signet_challenge_hash;
+use zk_light_client_core::bitcoin::to_little_endian_bytes;
+use zk_light_client_core::mmr::MerkleMountainRange;
+use zk_light_client_programs::{BTC_CONSENSUS_PROGRAM_ELF, generate_signet_witness};
+
+/// Header MMR state persisted next to each consensus proof.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct HeaderMmrCheckpoint {
+    /// Little-endian bytes of the block hash proven by the consensus proof.
+    block_hash: [u8; 32],
+    /// Little-endian bytes of the hash of its parent block.
+    prev_block_hash: [u8; 32],
+    /// Header MMR committed by the consensus proof.
+    header_mmr: MerkleMountainRange,
+}
+
+#[inline]
+fn header_mmr_file_path(consensus_proof_path: &Path, block_height: u64) -> PathBuf {
+    consensus_proof_path.join(format!("{block_height}.mmr"))
+}
+
+fn load_header_mmr_checkpoint(
+    consensus_proof_path: &Path,
+    block_height: u64,
+) -> Result<HeaderMmrCheckpoint, ProverError> {
+    let bytes = std::fs::read(header_mmr_file_path(consensus_proof_path, block_height))?;
+    Ok(serde_cbor::from_slice(&bytes)?)
+}
+
+fn save_header_mmr_checkpoint(
+    consensus_proof_path: &Path,
+    block_height: u64,
+    checkpoint: &HeaderMmrCheckpoint,
+) -> Result<(), ProverError> {
+    std::fs::write(
+        header_mmr_file_path(consensus_proof_path, block_height),
+        serde_cbor::to_vec(checkpoint)?,
+    )?;
+    Ok(())
+}
+
+/// Event emitted by the [`ConsensusProver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsensusProofEvent {
+    /// The block was proven.
+    Proven {
+        block_height: u64,
+        block_hash: BlockHash,
+    },
+    /// The proof of the block was removed as the block is no longer in the best chain.
+    ///
+    /// Inclusion proofs relying on this consensus proof must be regenerated.
+    Orphaned {
+        block_height: u64,
+        block_hash: BlockHash,
+    },
+}
+
+/// Converts the little-endian bytes of a block hash (as shown on explorers) to a [`BlockHash`].
+fn to_block_hash(block_hash: [u8; 32]) -> BlockHash {
+    BlockHash::from_byte_array(to_little_endian_bytes(block_hash))
+}
+
+/// Prover for generating consensus proof for Bitcoin blocks.
+pub struct ConsensusProver {
+    /// Height of the trusted block, the first proven block is `initial_height + 1`.
+    initial_height: u64,
+    consensus_proof_path: PathBuf,
+    /// Height of the next block to be proven.
+    next_height: u64,
+    /// Confirmation depth committed by every proof of the chain.
+    confirmation_depth: u32,
+    /// Challenge enforced on every block if the chain is a signet, committed by every proof.
+    signet_challenge: Option<Vec<u8>>,
+    event_sender: Option<async_channel::Sender<ConsensusProofEvent>>,
+    client: Arc<EnvProver>,
+    /// Keys of the consensus program, set up with the first proof.
+    keys: Option<Arc<(SP1ProvingKey, SP1VerifyingKey)>>,
+}
+
+impl ConsensusProver {
+    /// Constructs a new instance of [`ConsensusProver`], resuming after the last proof on disk.
+    pub fn new(
+        initial_height: u64,
+        consensus_proof_path: PathBuf,
+        confirmation_depth: u32,
+        client: Arc<EnvProver>,
+    ) -> Self {
+        let mut prover = Self {
+            initial_height,
+            consensus_proof_path,
+            next_height: initial_height + 1,
+            confirmation_depth,
+            signet_challenge: None,
+            event_sender: None,
+            client,
+            keys: None,
+        };
+        while prover.proof_file_path(prover.next_height).exists()
+            && header_mmr_file_path(&prover.consensus_proof_path, prover.next_height).exists()
+        {
+            prover.next_height += 1;
+        }
+        prover
+    }
+
+    /// Enforces the signet `challenge` on the proven blocks.
+    pub fn with_signet_challenge(mut self, challenge: Vec<u8>) -> Self {
+        self.signet_challenge = Some(challenge);
+        self
+    }
+
+    /// Returns the height of the next block to be proven.
+    pub fn next_height(&self) -> u64 {
+        self.next_height
+    }
+
+    /// Returns a stream of the events emitted from now on.
+    pub fn subscribe(&mut self) -> async_channel::Receiver<ConsensusProofEvent> {
+        let (sender, receiver) = async_channel::unbounded();
+        self.event_sender = Some(sender);
+        receiver
+    }
+
+    fn emit(&self, event: ConsensusProofEvent) {
+        if let Some(sender) = &self.event_sender {
+            // The stream is unbounded, sending only fails once the receiver is dropped.
+            let _ = sender.try_send(event);
+        }
+    }
+
+    /// Removes the proofs of the blocks which are no longer in the best chain of `header_store`.
+    ///
+    /// Each proof commits to the hash of its block, and the consensus program checks that the
+    /// block extends the block of the parent proof, so a proof is orphaned along with all the
+    /// proofs above it. The proofs are removed from the tip down to the fork point, and the blocks
+    /// of the new best chain are proven again by [`Self::prove_next`].
+    ///
+    /// Returns the number of removed proofs, or [`ProverError::ReorgBelowTrustedBlock`] without
+    /// removing any proof if the trusted block itself is no longer in the best chain.
+    pub fn rollback_orphaned_proofs(
+        &mut self,
+        header_store: &HeaderStore,
+    ) -> Result<u64, ProverError> {
+        let tip_height = self.next_height - 1;
+
+        // The whole chain of proofs descends from the trusted block, which can not be re-proven.
+        if tip_height > self.initial_height {
+            let first_checkpoint =
+                load_header_mmr_checkpoint(&self.consensus_proof_path, self.initial_height + 1)?;
+            let trusted_block_hash = header_store
+                .circuit_block(self.initial_height)?
+                .compute_block_hash();
+            if trusted_block_hash != first_checkpoint.prev_block_hash {
+                return Err(ProverError::ReorgBelowTrustedBlock(self.initial_height));
+            }
+        }
+
+        while self.next_height > self.initial_height + 1 {
+            let block_height = self.next_height - 1;
+            let checkpoint = load_header_mmr_checkpoint(&self.consensus_proof_path, block_height)?;
+
+            let best_block_hash = header_store
+                .circuit_block(block_height)
+                .ok()
+                .map(|block| block.compute_block_hash());
+            if best_block_hash == Some(checkpoint.block_hash) {
+                break;
+            }
+
+            std::fs::remove_file(self.proof_file_path(block_height))?;
+            std::fs::remove_file(header_mmr_file_path(
+                &self.consensus_proof_path,
+                block_height,
+            ))?;
+            self.next_height -= 1;
+
+            self.emit(ConsensusProofEvent::Orphaned {
+                block_height,
+                block_hash: to_block_hash(checkpoint.block_hash),
+            });
+        }
+
+        let orphaned = tip_height + 1 - self.next_height;
+        if orphaned > 0 {
+            tracing::warn!(
+                fork_height = self.next_height - 1,
+                orphaned,
+                "Rolled back Bitcoin consensus proofs to the fork point"
+            );
+        }
+
+        Ok(orphaned)
+    }
+
+    /// Proves the consensus for the next block, which must be in `header_store`.
+    ///
+    /// On a signet, the block is downloaded from `rpc` to prove its signet solution.
+    pub fn prove_next(
+        &mut self,
+        header_store: &HeaderStore,
+        rpc: &dyn BitcoinRpc,
+    ) -> Result<u64, ProverError> {
+        let block_height = self.next_height;
+
+        let keys = self
+            .keys
+            .get_or_insert_with(|| Arc::new(self.client.setup(BTC_CONSENSUS_PROGRAM_ELF)))
+            .clone();
+        let (pkey, vkey) = keys.as_ref();
+
+        // TODO: currently the blocks are proved one by one, we should prove them in batches.
+        let seq = block_height - self.initial_height - 1;
+
+        let (parent_proof_commitment, maybe_parent_proof, header_mmr) = if seq == 0 {
+            // The first block to be proven does not have a parent proof.
+            (
+                ConsensusVerifierPublicInput::default(),
+                None,
+                MerkleMountainRange::default(),
+            )
+        } else {
+            let (parent_proof, public_input): (_, ConsensusVerifierPublicInput) =
+                load_compressed_proof(self.proof_file_path(block_height - 1))?;
+            if public_input.confirmation_depth != self.confirmation_depth {
+                return Err(ProverError::Other(format!(
+                    "Confirmation depth {} does not match the parent proof with depth {}",
+                    self.confirmation_depth, public_input.confirmation_depth
+                )));
+            }
+            let signet_challenge_hash = self
+                .signet_challenge
+                .as_deref()
+                .map_or([0u8; 32], signet_challenge_hash);
+            if public_input.signet_challenge_hash != signet_challenge_hash {
+                return Err(ProverError::Other(
+                    "Signet challenge does not match the parent proof".to_string(),
+                ));
+            }
+            let header_mmr =
+                load_header_mmr_checkpoint(&self.consensus_proof_path, block_height - 1)?
+                    .header_mmr;
+            (public_input, Some(parent_proof), header_mmr)
+        };
+
+        let block_public_input =
+            header_store.block_public_input(block_height, self.confirmation_depth)?;
+        if seq != 0
+            && block_public_input.prev_block_hash != parent_proof_commitment.current_block_hash
+        {
+            return Err(ProverError::Other(format!(
+                "Block {block_height} does not extend the tip of the parent proof"
+            )));
+        }
+        let mut witness = header_store.consensus_witness(
+            block_height,
+            self.confirmation_depth,
+            header_mmr.clone(),
+        )?;
+        if self.signet_challenge.is_some() {
+            let block = rpc.get_block(&header_store.header(block_height)?.block_hash())?;
+            witness = witness.with_signet(generate_signet_witness(&block));
+        }
+
+        let mut target_header_mmr = header_mmr;
+        target_header_mmr.append(block_public_input.header_mmr_leaf());
+
+        let circuit_input = ConsensusInput::new(
+            seq as u32,
+            vkey.hash_u32(),
+            parent_proof_commitment,
+            block_public_input,
+            self.confirmation_depth,
+            self.signet_challenge.clone(),
+            witness,
+        );
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&circuit_input);
+
+        if let Some(proof) = maybe_parent_proof {
+            stdin.write_proof(*proof, vkey.vk.clone());
+        }
+
+        let proof_generation_time = generate_and_save_compressed_proof(
+            &self.client,
+            pkey,
+            &stdin,
+            self.proof_file_path(block_height),
+        )?;
+
+        save_header_mmr_checkpoint(
+            &self.consensus_proof_path,
+            block_height,
+            &HeaderMmrCheckpoint {
+                block_hash: block_public_input.proposed_block_hash,
+                prev_block_hash: block_public_input.prev_block_hash,
+                header_mmr: target_header_mmr,
+            },
+        )?;
+
+        self.next_height += 1;
+
+        self.emit(ConsensusProofEvent::Proven {
+            block_height,
+            block_hash: to_block_hash(block_public_input.proposed_block_hash),
+        });
+
+        Ok(proof_generation_time)
+    }
+
+    #[inline]
+    fn proof_file_path(&self, block_height: u64) -> PathBuf {
+        self.consensus_proof_path
+            .join(format!("{block_height}.bin"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btc::MockBitcoinRpc;
+    use crate::provers::test_prover_client as prover_client;
+
+    const INITIAL_HEIGHT: u64 = 20;
+
+    /// Stores fake proofs of the blocks up to `tip_height`, as left by [`ConsensusProver::prove_next`].
+    fn store_proofs(consensus_proof_path: &Path, header_store: &HeaderStore, tip_height: u64) {
+        for block_height in INITIAL_HEIGHT + 1..=tip_height {
+            std::fs::write(
+                consensus_proof_path.join(format!("{block_height}.bin")),
+                b"proof",
+            )
+            .unwrap();
+            save_header_mmr_checkpoint(
+                consensus_proof_path,
+                block_height,
+                &HeaderMmrCheckpoint {
+                    block_hash: header_store
+                        .circuit_block(block_height)
+                        .unwrap()
+                        .compute_block_hash(),
+                    prev_block_hash: header_store
+                        .circuit_block(block_height - 1)
+                        .unwrap()
+                        .compute_block_hash(),
+                    header_mmr: MerkleMountainRange::default(),
+                },
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn rollback_to_the_fork_point() {
+        let header_dir = tempfile::tempdir().unwrap();
+        let proof_dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(30);
+        let mut header_store = HeaderStore::open(header_dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+        store_proofs(proof_dir.path(), &header_store, 27);
+
+        let mut prover = ConsensusProver::new(
+            INITIAL_HEIGHT,
+            proof_dir.path().to_path_buf(),
+            3,
+            prover_client(),
+        );
+        assert_eq!(prover.next_height(), 28);
+        let events = prover.subscribe();
+
+        // Nothing to roll back while the proven blocks are in the best chain.
+        assert_eq!(prover.rollback_orphaned_proofs(&header_store).unwrap(), 0);
+        assert!(events.try_recv().is_err());
+
+        // Blocks 26 to 30 are replaced, the proofs of 26 and 27 are orphaned.
+        let orphaned_hashes =
+            [27, 26].map(|height| header_store.header(height).unwrap().block_hash());
+        rpc.reorg(5);
+        header_store.sync(&rpc).unwrap();
+
+        assert_eq!(prover.rollback_orphaned_proofs(&header_store).unwrap(), 2);
+        assert_eq!(prover.next_height(), 26);
+        for (block_height, block_hash) in [27, 26].into_iter().zip(orphaned_hashes) {
+            assert_eq!(
+                events.try_recv().unwrap(),
+                ConsensusProofEvent::Orphaned {
+                    block_height,
+                    block_hash
+                }
+            );
+            assert!(!prover.proof_file_path(block_height).exists());
+            assert!(!header_mmr_file_path(proof_dir.path(), block_height).exists());
+        }
+        assert!(events.try_recv().is_err());
+        assert!(prover.proof_file_path(25).exists());
+
+        // The prover resumes from the fork point after a restart.
+        let prover = ConsensusProver::new(
+            INITIAL_HEIGHT,
+            proof_dir.path().to_path_buf(),
+            3,
+            prover_client(),
+        );
+        assert_eq!(prover.next_height(), 26);
+    }
+
+    #[test]
+    fn rollback_stops_at_the_trusted_block() {
+        let header_dir = tempfile::tempdir().unwrap();
+        let proof_dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(30);
+        let mut header_store = HeaderStore::open(header_dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+        store_proofs(proof_dir.path(), &header_store, 23);
+
+        let mut prover = ConsensusProver::new(
+            INITIAL_HEIGHT,
+            proof_dir.path().to_path_buf(),
+            3,
+            prover_client(),
+        );
+        let events = prover.subscribe();
+
+        // The blocks above the trusted block are replaced.
+        rpc.reorg(30 - INITIAL_HEIGHT);
+        header_store.sync(&rpc).unwrap();
+
+        assert_eq!(prover.rollback_orphaned_proofs(&header_store).unwrap(), 3);
+        assert_eq!(prover.next_height(), INITIAL_HEIGHT + 1);
+        let heights = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| match event {
+                ConsensusProofEvent::Orphaned { block_height, .. } => block_height,
+                event => panic!("Unexpected event {event:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(heights, [23, 22, 21]);
+    }
+
+    #[test]
+    fn rollback_rejects_a_reorg_of_the_trusted_block() {
+        let header_dir = tempfile::tempdir().unwrap();
+        let proof_dir = tempfile::tempdir().unwrap();
+        let rpc = MockBitcoinRpc::new(30);
+        let mut header_store = HeaderStore::open(header_dir.path().to_path_buf(), 0).unwrap();
+        header_store.sync(&rpc).unwrap();
+        store_proofs(proof_dir.path(), &header_store, 23);
+
+        let mut prover = ConsensusProver::new(
+            INITIAL_HEIGHT,
+            proof_dir.path().to_path_buf(),
+            3,
+            prover_client(),
+        );
+        let events = prover.subscribe();
+
+        rpc.reorg(15);
+        header_store.sync(&rpc).unwrap();
+
+        assert!(matches!(
+            prover.rollback_orphaned_proofs(&header_store),
+            Err(ProverError::ReorgBelowTrustedBlock(INITIAL_HEIGHT))
+        ));
+        // No proof is removed, the prover can not resume on this chain.
+        assert_eq!(prover.next_height(), 24);
+        assert!(prover.proof_file_path(23).exists());
+        assert!(events.try_recv().is_err());
+    }
+}